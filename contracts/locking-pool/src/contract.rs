@@ -1,9 +1,22 @@
 use crate::errors::ContractErrors;
 use crate::storage::core::{CoreDataKeys, CoreStorageFunc};
 use crate::storage::deposits::{Deposit, DepositsStorageFunc};
-use crate::storage::pools::{Pool, PoolsDataFunc};
+use crate::storage::pools::{
+    Pool, PoolsDataFunc, RewardStream, FACTOR_PRECISION, MAX_DISTRIBUTORS, MAX_REWARD_ASSETS,
+};
+use crate::storage::voting::{VotingStorageFunc, MAX_VOTING_LOCK};
 use crate::utils::core::validate;
-use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, BytesN, Env, Vec};
+use crate::utils::rewards::{
+    accrue_rewards, add_dust, add_reward, calculate_rewards, calculate_weight, record_distribution,
+    send_rewards, settle_rewards, shares_for_amount, shares_value, validate_distributor,
+};
+use crate::utils::voting::{
+    account_voting_power, checkpoint_lock, checkpoint_total, total_voting_power, voting_lock,
+};
+use crate::utils::withdrawals::{calculate_penalty, withdraw_deposit};
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, token, Address, BytesN, Env, Map, Vec,
+};
 
 pub trait LockingPoolContractTrait {
     fn init(e: Env, admin: Address, manager: Address, reward_asset: Address);
@@ -15,9 +28,47 @@ pub trait LockingPoolContractTrait {
     fn toggle_pool(e: Env, deposit_asset: Address, status: bool);
     fn remove_pool(e: Env, deposit_asset: Address);
     fn migrate_deposits(e: Env, old_asset: Address, new_asset: Address, depositors: Vec<Address>);
-    fn deposit(e: Env, deposit_asset: Address, caller: Address, amount: u128);
+    fn set_lock_boost(e: Env, deposit_asset: Address, max_lock_period: u64, max_boost: u128);
+    fn deposit(e: Env, deposit_asset: Address, caller: Address, amount: u128, lock_duration: u64);
     fn withdraw(e: Env, deposit_asset: Address, caller: Address);
-    fn distribute(e: Env, caller: Address, deposit_asset: Address, amount: u128);
+    fn set_early_withdraw(e: Env, deposit_asset: Address, penalty: u128, treasury: Option<Address>);
+    fn early_withdraw(e: Env, deposit_asset: Address, caller: Address);
+    fn add_reward_asset(e: Env, deposit_asset: Address, reward_asset: Address);
+    fn distribute(
+        e: Env,
+        caller: Address,
+        deposit_asset: Address,
+        reward_asset: Address,
+        amount: u128,
+    );
+    fn fund_rewards(
+        e: Env,
+        caller: Address,
+        deposit_asset: Address,
+        reward_asset: Address,
+        amount: u128,
+        duration: u64,
+    );
+    fn emission_rate(e: Env, deposit_asset: Address, reward_asset: Address) -> u128;
+    fn set_distribution_config(
+        e: Env,
+        deposit_asset: Address,
+        min_distribution: u128,
+        distributors: Vec<Address>,
+    );
+    fn distributed(e: Env, deposit_asset: Address) -> Map<Address, u128>;
+    fn claim(e: Env, deposit_asset: Address, caller: Address);
+    fn pending_rewards(e: Env, deposit_asset: Address, depositor: Address) -> Map<Address, u128>;
+    fn set_compounding(e: Env, deposit_asset: Address, enabled: bool);
+    fn deposit_balance(e: Env, deposit_asset: Address, depositor: Address) -> u128;
+    fn sweep_dust(e: Env, deposit_asset: Address, reward_asset: Address, to: Address) -> u128;
+    fn dust(e: Env, deposit_asset: Address, reward_asset: Address) -> u128;
+    fn set_voting_weight(e: Env, deposit_asset: Address, weight: u128);
+    fn voting_weight(e: Env, deposit_asset: Address) -> u128;
+    fn checkpoint(e: Env, account: Address, deposit_asset: Address);
+    fn checkpoint_total(e: Env, max_weeks: u32) -> u64;
+    fn voting_power(e: Env, account: Address, timestamp: u64) -> u128;
+    fn total_voting_power(e: Env, timestamp: u64) -> u128;
 }
 
 #[contract]
@@ -72,19 +123,49 @@ impl LockingPoolContractTrait for LockingPoolContract {
                 asset: pool.asset,
                 balance: pool.balance,
                 deposits: pool.deposits,
-                factor: pool.factor,
+                principal: pool.principal,
+                shares: pool.shares,
+                compounding: pool.compounding,
+                factors: pool.factors,
+                remainders: pool.remainders,
+                dust: pool.dust,
+                streams: pool.streams,
+                min_distribution: pool.min_distribution,
+                distributors: pool.distributors,
+                distributed: pool.distributed,
                 lock_period,
+                max_lock_period: pool.max_lock_period,
+                max_boost: pool.max_boost,
                 min_deposit,
+                early_withdraw_penalty: pool.early_withdraw_penalty,
+                penalty_treasury: pool.penalty_treasury,
             };
         } else {
+            // New pools start with the default rewards asset, more assets can be added with `add_reward_asset`
+            let mut factors: Map<Address, u128> = Map::new(&e);
+            factors.set(e._core().address(&CoreDataKeys::RewardsAsset).unwrap(), 0);
+
             new_pool = Pool {
                 active: false,
                 asset: deposit_asset,
                 balance: 0,
                 deposits: 0,
-                factor: 0,
+                principal: 0,
+                shares: 0,
+                compounding: false,
+                factors,
+                remainders: Map::new(&e),
+                dust: Map::new(&e),
+                streams: Map::new(&e),
+                min_distribution: 100_0000000,
+                distributors: Vec::new(&e),
+                distributed: Map::new(&e),
                 lock_period,
+                max_lock_period: lock_period,
+                max_boost: 1_0000000,
                 min_deposit,
+                early_withdraw_penalty: 0,
+                penalty_treasury: None,
             };
         }
 
@@ -104,13 +185,29 @@ impl LockingPoolContractTrait for LockingPoolContract {
             asset: new_asset,
             balance: existing_pool.balance,
             deposits: existing_pool.deposits,
-            factor: existing_pool.factor,
+            principal: existing_pool.principal,
+            shares: existing_pool.shares,
+            compounding: existing_pool.compounding,
+            factors: existing_pool.factors,
+            remainders: existing_pool.remainders,
+            dust: Map::new(&e),
+            // The funded emissions stay in the existing pool, otherwise both pools would pay them
+            streams: Map::new(&e),
+            min_distribution: existing_pool.min_distribution,
+            distributors: existing_pool.distributors,
+            distributed: Map::new(&e),
             lock_period: existing_pool.lock_period,
+            max_lock_period: existing_pool.max_lock_period,
+            max_boost: existing_pool.max_boost,
+            early_withdraw_penalty: existing_pool.early_withdraw_penalty,
+            penalty_treasury: existing_pool.penalty_treasury,
             min_deposit: existing_pool.min_deposit,
         };
 
         e._pools().set_pool(&new_pool);
         e._pools().bump_pool(&new_pool.asset);
+        e._voting()
+            .set_pool_weight(&new_pool.asset, e._voting().pool_weight(&existing_asset));
         e._core().bump();
     }
 
@@ -166,10 +263,44 @@ impl LockingPoolContractTrait for LockingPoolContract {
             e._deposits().set(&new_asset, &depositor, &old_deposit);
             e._deposits().bump(&new_asset, &depositor);
             e._deposits().remove(&old_asset, &depositor);
+
+            checkpoint_lock(&e, &depositor, &old_asset, &None);
+            checkpoint_lock(
+                &e,
+                &depositor,
+                &new_asset,
+                &voting_lock(&e, &new_asset, &old_deposit),
+            );
+        }
+    }
+
+    // Lets the deposits of the pool be locked for longer than the `lock_period` in exchange of a bigger share of the
+    // rewards, the new values don't affect the weight of the current deposits. Compounding pools can't have a boost
+    // because the compounded rewards are shared by the amount deposited, not by the weight.
+    fn set_lock_boost(e: Env, deposit_asset: Address, max_lock_period: u64, max_boost: u128) {
+        validate(&e, CoreDataKeys::Manager);
+
+        let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        if max_lock_period < pool.lock_period
+            || max_lock_period > MAX_VOTING_LOCK
+            || max_boost < 1_0000000
+            || (pool.compounding && max_boost > 1_0000000)
+        {
+            panic_with_error!(&e, &ContractErrors::InvalidLockConfig);
         }
+
+        pool.max_lock_period = max_lock_period;
+        pool.max_boost = max_boost;
+
+        e._pools().set_pool(&pool);
+        e._pools().bump_pool(&pool.asset);
+        e._core().bump();
     }
 
-    fn deposit(e: Env, deposit_asset: Address, caller: Address, amount: u128) {
+    fn deposit(e: Env, deposit_asset: Address, caller: Address, amount: u128, lock_duration: u64) {
         caller.require_auth();
 
         let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
@@ -184,10 +315,14 @@ impl LockingPoolContractTrait for LockingPoolContract {
             panic_with_error!(&e, &ContractErrors::InvalidDepositAmount);
         }
 
-        if e._deposits().get(&deposit_asset, &caller).is_some() {
-            panic_with_error!(&e, &ContractErrors::DepositAlreadyExists);
+        if lock_duration < pool.lock_period
+            || lock_duration > pool.max_lock_period.max(pool.lock_period)
+        {
+            panic_with_error!(&e, &ContractErrors::InvalidLockDuration);
         }
 
+        accrue_rewards(&e, &mut pool);
+
         let result = token::Client::new(&e, &pool.asset).try_transfer(
             &caller,
             &e.current_contract_address(),
@@ -198,18 +333,55 @@ impl LockingPoolContractTrait for LockingPoolContract {
             panic_with_error!(&e, &ContractErrors::FundsDepositFailed);
         }
 
-        pool.deposits += 1;
-        pool.balance += amount;
+        let unlocks_at: u64 = e.ledger().timestamp() + lock_duration;
+
+        // A deposit made on top of an existing one pays the pending rewards and restarts the lock of the whole amount,
+        // the new lock can't end before the current one
+        let (current_amount, current_shares): (u128, u128) =
+            match e._deposits().get(&deposit_asset, &caller) {
+                None => {
+                    pool.deposits += 1;
+                    (0, 0)
+                }
+                Some(current_deposit) => {
+                    if unlocks_at < current_deposit.unlocks_at {
+                        panic_with_error!(&e, &ContractErrors::InvalidLockDuration);
+                    }
+
+                    let rewards: Map<Address, u128> =
+                        settle_rewards(&e, &mut pool, &current_deposit);
+                    send_rewards(&e, &caller, &rewards);
+                    pool.balance -= current_deposit.weight;
+                    (current_deposit.amount, current_deposit.shares)
+                }
+            };
+
+        let shares: u128 = shares_for_amount(&pool, amount);
+        pool.principal += amount;
+        pool.shares += shares;
+
+        let weight: u128 = calculate_weight(&pool, current_amount + amount, lock_duration);
+        pool.balance += weight;
 
         let deposit: Deposit = Deposit {
-            amount,
-            snapshot: pool.factor,
-            unlocks_at: e.ledger().timestamp() + pool.lock_period,
+            amount: current_amount + amount,
+            shares: current_shares + shares,
+            weight,
+            snapshots: pool.factors.clone(),
+            locked_at: e.ledger().timestamp(),
+            unlocks_at,
         };
 
         e._deposits().set(&deposit_asset, &caller, &deposit);
         e._deposits().bump(&deposit_asset, &caller);
 
+        checkpoint_lock(
+            &e,
+            &caller,
+            &deposit_asset,
+            &voting_lock(&e, &deposit_asset, &deposit),
+        );
+
         e._pools().set_pool(&pool);
         e._pools().bump_pool(&pool.asset);
         e._core().bump();
@@ -233,68 +405,423 @@ impl LockingPoolContractTrait for LockingPoolContract {
             panic_with_error!(&e, &ContractErrors::DepositIsStillLocked);
         }
 
-        e._deposits().remove(&deposit_asset, &caller);
+        withdraw_deposit(&e, &mut pool, &caller, &deposit, 0);
 
-        let reward: u128 = (deposit.amount * (pool.factor - deposit.snapshot)) / 1_0000000;
+        e._pools().set_pool(&pool);
+        e._pools().bump_pool(&pool.asset);
+        e._core().bump();
+    }
 
-        pool.deposits -= 1;
-        pool.balance -= deposit.amount;
+    // Allows withdrawing deposits before they unlock paying a penalty, if there is no treasury the penalty is shared
+    // with the remaining depositors so the deposit asset is added as a reward asset of the pool
+    fn set_early_withdraw(
+        e: Env,
+        deposit_asset: Address,
+        penalty: u128,
+        treasury: Option<Address>,
+    ) {
+        validate(&e, CoreDataKeys::Manager);
 
-        if pool.deposits == 0 && pool.balance == 0 {
-            pool.factor = 0;
-        }
+        let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
 
-        if reward > 0 {
-            let result =
-                token::Client::new(&e, &e._core().address(&CoreDataKeys::RewardsAsset).unwrap())
-                    .try_transfer(&e.current_contract_address(), &caller, &(reward as i128));
+        if penalty > 1_0000000 {
+            panic_with_error!(&e, &ContractErrors::InvalidPenaltyConfig);
+        }
 
-            if result.is_err() {
-                panic_with_error!(&e, &ContractErrors::RewardsWithdrawFailed);
+        if treasury.is_none() && !pool.factors.contains_key(pool.asset.clone()) {
+            if pool.factors.len() >= MAX_REWARD_ASSETS {
+                panic_with_error!(&e, &ContractErrors::InvalidPenaltyConfig);
             }
+
+            pool.factors.set(pool.asset.clone(), 0);
         }
 
-        let result = token::Client::new(&e, &pool.asset).try_transfer(
-            &e.current_contract_address(),
-            &caller,
-            &(deposit.amount as i128),
-        );
+        pool.early_withdraw_penalty = penalty;
+        pool.penalty_treasury = treasury;
 
-        if result.is_err() {
-            panic_with_error!(&e, &ContractErrors::FundsWithdrawFailed);
+        e._pools().set_pool(&pool);
+        e._pools().bump_pool(&pool.asset);
+        e._core().bump();
+    }
+
+    fn early_withdraw(e: Env, deposit_asset: Address, caller: Address) {
+        caller.require_auth();
+
+        let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        if pool.early_withdraw_penalty == 0 {
+            panic_with_error!(&e, &ContractErrors::EarlyWithdrawIsDisabled);
         }
 
+        let deposit: Deposit = e
+            ._deposits()
+            .get(&deposit_asset, &caller)
+            .unwrap_or_else(|| {
+                panic_with_error!(&e, &ContractErrors::DepositDoesntExist);
+            });
+
+        accrue_rewards(&e, &mut pool);
+        let penalty: u128 = calculate_penalty(&e, &pool, &deposit);
+        withdraw_deposit(&e, &mut pool, &caller, &deposit, penalty);
+
         e._pools().set_pool(&pool);
         e._pools().bump_pool(&pool.asset);
         e._core().bump();
     }
 
-    fn distribute(e: Env, caller: Address, deposit_asset: Address, amount: u128) {
+    fn add_reward_asset(e: Env, deposit_asset: Address, reward_asset: Address) {
+        validate(&e, CoreDataKeys::Manager);
+
+        let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        if pool.factors.contains_key(reward_asset.clone())
+            || pool.factors.len() >= MAX_REWARD_ASSETS
+        {
+            panic_with_error!(&e, &ContractErrors::InvalidRewardAsset);
+        }
+
+        // Current deposits don't have a snapshot of the new asset so its factor must start at 0
+        pool.factors.set(reward_asset, 0);
+
+        e._pools().set_pool(&pool);
+        e._pools().bump_pool(&pool.asset);
+        e._core().bump();
+    }
+
+    fn distribute(
+        e: Env,
+        caller: Address,
+        deposit_asset: Address,
+        reward_asset: Address,
+        amount: u128,
+    ) {
         caller.require_auth();
 
-        if amount < 100_0000000 {
+        let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        validate_distributor(&e, &pool, &caller);
+
+        if amount < pool.min_distribution || pool.balance == 0 {
             panic_with_error!(&e, &ContractErrors::CantDistributeReward);
         }
 
+        if !pool.factors.contains_key(reward_asset.clone()) {
+            panic_with_error!(&e, &ContractErrors::RewardAssetDoesntExist);
+        }
+
+        let result = token::Client::new(&e, &reward_asset).try_transfer(
+            &caller,
+            &e.current_contract_address(),
+            &(amount as i128),
+        );
+
+        if result.is_err() {
+            panic_with_error!(&e, &ContractErrors::RewardsDepositFailed);
+        }
+
+        add_reward(&mut pool, &reward_asset, amount);
+        record_distribution(&mut pool, &reward_asset, amount);
+        e._pools().set_pool(&pool);
+        e._pools().bump_pool(&pool.asset);
+        e._core().bump();
+    }
+
+    // Funds a reward schedule of `amount` emitted linearly over `duration` seconds, the rewards that are still
+    // pending from the current schedule are added to the new one.
+    fn fund_rewards(
+        e: Env,
+        caller: Address,
+        deposit_asset: Address,
+        reward_asset: Address,
+        amount: u128,
+        duration: u64,
+    ) {
+        caller.require_auth();
+
         let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
             panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
         });
 
-        if pool.balance == 0 {
-            panic_with_error!(&e, &ContractErrors::CantDistributeReward);
+        validate_distributor(&e, &pool, &caller);
+
+        if !pool.factors.contains_key(reward_asset.clone()) {
+            panic_with_error!(&e, &ContractErrors::RewardAssetDoesntExist);
         }
 
-        let result =
-            token::Client::new(&e, &e._core().address(&CoreDataKeys::RewardsAsset).unwrap())
-                .try_transfer(&caller, &e.current_contract_address(), &(amount as i128));
+        accrue_rewards(&e, &mut pool);
+
+        let now: u64 = e.ledger().timestamp();
+        let leftover: u128 = match pool.streams.get(reward_asset.clone()) {
+            Some(stream) if stream.period_finish > now => {
+                stream.rate * ((stream.period_finish - now) as u128)
+            }
+            _ => 0,
+        };
+
+        if duration == 0 || (amount + leftover) / (duration as u128) == 0 {
+            panic_with_error!(&e, &ContractErrors::InvalidRewardSchedule);
+        }
+
+        let result = token::Client::new(&e, &reward_asset).try_transfer(
+            &caller,
+            &e.current_contract_address(),
+            &(amount as i128),
+        );
 
         if result.is_err() {
             panic_with_error!(&e, &ContractErrors::RewardsDepositFailed);
         }
 
-        pool.factor += (amount * 1_0000000) / pool.balance;
+        // The part of the schedule that doesn't fit in the rate is never emitted
+        record_distribution(&mut pool, &reward_asset, amount);
+        add_dust(
+            &mut pool,
+            &reward_asset,
+            ((amount + leftover) % (duration as u128)) * FACTOR_PRECISION,
+        );
+        pool.streams.set(
+            reward_asset,
+            RewardStream {
+                rate: (amount + leftover) / (duration as u128),
+                period_finish: now + duration,
+                last_update: now,
+            },
+        );
+
+        e._pools().set_pool(&pool);
+        e._pools().bump_pool(&pool.asset);
+        e._core().bump();
+    }
+
+    fn emission_rate(e: Env, deposit_asset: Address, reward_asset: Address) -> u128 {
+        let pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        match pool.streams.get(reward_asset) {
+            Some(stream) if stream.period_finish > e.ledger().timestamp() => stream.rate,
+            _ => 0,
+        }
+    }
+
+    // Sets the min amount of a distribution and the addresses allowed to send rewards to the pool, an empty list lets
+    // anyone send rewards
+    fn set_distribution_config(
+        e: Env,
+        deposit_asset: Address,
+        min_distribution: u128,
+        distributors: Vec<Address>,
+    ) {
+        validate(&e, CoreDataKeys::Manager);
+
+        let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        if min_distribution == 0 || distributors.len() > MAX_DISTRIBUTORS {
+            panic_with_error!(&e, &ContractErrors::InvalidDistributionConfig);
+        }
+
+        pool.min_distribution = min_distribution;
+        pool.distributors = distributors;
+
+        e._pools().set_pool(&pool);
+        e._pools().bump_pool(&pool.asset);
+        e._core().bump();
+    }
+
+    fn distributed(e: Env, deposit_asset: Address) -> Map<Address, u128> {
+        let pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        pool.distributed
+    }
+
+    // Pays the rewards of the deposit in all the reward assets of the pool while keeping the principal locked
+    fn claim(e: Env, deposit_asset: Address, caller: Address) {
+        caller.require_auth();
+
+        let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        let mut deposit: Deposit =
+            e._deposits()
+                .get(&deposit_asset, &caller)
+                .unwrap_or_else(|| {
+                    panic_with_error!(&e, &ContractErrors::DepositDoesntExist);
+                });
+
+        accrue_rewards(&e, &mut pool);
+        let rewards: Map<Address, u128> = settle_rewards(&e, &mut pool, &deposit);
+        deposit.snapshots = pool.factors.clone();
+
+        e._deposits().set(&deposit_asset, &caller, &deposit);
+        e._deposits().bump(&deposit_asset, &caller);
+
+        send_rewards(&e, &caller, &rewards);
+
         e._pools().set_pool(&pool);
         e._pools().bump_pool(&pool.asset);
         e._core().bump();
     }
+
+    fn pending_rewards(e: Env, deposit_asset: Address, depositor: Address) -> Map<Address, u128> {
+        let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        let deposit: Deposit = e
+            ._deposits()
+            .get(&deposit_asset, &depositor)
+            .unwrap_or_else(|| {
+                panic_with_error!(&e, &ContractErrors::DepositDoesntExist);
+            });
+
+        // The pool is not saved, this only includes the rewards streamed since the last update
+        accrue_rewards(&e, &mut pool);
+        calculate_rewards(&e, &pool, &deposit)
+    }
+
+    // Compounding can only be enabled if the deposit asset is one of the reward assets of the pool and the pool has no
+    // lock boost, the compounded rewards are shared by the amount deposited so a boost wouldn't apply to them
+    fn set_compounding(e: Env, deposit_asset: Address, enabled: bool) {
+        validate(&e, CoreDataKeys::Manager);
+
+        let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        if enabled && (!pool.factors.contains_key(pool.asset.clone()) || pool.max_boost > 1_0000000)
+        {
+            panic_with_error!(&e, &ContractErrors::InvalidCompoundingConfig);
+        }
+
+        // The rewards streamed until now are added with the previous mode
+        accrue_rewards(&e, &mut pool);
+        pool.compounding = enabled;
+
+        e._pools().set_pool(&pool);
+        e._pools().bump_pool(&pool.asset);
+        e._core().bump();
+    }
+
+    // The amount the depositor would receive when withdrawing the deposit, including the compounded rewards
+    fn deposit_balance(e: Env, deposit_asset: Address, depositor: Address) -> u128 {
+        let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        let deposit: Deposit = e
+            ._deposits()
+            .get(&deposit_asset, &depositor)
+            .unwrap_or_else(|| {
+                panic_with_error!(&e, &ContractErrors::DepositDoesntExist);
+            });
+
+        accrue_rewards(&e, &mut pool);
+        shares_value(&pool, deposit.shares)
+    }
+
+    // Sends the whole units of the dust of the reward asset to `to`, the fraction that is left stays in the dust
+    fn sweep_dust(e: Env, deposit_asset: Address, reward_asset: Address, to: Address) -> u128 {
+        validate(&e, CoreDataKeys::Manager);
+
+        let mut pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        let dust: u128 = pool.dust.get(reward_asset.clone()).unwrap_or(0);
+        let amount: u128 = dust / FACTOR_PRECISION;
+        if amount == 0 {
+            return 0;
+        }
+
+        pool.dust
+            .set(reward_asset.clone(), dust - amount * FACTOR_PRECISION);
+
+        e._pools().set_pool(&pool);
+        e._pools().bump_pool(&pool.asset);
+        e._core().bump();
+
+        let result = token::Client::new(&e, &reward_asset).try_transfer(
+            &e.current_contract_address(),
+            &to,
+            &(amount as i128),
+        );
+
+        if result.is_err() {
+            panic_with_error!(&e, &ContractErrors::RewardsWithdrawFailed);
+        }
+
+        amount
+    }
+
+    fn dust(e: Env, deposit_asset: Address, reward_asset: Address) -> u128 {
+        let pool: Pool = e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        pool.dust.get(reward_asset).unwrap_or(0) / FACTOR_PRECISION
+    }
+
+    // The voting power a unit of the deposit asset gives when it's locked for `MAX_VOTING_LOCK` (1_0000000 = 1),
+    // pools without a weight don't give voting power. The current locks keep the previous weight until they are
+    // checkpointed again.
+    fn set_voting_weight(e: Env, deposit_asset: Address, weight: u128) {
+        validate(&e, CoreDataKeys::Manager);
+
+        e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        e._voting().set_pool_weight(&deposit_asset, weight);
+        e._core().bump();
+    }
+
+    fn voting_weight(e: Env, deposit_asset: Address) -> u128 {
+        e._voting().pool_weight(&deposit_asset)
+    }
+
+    // Updates the voting lock of the deposit with its current amount, unlock time and the voting weight of the pool,
+    // anyone can call it so the deposits made before the voting power existed or before a weight change are counted
+    fn checkpoint(e: Env, account: Address, deposit_asset: Address) {
+        e._pools().pool(&deposit_asset).unwrap_or_else(|| {
+            panic_with_error!(&e, &ContractErrors::PoolDoesntExist);
+        });
+
+        let new_lock = match e._deposits().get(&deposit_asset, &account) {
+            None => None,
+            Some(deposit) => voting_lock(&e, &deposit_asset, &deposit),
+        };
+
+        checkpoint_lock(&e, &account, &deposit_asset, &new_lock);
+        e._core().bump();
+    }
+
+    // Advances the total voting power by up to `max_weeks` weeks and returns the timestamp it reached
+    fn checkpoint_total(e: Env, max_weeks: u32) -> u64 {
+        let timestamp: u64 = checkpoint_total(&e, max_weeks);
+        e._core().bump();
+        timestamp
+    }
+
+    // The voting power of the account at the timestamp, it's calculated from the deposits the account had (or has)
+    // in all the pools at that time and it decays until the deposits unlock
+    fn voting_power(e: Env, account: Address, timestamp: u64) -> u128 {
+        account_voting_power(&e, &account, timestamp)
+    }
+
+    fn total_voting_power(e: Env, timestamp: u64) -> u128 {
+        total_voting_power(&e, timestamp)
+    }
 }
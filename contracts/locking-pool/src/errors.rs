@@ -7,7 +7,7 @@ pub enum ContractErrors {
     NotStarted = 0,
     PoolDoesntExist = 1,
     InvalidDepositAmount = 2,
-    DepositAlreadyExists = 3,
+    // 3 was `DepositAlreadyExists`, a second deposit now tops up the existing one
     FundsDepositFailed = 4,
     DepositDoesntExist = 5,
     DepositIsStillLocked = 6,
@@ -19,4 +19,14 @@ pub enum ContractErrors {
     PoolAlreadyExists = 12,
     PoolCanNotBeDeleted = 13,
     AlreadyStarted = 14,
+    InvalidRewardAsset = 15,
+    RewardAssetDoesntExist = 16,
+    InvalidRewardSchedule = 17,
+    InvalidLockDuration = 18,
+    InvalidLockConfig = 19,
+    EarlyWithdrawIsDisabled = 20,
+    InvalidPenaltyConfig = 21,
+    InvalidCompoundingConfig = 22,
+    InvalidDistributionConfig = 23,
+    DistributorNotAllowed = 24,
 }
@@ -4,3 +4,5 @@ mod errors;
 mod storage;
 mod tests;
 mod utils;
+
+pub use crate::contract::{LockingPoolContract, LockingPoolContractClient};
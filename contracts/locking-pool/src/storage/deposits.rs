@@ -1,8 +1,29 @@
-use soroban_sdk::{contracttype, Address, Env};
+use crate::storage::core::{CoreDataKeys, CoreStorageFunc};
+use crate::storage::pools::{PoolsDataFunc, FACTOR_PRECISION, LEGACY_FACTOR_PRECISION};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, Symbol, TryFromVal, Val};
 
 #[contracttype]
 #[derive(Debug)]
 pub struct Deposit {
+    // The amount deposited, without the compounded rewards
+    pub amount: u128,
+    pub shares: u128,
+
+    // The amount with the boost of its lock duration applied, rewards are calculated using the weight
+    pub weight: u128,
+    pub locked_at: u64,
+    pub unlocks_at: u64,
+
+    // The factor of each reward asset when the rewards of the deposit were last paid, assets added to the pool after
+    // that are not included and their snapshot is 0
+    pub snapshots: Map<Address, u128>,
+}
+
+// The layout of the deposits before the pools had multiple reward assets, they are converted to the current layout
+// when they are read
+#[contracttype]
+#[derive(Debug)]
+pub struct LegacyDeposit {
     pub amount: u128,
     pub unlocks_at: u64,
     pub snapshot: u128,
@@ -24,13 +45,46 @@ impl Deposits {
     }
 
     pub fn get(&self, deposit_address: &Address, depositor: &Address) -> Option<Deposit> {
-        self.env
-            .storage()
-            .persistent()
-            .get(&DepositsStorageKeys::Deposit((
-                deposit_address.clone(),
-                depositor.clone(),
-            )))
+        let value: Map<Symbol, Val> =
+            self.env
+                .storage()
+                .persistent()
+                .get(&DepositsStorageKeys::Deposit((
+                    deposit_address.clone(),
+                    depositor.clone(),
+                )))?;
+
+        // The fields of a stored struct must match exactly, so the layout is checked before decoding it
+        if !value.contains_key(symbol_short!("snapshot")) {
+            return Some(Deposit::try_from_val(&self.env, &value.to_val()).unwrap());
+        }
+
+        // Legacy deposits were locked for the lock period of the pool and had no boost
+        let legacy_deposit: LegacyDeposit =
+            LegacyDeposit::try_from_val(&self.env, &value.to_val()).unwrap();
+        let lock_period: u64 = self
+            .env
+            ._pools()
+            .pool(deposit_address)
+            .map_or(0, |pool| pool.lock_period);
+
+        let mut snapshots: Map<Address, u128> = Map::new(&self.env);
+        snapshots.set(
+            self.env
+                ._core()
+                .address(&CoreDataKeys::RewardsAsset)
+                .unwrap(),
+            legacy_deposit.snapshot * (FACTOR_PRECISION / LEGACY_FACTOR_PRECISION),
+        );
+
+        Some(Deposit {
+            amount: legacy_deposit.amount,
+            shares: legacy_deposit.amount,
+            weight: legacy_deposit.amount,
+            locked_at: legacy_deposit.unlocks_at.saturating_sub(lock_period),
+            unlocks_at: legacy_deposit.unlocks_at,
+            snapshots,
+        })
     }
 
     pub fn set(&self, deposit_address: &Address, depositor: &Address, deposit: &Deposit) {
@@ -1,3 +1,4 @@
 pub mod core;
 pub mod deposits;
 pub mod pools;
+pub mod voting;
@@ -1,10 +1,85 @@
-use soroban_sdk::{contracttype, Address, Env};
+use crate::storage::core::{CoreDataKeys, CoreStorageFunc};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, Symbol, TryFromVal, Val, Vec};
+
+pub const MAX_REWARD_ASSETS: u32 = 5;
+pub const MAX_DISTRIBUTORS: u32 = 10;
+
+// The scale of the reward factors, IE the rewards per unit of weight multiplied by this value
+pub const FACTOR_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+// The scale of the factors of the legacy pools and deposits
+pub const LEGACY_FACTOR_PRECISION: u128 = 1_0000000;
+
+// Rewards of a reward asset emitted linearly at `rate` per second until `period_finish`, they are added to the
+// factor of the asset every time the pool is updated.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RewardStream {
+    pub rate: u128,
+    pub period_finish: u64,
+    pub last_update: u64,
+}
 
 #[contracttype]
 #[derive(Debug, PartialEq)]
 pub struct Pool {
     pub active: bool,
     pub asset: Address,
+
+    // The sum of the weights of the deposits, IE the amounts with the lock boost applied
+    pub balance: u128,
+    pub deposits: u64,
+
+    // The deposited assets (including the compounded rewards) and the shares of the deposits, withdrawals are paid
+    // at `principal / shares` per share
+    pub principal: u128,
+    pub shares: u128,
+
+    // If enabled, the rewards in the deposit asset are added to the principal instead of the factor
+    pub compounding: bool,
+
+    // The reward accumulator of each of the reward assets of the pool
+    pub factors: Map<Address, u128>,
+
+    // The part of each reward (scaled by `FACTOR_PRECISION`) that didn't fit in the factor, it's carried to the next
+    // reward of the asset
+    pub remainders: Map<Address, u128>,
+
+    // The rewards (scaled by `FACTOR_PRECISION`) lost to rounding when paying the deposits, they don't belong to
+    // anyone and can be recovered with `sweep_dust`
+    pub dust: Map<Address, u128>,
+
+    // The streaming emissions of the reward assets that have one
+    pub streams: Map<Address, RewardStream>,
+
+    // The min amount of a `distribute` call and the addresses allowed to distribute or fund rewards, anyone can do
+    // it if the list is empty
+    pub min_distribution: u128,
+    pub distributors: Vec<Address>,
+
+    // The total amount sent to the pool with `distribute` and `fund_rewards` for each reward asset
+    pub distributed: Map<Address, u128>,
+
+    // Deposits can be locked from `lock_period` up to `max_lock_period`, the weight of a deposit goes linearly from
+    // its amount (1_0000000) to its amount multiplied by `max_boost`
+    pub lock_period: u64,
+    pub max_lock_period: u64,
+    pub max_boost: u128,
+    pub min_deposit: u128,
+
+    // The max penalty (1_0000000 = 100%) of withdrawing a deposit before it unlocks, 0 if early withdrawals are not
+    // allowed. The penalty is sent to the `penalty_treasury` or shared with the depositors if there is no treasury.
+    pub early_withdraw_penalty: u128,
+    pub penalty_treasury: Option<Address>,
+}
+
+// The layout of the pools before they had multiple reward assets, they are converted to the current layout when they
+// are read and saved with it the next time the pool is updated
+#[contracttype]
+#[derive(Debug, PartialEq)]
+pub struct LegacyPool {
+    pub active: bool,
+    pub asset: Address,
     pub balance: u128,
     pub deposits: u64,
     pub factor: u128,
@@ -12,6 +87,39 @@ pub struct Pool {
     pub min_deposit: u128,
 }
 
+impl Pool {
+    pub fn from_legacy(e: &Env, legacy_pool: LegacyPool) -> Pool {
+        let mut factors: Map<Address, u128> = Map::new(e);
+        factors.set(
+            e._core().address(&CoreDataKeys::RewardsAsset).unwrap(),
+            legacy_pool.factor * (FACTOR_PRECISION / LEGACY_FACTOR_PRECISION),
+        );
+
+        Pool {
+            active: legacy_pool.active,
+            asset: legacy_pool.asset,
+            balance: legacy_pool.balance,
+            deposits: legacy_pool.deposits,
+            principal: legacy_pool.balance,
+            shares: legacy_pool.balance,
+            compounding: false,
+            factors,
+            remainders: Map::new(e),
+            dust: Map::new(e),
+            streams: Map::new(e),
+            min_distribution: 100_0000000,
+            distributors: Vec::new(e),
+            distributed: Map::new(e),
+            lock_period: legacy_pool.lock_period,
+            max_lock_period: legacy_pool.lock_period,
+            max_boost: 1_0000000,
+            min_deposit: legacy_pool.min_deposit,
+            early_withdraw_penalty: 0,
+            penalty_treasury: None,
+        }
+    }
+}
+
 #[contracttype]
 pub enum PoolDataKeys {
     Pool(Address),
@@ -28,10 +136,20 @@ impl Pools {
     }
 
     pub fn pool(&self, address: &Address) -> Option<Pool> {
-        self.env
+        let value: Map<Symbol, Val> = self
+            .env
             .storage()
             .persistent()
-            .get(&PoolDataKeys::Pool(address.clone()))
+            .get(&PoolDataKeys::Pool(address.clone()))?;
+
+        // The fields of a stored struct must match exactly, so the layout is checked before decoding it
+        if value.contains_key(symbol_short!("factor")) {
+            let legacy_pool: LegacyPool =
+                LegacyPool::try_from_val(&self.env, &value.to_val()).unwrap();
+            return Some(Pool::from_legacy(&self.env, legacy_pool));
+        }
+
+        Some(Pool::try_from_val(&self.env, &value.to_val()).unwrap())
     }
 
     pub fn set_pool(&self, pool: &Pool) {
@@ -0,0 +1,177 @@
+use soroban_sdk::{contracttype, Address, Env, Map};
+
+// Voting power is counted by weeks, locks end at the start of the week of `unlocks_at`
+pub const WEEK: u64 = 3600 * 24 * 7;
+
+// A lock of this duration has the same voting power as its amount
+pub const MAX_VOTING_LOCK: u64 = WEEK * 52 * 4;
+
+// The voting power of a lock at a given time is `slope * (end - time) / 1_0000000`, the slope includes the voting
+// weight of the pool so deposit assets with different decimals or values can be compared
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VotingLock {
+    pub slope: u128,
+    pub end: u64,
+}
+
+// The locks the account had (one for each pool where it has a deposit) since `timestamp`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountCheckpoint {
+    pub timestamp: u64,
+    pub locks: Map<Address, VotingLock>,
+}
+
+// The sum of all the locks at `timestamp`, the `slope` changes every week when locks end
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TotalCheckpoint {
+    pub timestamp: u64,
+    pub bias: u128,
+    pub slope: u128,
+}
+
+#[contracttype]
+pub enum VotingDataKeys {
+    AccountCheckpoints(Address),
+    AccountCheckpoint((Address, u32)),
+    TotalCheckpoints,
+    TotalCheckpoint(u32),
+    SlopeChange(u64),
+    PoolWeight(Address),
+}
+
+pub struct Voting {
+    pub env: Env,
+}
+
+impl Voting {
+    #[inline(always)]
+    pub fn new(e: &Env) -> Voting {
+        Voting { env: e.clone() }
+    }
+
+    pub fn account_checkpoints(&self, account: &Address) -> u32 {
+        self.env
+            .storage()
+            .persistent()
+            .get(&VotingDataKeys::AccountCheckpoints(account.clone()))
+            .unwrap_or(0)
+    }
+
+    pub fn account_checkpoint(&self, account: &Address, index: u32) -> Option<AccountCheckpoint> {
+        self.env
+            .storage()
+            .persistent()
+            .get(&VotingDataKeys::AccountCheckpoint((account.clone(), index)))
+    }
+
+    pub fn set_account_checkpoint(
+        &self,
+        account: &Address,
+        index: u32,
+        checkpoint: &AccountCheckpoint,
+    ) {
+        let key = VotingDataKeys::AccountCheckpoint((account.clone(), index));
+        self.env.storage().persistent().set(&key, checkpoint);
+        self.env
+            .storage()
+            .persistent()
+            .extend_ttl(&key, 17280, 17280 * 30);
+
+        let count_key = VotingDataKeys::AccountCheckpoints(account.clone());
+        if index >= self.account_checkpoints(account) {
+            self.env
+                .storage()
+                .persistent()
+                .set(&count_key, &(index + 1));
+        }
+        self.env
+            .storage()
+            .persistent()
+            .extend_ttl(&count_key, 17280, 17280 * 30);
+    }
+
+    pub fn total_checkpoints(&self) -> u32 {
+        self.env
+            .storage()
+            .persistent()
+            .get(&VotingDataKeys::TotalCheckpoints)
+            .unwrap_or(0)
+    }
+
+    pub fn total_checkpoint(&self, index: u32) -> Option<TotalCheckpoint> {
+        self.env
+            .storage()
+            .persistent()
+            .get(&VotingDataKeys::TotalCheckpoint(index))
+    }
+
+    pub fn set_total_checkpoint(&self, index: u32, checkpoint: &TotalCheckpoint) {
+        let key = VotingDataKeys::TotalCheckpoint(index);
+        self.env.storage().persistent().set(&key, checkpoint);
+        self.env
+            .storage()
+            .persistent()
+            .extend_ttl(&key, 17280, 17280 * 30);
+
+        if index >= self.total_checkpoints() {
+            self.env
+                .storage()
+                .persistent()
+                .set(&VotingDataKeys::TotalCheckpoints, &(index + 1));
+        }
+        self.env.storage().persistent().extend_ttl(
+            &VotingDataKeys::TotalCheckpoints,
+            17280,
+            17280 * 30,
+        );
+    }
+
+    pub fn slope_change(&self, week: u64) -> u128 {
+        self.env
+            .storage()
+            .persistent()
+            .get(&VotingDataKeys::SlopeChange(week))
+            .unwrap_or(0)
+    }
+
+    pub fn set_slope_change(&self, week: u64, slope: u128) {
+        let key = VotingDataKeys::SlopeChange(week);
+        self.env.storage().persistent().set(&key, &slope);
+        self.env
+            .storage()
+            .persistent()
+            .extend_ttl(&key, 17280, 17280 * 30);
+    }
+
+    // Pools without a weight don't give voting power
+    pub fn pool_weight(&self, pool_asset: &Address) -> u128 {
+        self.env
+            .storage()
+            .persistent()
+            .get(&VotingDataKeys::PoolWeight(pool_asset.clone()))
+            .unwrap_or(0)
+    }
+
+    pub fn set_pool_weight(&self, pool_asset: &Address, weight: u128) {
+        let key = VotingDataKeys::PoolWeight(pool_asset.clone());
+        self.env.storage().persistent().set(&key, &weight);
+        self.env
+            .storage()
+            .persistent()
+            .extend_ttl(&key, 17280, 17280 * 30);
+    }
+}
+
+pub trait VotingStorageFunc {
+    fn _voting(&self) -> Voting;
+}
+
+impl VotingStorageFunc for Env {
+    #[inline(always)]
+    fn _voting(&self) -> Voting {
+        Voting::new(self)
+    }
+}
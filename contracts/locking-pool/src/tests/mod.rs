@@ -2,5 +2,8 @@ mod test_cloning;
 mod test_core;
 mod test_deposits;
 mod test_distribute;
+mod test_dust;
+mod test_legacy_storage;
 mod test_pools;
 mod test_utils;
+mod test_voting;
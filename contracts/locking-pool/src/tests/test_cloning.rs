@@ -18,6 +18,18 @@ fn test_cloning_a_pool() {
         &test_data.min_deposit,
     );
 
+    test_data
+        .rewards_asset_stellar
+        .mock_all_auths()
+        .mint(&test_data.manager, &700_0000000);
+    test_data.contract_client.mock_all_auths().fund_rewards(
+        &test_data.manager,
+        &test_data.staking_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &700_0000000,
+        &7000,
+    );
+
     let new_asset_address: Address = Address::generate(&e);
 
     assert!(test_data
@@ -52,10 +64,19 @@ fn test_cloning_a_pool() {
         assert_eq!(old_pool.active, new_pool.active);
         assert_eq!(old_pool.balance, new_pool.balance);
         assert_eq!(old_pool.deposits, new_pool.deposits);
-        assert_eq!(old_pool.factor, new_pool.factor);
+        assert_eq!(old_pool.factors, new_pool.factors);
         assert_eq!(old_pool.lock_period, new_pool.lock_period);
         assert_eq!(old_pool.min_deposit, new_pool.min_deposit);
+        assert_eq!(old_pool.streams.len(), 1);
+        assert!(new_pool.streams.is_empty());
     });
+
+    assert_eq!(
+        test_data
+            .contract_client
+            .emission_rate(&new_asset_address, &test_data.rewards_asset_client.address),
+        0
+    );
 }
 
 #[test]
@@ -90,6 +111,7 @@ fn test_migrating_deposits() {
             &test_data.staking_asset_client.address,
             &new_depositor,
             &test_data.min_deposit,
+            &test_data.lock_period,
         );
     }
 
@@ -3,6 +3,7 @@
 use crate::errors::ContractErrors;
 use crate::storage::deposits::{Deposit, DepositsStorageFunc};
 use crate::storage::pools::{Pool, PoolsDataFunc};
+use crate::storage::voting::MAX_VOTING_LOCK;
 use crate::tests::test_utils::{create_test_data, init_contract, TestData};
 use soroban_sdk::testutils::{Address as _, Ledger, LedgerInfo, MockAuth, MockAuthInvoke};
 use soroban_sdk::{Address, Env, IntoVal, Vec};
@@ -30,14 +31,20 @@ pub fn test_a_deposit() {
         .try_deposit(
             &test_data.staking_asset_client.address,
             &depositor,
-            &test_data.min_deposit
+            &test_data.min_deposit,
+            &test_data.lock_period,
         )
         .is_err());
 
     let inactive_pool_error = test_data
         .contract_client
         .mock_all_auths()
-        .try_deposit(&test_data.staking_asset_client.address, &depositor, &1)
+        .try_deposit(
+            &test_data.staking_asset_client.address,
+            &depositor,
+            &1,
+            &test_data.lock_period,
+        )
         .unwrap_err()
         .unwrap();
 
@@ -54,7 +61,12 @@ pub fn test_a_deposit() {
     let min_deposit_error = test_data
         .contract_client
         .mock_all_auths()
-        .try_deposit(&test_data.staking_asset_client.address, &depositor, &1)
+        .try_deposit(
+            &test_data.staking_asset_client.address,
+            &depositor,
+            &1,
+            &test_data.lock_period,
+        )
         .unwrap_err()
         .unwrap();
 
@@ -70,6 +82,7 @@ pub fn test_a_deposit() {
             &test_data.staking_asset_client.address,
             &depositor,
             &(test_data.min_deposit * 3),
+            &test_data.lock_period,
         )
         .unwrap_err()
         .unwrap();
@@ -90,6 +103,7 @@ pub fn test_a_deposit() {
                     test_data.staking_asset_client.address.clone(),
                     depositor.clone(),
                     test_data.min_deposit,
+                    test_data.lock_period,
                 )
                     .into_val(&e),
                 sub_invokes: &[MockAuthInvoke {
@@ -109,6 +123,7 @@ pub fn test_a_deposit() {
             &test_data.staking_asset_client.address,
             &depositor,
             &test_data.min_deposit,
+            &test_data.lock_period,
         );
 
     e.as_contract(&test_data.contract_client.address, || {
@@ -118,33 +133,27 @@ pub fn test_a_deposit() {
             .unwrap();
         assert_eq!(&deposit.amount, &test_data.min_deposit);
         assert_eq!(&deposit.unlocks_at, &test_data.lock_period);
-        assert_eq!(&deposit.snapshot, &0);
+        assert_eq!(
+            deposit
+                .snapshots
+                .get(test_data.rewards_asset_client.address.clone()),
+            Some(0)
+        );
 
         let core_state: Pool = e
             ._pools()
             .pool(&test_data.staking_asset_client.address)
             .unwrap();
-        assert_eq!(&core_state.factor, &0);
+        assert_eq!(
+            &core_state
+                .factors
+                .get(test_data.rewards_asset_client.address.clone()),
+            &Some(0)
+        );
         assert_eq!(&core_state.balance, &deposit.amount);
         assert_eq!(&core_state.deposits, &1);
     });
 
-    let already_staked_error = test_data
-        .contract_client
-        .mock_all_auths()
-        .try_deposit(
-            &test_data.staking_asset_client.address,
-            &depositor,
-            &(test_data.min_deposit),
-        )
-        .unwrap_err()
-        .unwrap();
-
-    assert_eq!(
-        &already_staked_error,
-        &ContractErrors::DepositAlreadyExists.into()
-    );
-
     assert_eq!(
         test_data.min_deposit as i128,
         test_data.staking_asset_client.balance(&depositor),
@@ -157,6 +166,115 @@ pub fn test_a_deposit() {
     );
 }
 
+#[test]
+pub fn test_deposit_top_up() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    test_data.contract_client.mock_all_auths().set_pool(
+        &test_data.staking_asset_client.address,
+        &test_data.lock_period,
+        &test_data.min_deposit,
+    );
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .toggle_pool(&test_data.staking_asset_client.address, &true);
+
+    let depositor: Address = Address::generate(&e);
+    test_data
+        .staking_asset_stellar
+        .mock_all_auths()
+        .mint(&depositor, &((test_data.min_deposit * 2) as i128));
+    test_data
+        .rewards_asset_stellar
+        .mock_all_auths()
+        .mint(&test_data.manager, &(test_data.min_deposit as i128));
+
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.staking_asset_client.address,
+        &depositor,
+        &test_data.min_deposit,
+        &test_data.lock_period,
+    );
+
+    test_data.contract_client.mock_all_auths().distribute(
+        &test_data.manager,
+        &test_data.staking_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &test_data.min_deposit,
+    );
+
+    e.ledger().set(LedgerInfo {
+        timestamp: test_data.lock_period / 2,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+
+    // The top up pays the pending rewards and restarts the lock
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.staking_asset_client.address,
+        &depositor,
+        &test_data.min_deposit,
+        &test_data.lock_period,
+    );
+
+    assert_eq!(
+        test_data.min_deposit as i128,
+        test_data.rewards_asset_client.balance(&depositor),
+    );
+
+    e.as_contract(&test_data.contract_client.address, || {
+        let deposit: Deposit = e
+            ._deposits()
+            .get(&test_data.staking_asset_client.address, &depositor)
+            .unwrap();
+        let pool: Pool = e
+            ._pools()
+            .pool(&test_data.staking_asset_client.address)
+            .unwrap();
+
+        assert_eq!(deposit.amount, test_data.min_deposit * 2);
+        assert_eq!(
+            deposit.unlocks_at,
+            test_data.lock_period / 2 + test_data.lock_period
+        );
+        assert_eq!(deposit.snapshots, pool.factors);
+        assert_eq!(pool.balance, test_data.min_deposit * 2);
+        assert_eq!(pool.deposits, 1);
+    });
+
+    e.ledger().set(LedgerInfo {
+        timestamp: test_data.lock_period,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+
+    let stake_is_locked_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_withdraw(&test_data.staking_asset_client.address, &depositor)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        stake_is_locked_error,
+        ContractErrors::DepositIsStillLocked.into()
+    );
+}
+
 #[test]
 pub fn test_multiple_deposits() {
     let e: Env = Env::default();
@@ -191,6 +309,7 @@ pub fn test_multiple_deposits() {
             &test_data.staking_asset_client.address,
             &depositor,
             &test_data.min_deposit,
+            &test_data.lock_period,
         );
     }
 
@@ -205,7 +324,12 @@ pub fn test_multiple_deposits() {
             core_state.balance,
             (depositors.len() as u128) * test_data.min_deposit
         );
-        assert_eq!(core_state.factor, 0);
+        assert_eq!(
+            core_state
+                .factors
+                .get(test_data.rewards_asset_client.address.clone()),
+            Some(0)
+        );
     })
 }
 
@@ -250,6 +374,7 @@ pub fn test_withdraws() {
             &test_data.staking_asset_client.address,
             &depositor,
             &test_data.min_deposit,
+            &test_data.lock_period,
         );
     }
 
@@ -328,8 +453,300 @@ pub fn test_withdraws() {
             .pool(&test_data.staking_asset_client.address)
             .unwrap();
 
-        assert_eq!(core_state.factor, 0);
+        assert_eq!(
+            core_state
+                .factors
+                .get(test_data.rewards_asset_client.address.clone()),
+            Some(0)
+        );
         assert_eq!(core_state.balance, 0);
         assert_eq!(core_state.deposits, 0);
     });
 }
+
+#[test]
+pub fn test_lock_boost() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    test_data.contract_client.mock_all_auths().set_pool(
+        &test_data.staking_asset_client.address,
+        &test_data.lock_period,
+        &test_data.min_deposit,
+    );
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .toggle_pool(&test_data.staking_asset_client.address, &true);
+
+    let invalid_config_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_set_lock_boost(
+            &test_data.staking_asset_client.address,
+            &(test_data.lock_period - 1),
+            &2_0000000,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        invalid_config_error,
+        ContractErrors::InvalidLockConfig.into()
+    );
+
+    // Locks can't be longer than the max voting lock
+    let too_long_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_set_lock_boost(
+            &test_data.staking_asset_client.address,
+            &(MAX_VOTING_LOCK + 1),
+            &2_0000000,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(too_long_error, ContractErrors::InvalidLockConfig.into());
+
+    test_data.contract_client.mock_all_auths().set_lock_boost(
+        &test_data.staking_asset_client.address,
+        &(test_data.lock_period * 4),
+        &2_0000000,
+    );
+
+    let short_depositor: Address = Address::generate(&e);
+    let long_depositor: Address = Address::generate(&e);
+    for depositor in [short_depositor.clone(), long_depositor.clone()] {
+        test_data
+            .staking_asset_stellar
+            .mock_all_auths()
+            .mint(&depositor, &((test_data.min_deposit * 2) as i128));
+    }
+
+    let invalid_duration_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_deposit(
+            &test_data.staking_asset_client.address,
+            &long_depositor,
+            &test_data.min_deposit,
+            &(test_data.lock_period * 4 + 1),
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        invalid_duration_error,
+        ContractErrors::InvalidLockDuration.into()
+    );
+
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.staking_asset_client.address,
+        &short_depositor,
+        &test_data.min_deposit,
+        &test_data.lock_period,
+    );
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.staking_asset_client.address,
+        &long_depositor,
+        &test_data.min_deposit,
+        &(test_data.lock_period * 4),
+    );
+
+    e.as_contract(&test_data.contract_client.address, || {
+        let deposit: Deposit = e
+            ._deposits()
+            .get(&test_data.staking_asset_client.address, &long_depositor)
+            .unwrap();
+        let pool: Pool = e
+            ._pools()
+            .pool(&test_data.staking_asset_client.address)
+            .unwrap();
+
+        assert_eq!(deposit.amount, test_data.min_deposit);
+        assert_eq!(deposit.weight, test_data.min_deposit * 2);
+        assert_eq!(deposit.unlocks_at, test_data.lock_period * 4);
+        assert_eq!(pool.balance, test_data.min_deposit * 3);
+    });
+
+    // A top up can't end the lock before the current one
+    let shorter_lock_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_deposit(
+            &test_data.staking_asset_client.address,
+            &long_depositor,
+            &test_data.min_deposit,
+            &test_data.lock_period,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        shorter_lock_error,
+        ContractErrors::InvalidLockDuration.into()
+    );
+
+    test_data
+        .rewards_asset_stellar
+        .mock_all_auths()
+        .mint(&test_data.manager, &((test_data.min_deposit * 3) as i128));
+    test_data.contract_client.mock_all_auths().distribute(
+        &test_data.manager,
+        &test_data.staking_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &(test_data.min_deposit * 3),
+    );
+
+    assert_eq!(
+        test_data
+            .contract_client
+            .pending_rewards(&test_data.staking_asset_client.address, &short_depositor)
+            .get(test_data.rewards_asset_client.address.clone()),
+        Some(test_data.min_deposit)
+    );
+    assert_eq!(
+        test_data
+            .contract_client
+            .pending_rewards(&test_data.staking_asset_client.address, &long_depositor)
+            .get(test_data.rewards_asset_client.address.clone()),
+        Some(test_data.min_deposit * 2)
+    );
+}
+
+#[test]
+pub fn test_early_withdraw() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    test_data.contract_client.mock_all_auths().set_pool(
+        &test_data.staking_asset_client.address,
+        &test_data.lock_period,
+        &test_data.min_deposit,
+    );
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .toggle_pool(&test_data.staking_asset_client.address, &true);
+
+    let depositor_1: Address = Address::generate(&e);
+    let depositor_2: Address = Address::generate(&e);
+    for depositor in [depositor_1.clone(), depositor_2.clone()] {
+        test_data
+            .staking_asset_stellar
+            .mock_all_auths()
+            .mint(&depositor, &(test_data.min_deposit as i128));
+
+        test_data.contract_client.mock_all_auths().deposit(
+            &test_data.staking_asset_client.address,
+            &depositor,
+            &test_data.min_deposit,
+            &test_data.lock_period,
+        );
+    }
+
+    let disabled_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_early_withdraw(&test_data.staking_asset_client.address, &depositor_1)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        disabled_error,
+        ContractErrors::EarlyWithdrawIsDisabled.into()
+    );
+
+    let invalid_penalty_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_set_early_withdraw(&test_data.staking_asset_client.address, &1_0000001, &None)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        invalid_penalty_error,
+        ContractErrors::InvalidPenaltyConfig.into()
+    );
+
+    // Without a treasury the penalty is shared with the remaining depositors
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .set_early_withdraw(&test_data.staking_asset_client.address, &5000000, &None);
+
+    e.ledger().set(LedgerInfo {
+        timestamp: test_data.lock_period / 2,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .early_withdraw(&test_data.staking_asset_client.address, &depositor_1);
+
+    assert_eq!(
+        (test_data.min_deposit * 3 / 4) as i128,
+        test_data.staking_asset_client.balance(&depositor_1),
+    );
+    assert_eq!(
+        test_data
+            .contract_client
+            .pending_rewards(&test_data.staking_asset_client.address, &depositor_2)
+            .get(test_data.staking_asset_client.address.clone()),
+        Some(test_data.min_deposit / 4)
+    );
+
+    let treasury: Address = Address::generate(&e);
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .set_early_withdraw(
+            &test_data.staking_asset_client.address,
+            &5000000,
+            &Some(treasury.clone()),
+        );
+
+    e.ledger().set(LedgerInfo {
+        timestamp: test_data.lock_period * 3 / 4,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+
+    // The penalty decreases linearly until the deposit unlocks
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .early_withdraw(&test_data.staking_asset_client.address, &depositor_2);
+
+    assert_eq!(
+        (test_data.min_deposit / 8) as i128,
+        test_data.staking_asset_client.balance(&treasury),
+    );
+    assert_eq!(
+        (test_data.min_deposit * 7 / 8 + test_data.min_deposit / 4) as i128,
+        test_data.staking_asset_client.balance(&depositor_2),
+    );
+    assert_eq!(
+        0,
+        test_data
+            .staking_asset_client
+            .balance(&test_data.contract_client.address),
+    );
+}
@@ -1,10 +1,10 @@
 #![cfg(test)]
 
 use crate::errors::ContractErrors;
-use crate::storage::pools::{Pool, PoolsDataFunc};
-use crate::tests::test_utils::{create_test_data, init_contract, TestData};
+use crate::storage::pools::{Pool, PoolsDataFunc, FACTOR_PRECISION};
+use crate::tests::test_utils::{create_test_data, create_token_contract, init_contract, TestData};
 use soroban_sdk::testutils::{Address as _, Ledger, LedgerInfo, MockAuth, MockAuthInvoke};
-use soroban_sdk::{Address, Env, IntoVal};
+use soroban_sdk::{vec, Address, Env, IntoVal, Vec};
 
 #[test]
 fn test_distribute_and_withdraw() {
@@ -39,6 +39,7 @@ fn test_distribute_and_withdraw() {
         .try_distribute(
             &test_data.manager,
             &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
             &test_data.min_deposit
         )
         .is_err());
@@ -49,6 +50,7 @@ fn test_distribute_and_withdraw() {
         .try_distribute(
             &test_data.manager,
             &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
             &test_data.min_deposit,
         )
         .unwrap_err()
@@ -63,6 +65,7 @@ fn test_distribute_and_withdraw() {
         &test_data.staking_asset_client.address,
         &depositor,
         &test_data.min_deposit,
+        &test_data.lock_period,
     );
 
     let rewards_deposit_failed_error = test_data
@@ -71,6 +74,7 @@ fn test_distribute_and_withdraw() {
         .try_distribute(
             &test_data.manager,
             &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
             &(test_data.min_deposit * 10),
         )
         .unwrap_err()
@@ -91,6 +95,7 @@ fn test_distribute_and_withdraw() {
                 args: (
                     test_data.manager.clone(),
                     test_data.staking_asset_client.address.clone(),
+                    test_data.rewards_asset_client.address.clone(),
                     test_data.min_deposit.clone(),
                 )
                     .into_val(&e),
@@ -110,6 +115,7 @@ fn test_distribute_and_withdraw() {
         .distribute(
             &test_data.manager,
             &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
             &test_data.min_deposit,
         );
 
@@ -120,8 +126,9 @@ fn test_distribute_and_withdraw() {
             .unwrap();
 
         assert_eq!(
-            pool.factor,
-            (test_data.min_deposit * 1_0000000) / pool.balance
+            pool.factors
+                .get(test_data.rewards_asset_client.address.clone()),
+            Some((test_data.min_deposit * FACTOR_PRECISION) / pool.balance)
         );
     });
 
@@ -153,7 +160,11 @@ fn test_distribute_and_withdraw() {
             ._pools()
             .pool(&test_data.staking_asset_client.address)
             .unwrap();
-        assert_eq!(pool.factor, 0);
+        assert_eq!(
+            pool.factors
+                .get(test_data.rewards_asset_client.address.clone()),
+            Some(0)
+        );
     });
 
     assert_eq!(
@@ -169,4 +180,768 @@ fn test_distribute_and_withdraw() {
     );
 }
 
+#[test]
+fn test_claim_rewards() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    test_data.contract_client.mock_all_auths().set_pool(
+        &test_data.staking_asset_client.address,
+        &test_data.lock_period,
+        &test_data.min_deposit,
+    );
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .toggle_pool(&test_data.staking_asset_client.address, &true);
+
+    let depositor_1: Address = Address::generate(&e);
+    let depositor_2: Address = Address::generate(&e);
+    for depositor in [depositor_1.clone(), depositor_2.clone()] {
+        test_data
+            .staking_asset_stellar
+            .mock_all_auths()
+            .mint(&depositor, &(test_data.min_deposit as i128));
+
+        test_data.contract_client.mock_all_auths().deposit(
+            &test_data.staking_asset_client.address,
+            &depositor,
+            &test_data.min_deposit,
+            &test_data.lock_period,
+        );
+    }
+
+    test_data
+        .rewards_asset_stellar
+        .mock_all_auths()
+        .mint(&test_data.manager, &((test_data.min_deposit * 4) as i128));
+
+    test_data.contract_client.mock_all_auths().distribute(
+        &test_data.manager,
+        &test_data.staking_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &(test_data.min_deposit * 2),
+    );
+
+    assert_eq!(
+        test_data
+            .contract_client
+            .pending_rewards(&test_data.staking_asset_client.address, &depositor_1)
+            .get(test_data.rewards_asset_client.address.clone())
+            .unwrap(),
+        test_data.min_deposit
+    );
+
+    assert!(test_data
+        .contract_client
+        .try_claim(&test_data.staking_asset_client.address, &depositor_1)
+        .is_err());
+
+    test_data
+        .contract_client
+        .mock_auths(&[MockAuth {
+            address: &depositor_1,
+            invoke: &MockAuthInvoke {
+                contract: &test_data.contract_client.address,
+                fn_name: "claim",
+                args: (
+                    test_data.staking_asset_client.address.clone(),
+                    depositor_1.clone(),
+                )
+                    .into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .claim(&test_data.staking_asset_client.address, &depositor_1);
+
+    assert_eq!(
+        test_data.min_deposit as i128,
+        test_data.rewards_asset_client.balance(&depositor_1),
+    );
+    assert_eq!(
+        test_data
+            .contract_client
+            .pending_rewards(&test_data.staking_asset_client.address, &depositor_1)
+            .get(test_data.rewards_asset_client.address.clone())
+            .unwrap(),
+        0
+    );
+
+    // The principal is still locked after claiming
+    let stake_is_locked_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_withdraw(&test_data.staking_asset_client.address, &depositor_1)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        stake_is_locked_error,
+        ContractErrors::DepositIsStillLocked.into()
+    );
+
+    test_data.contract_client.mock_all_auths().distribute(
+        &test_data.manager,
+        &test_data.staking_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &(test_data.min_deposit * 2),
+    );
+
+    e.ledger().set(LedgerInfo {
+        timestamp: test_data.lock_period,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+
+    // Only the rewards distributed after the claim are paid with the withdrawal
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .withdraw(&test_data.staking_asset_client.address, &depositor_1);
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .withdraw(&test_data.staking_asset_client.address, &depositor_2);
+
+    assert_eq!(
+        (test_data.min_deposit * 2) as i128,
+        test_data.rewards_asset_client.balance(&depositor_1),
+    );
+    assert_eq!(
+        (test_data.min_deposit * 2) as i128,
+        test_data.rewards_asset_client.balance(&depositor_2),
+    );
+
+    let no_deposit_error = test_data
+        .contract_client
+        .try_pending_rewards(&test_data.staking_asset_client.address, &depositor_1)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(no_deposit_error, ContractErrors::DepositDoesntExist.into());
+}
+
+#[test]
+fn test_multiple_reward_assets() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    test_data.contract_client.mock_all_auths().set_pool(
+        &test_data.staking_asset_client.address,
+        &test_data.lock_period,
+        &test_data.min_deposit,
+    );
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .toggle_pool(&test_data.staking_asset_client.address, &true);
+
+    let depositor: Address = Address::generate(&e);
+    test_data
+        .staking_asset_stellar
+        .mock_all_auths()
+        .mint(&depositor, &(test_data.min_deposit as i128));
+
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.staking_asset_client.address,
+        &depositor,
+        &test_data.min_deposit,
+        &test_data.lock_period,
+    );
+
+    let (extra_asset_client, extra_asset_stellar) =
+        create_token_contract(&e, &Address::generate(&e));
+    extra_asset_stellar
+        .mock_all_auths()
+        .mint(&test_data.manager, &(test_data.min_deposit as i128));
+    test_data
+        .rewards_asset_stellar
+        .mock_all_auths()
+        .mint(&test_data.manager, &(test_data.min_deposit as i128));
+
+    let unknown_asset_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_distribute(
+            &test_data.manager,
+            &test_data.staking_asset_client.address,
+            &extra_asset_client.address,
+            &test_data.min_deposit,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        unknown_asset_error,
+        ContractErrors::RewardAssetDoesntExist.into()
+    );
+
+    test_data.contract_client.mock_all_auths().add_reward_asset(
+        &test_data.staking_asset_client.address,
+        &extra_asset_client.address,
+    );
+
+    let already_added_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_add_reward_asset(
+            &test_data.staking_asset_client.address,
+            &extra_asset_client.address,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        already_added_error,
+        ContractErrors::InvalidRewardAsset.into()
+    );
+
+    for reward_asset in [
+        test_data.rewards_asset_client.address.clone(),
+        extra_asset_client.address.clone(),
+    ] {
+        test_data.contract_client.mock_all_auths().distribute(
+            &test_data.manager,
+            &test_data.staking_asset_client.address,
+            &reward_asset,
+            &test_data.min_deposit,
+        );
+    }
+
+    e.ledger().set(LedgerInfo {
+        timestamp: test_data.lock_period,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .withdraw(&test_data.staking_asset_client.address, &depositor);
+
+    assert_eq!(
+        test_data.min_deposit as i128,
+        test_data.rewards_asset_client.balance(&depositor),
+    );
+    assert_eq!(
+        test_data.min_deposit as i128,
+        extra_asset_client.balance(&depositor),
+    );
+}
+
+#[test]
+fn test_streaming_rewards() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    test_data.contract_client.mock_all_auths().set_pool(
+        &test_data.staking_asset_client.address,
+        &test_data.lock_period,
+        &test_data.min_deposit,
+    );
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .toggle_pool(&test_data.staking_asset_client.address, &true);
+
+    let depositor: Address = Address::generate(&e);
+    test_data
+        .staking_asset_stellar
+        .mock_all_auths()
+        .mint(&depositor, &(test_data.min_deposit as i128));
+    test_data
+        .rewards_asset_stellar
+        .mock_all_auths()
+        .mint(&test_data.manager, &1400_0000000);
+
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.staking_asset_client.address,
+        &depositor,
+        &test_data.min_deposit,
+        &test_data.lock_period,
+    );
+
+    let invalid_schedule_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_fund_rewards(
+            &test_data.manager,
+            &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
+            &700_0000000,
+            &0,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        invalid_schedule_error,
+        ContractErrors::InvalidRewardSchedule.into()
+    );
+
+    test_data.contract_client.mock_all_auths().fund_rewards(
+        &test_data.manager,
+        &test_data.staking_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &700_0000000,
+        &7000,
+    );
+
+    assert_eq!(
+        test_data.contract_client.emission_rate(
+            &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
+        ),
+        1000000
+    );
+
+    e.ledger().set(LedgerInfo {
+        timestamp: 3500,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+
+    assert_eq!(
+        test_data
+            .contract_client
+            .pending_rewards(&test_data.staking_asset_client.address, &depositor)
+            .get(test_data.rewards_asset_client.address.clone()),
+        Some(350_0000000)
+    );
+
+    // The rewards that weren't emitted yet are added to the new schedule
+    test_data.contract_client.mock_all_auths().fund_rewards(
+        &test_data.manager,
+        &test_data.staking_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &700_0000000,
+        &7000,
+    );
+
+    assert_eq!(
+        test_data.contract_client.emission_rate(
+            &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
+        ),
+        1500000
+    );
+
+    e.ledger().set(LedgerInfo {
+        timestamp: 20000,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+
+    assert_eq!(
+        test_data.contract_client.emission_rate(
+            &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
+        ),
+        0
+    );
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .claim(&test_data.staking_asset_client.address, &depositor);
+
+    assert_eq!(
+        1400_0000000,
+        test_data.rewards_asset_client.balance(&depositor),
+    );
+}
+
+#[test]
+fn test_compounding_rewards() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    for asset in [
+        test_data.staking_asset_client.address.clone(),
+        test_data.rewards_asset_client.address.clone(),
+    ] {
+        test_data.contract_client.mock_all_auths().set_pool(
+            &asset,
+            &test_data.lock_period,
+            &test_data.min_deposit,
+        );
+        test_data
+            .contract_client
+            .mock_all_auths()
+            .toggle_pool(&asset, &true);
+    }
+
+    // The deposit asset of the pool must be one of its reward assets
+    let invalid_config_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_set_compounding(&test_data.staking_asset_client.address, &true)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        invalid_config_error,
+        ContractErrors::InvalidCompoundingConfig.into()
+    );
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .set_compounding(&test_data.rewards_asset_client.address, &true);
+
+    let depositor_1: Address = Address::generate(&e);
+    let depositor_2: Address = Address::generate(&e);
+    for depositor in [depositor_1.clone(), depositor_2.clone()] {
+        test_data
+            .rewards_asset_stellar
+            .mock_all_auths()
+            .mint(&depositor, &(test_data.min_deposit as i128));
+    }
+    test_data
+        .rewards_asset_stellar
+        .mock_all_auths()
+        .mint(&test_data.manager, &250_0000000);
+
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.rewards_asset_client.address,
+        &depositor_1,
+        &test_data.min_deposit,
+        &test_data.lock_period,
+    );
+
+    test_data.contract_client.mock_all_auths().distribute(
+        &test_data.manager,
+        &test_data.rewards_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &100_0000000,
+    );
+
+    // The second deposit gets fewer shares because the first one already compounded its rewards
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.rewards_asset_client.address,
+        &depositor_2,
+        &test_data.min_deposit,
+        &test_data.lock_period,
+    );
+
+    assert_eq!(
+        test_data
+            .contract_client
+            .deposit_balance(&test_data.rewards_asset_client.address, &depositor_1),
+        200_0000000
+    );
+    assert_eq!(
+        test_data
+            .contract_client
+            .deposit_balance(&test_data.rewards_asset_client.address, &depositor_2),
+        100_0000000
+    );
+
+    test_data.contract_client.mock_all_auths().distribute(
+        &test_data.manager,
+        &test_data.rewards_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &150_0000000,
+    );
+
+    assert_eq!(
+        test_data
+            .contract_client
+            .pending_rewards(&test_data.rewards_asset_client.address, &depositor_1)
+            .get(test_data.rewards_asset_client.address.clone()),
+        Some(0)
+    );
+
+    e.ledger().set(LedgerInfo {
+        timestamp: test_data.lock_period,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+
+    for depositor in [depositor_1.clone(), depositor_2.clone()] {
+        test_data
+            .contract_client
+            .mock_all_auths()
+            .withdraw(&test_data.rewards_asset_client.address, &depositor);
+    }
+
+    assert_eq!(
+        300_0000000,
+        test_data.rewards_asset_client.balance(&depositor_1),
+    );
+    assert_eq!(
+        150_0000000,
+        test_data.rewards_asset_client.balance(&depositor_2),
+    );
+    assert_eq!(
+        0,
+        test_data
+            .rewards_asset_client
+            .balance(&test_data.contract_client.address),
+    );
+}
+
+#[test]
+fn test_compounding_rewards_with_different_lock_durations() {
+    let e: Env = Env::default();
+    e.mock_all_auths();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    let asset: Address = test_data.rewards_asset_client.address.clone();
+    let max_lock: u64 = test_data.lock_period * 4;
+
+    test_data
+        .contract_client
+        .set_pool(&asset, &test_data.lock_period, &test_data.min_deposit);
+    test_data.contract_client.toggle_pool(&asset, &true);
+    test_data
+        .contract_client
+        .set_lock_boost(&asset, &max_lock, &2_5000000);
+
+    // The compounded rewards are shared by the amount deposited so a pool with a lock boost can't compound
+    assert_eq!(
+        test_data
+            .contract_client
+            .try_set_compounding(&asset, &true)
+            .unwrap_err()
+            .unwrap(),
+        ContractErrors::InvalidCompoundingConfig.into()
+    );
+
+    test_data
+        .contract_client
+        .set_lock_boost(&asset, &max_lock, &1_0000000);
+    test_data.contract_client.set_compounding(&asset, &true);
+
+    assert_eq!(
+        test_data
+            .contract_client
+            .try_set_lock_boost(&asset, &max_lock, &2_0000000)
+            .unwrap_err()
+            .unwrap(),
+        ContractErrors::InvalidLockConfig.into()
+    );
+
+    let short_depositor: Address = Address::generate(&e);
+    let long_depositor: Address = Address::generate(&e);
+    for (depositor, lock_duration) in [
+        (short_depositor.clone(), test_data.lock_period),
+        (long_depositor.clone(), max_lock),
+    ] {
+        test_data
+            .rewards_asset_stellar
+            .mint(&depositor, &(test_data.min_deposit as i128));
+        test_data.contract_client.deposit(
+            &asset,
+            &depositor,
+            &test_data.min_deposit,
+            &lock_duration,
+        );
+    }
+
+    test_data
+        .rewards_asset_stellar
+        .mint(&test_data.manager, &100_0000000);
+    test_data
+        .contract_client
+        .distribute(&test_data.manager, &asset, &asset, &100_0000000);
+
+    // Without a boost both deposits get the same part of the compounded reward
+    for depositor in [short_depositor, long_depositor] {
+        assert_eq!(
+            test_data
+                .contract_client
+                .deposit_balance(&asset, &depositor),
+            test_data.min_deposit + 50_0000000
+        );
+    }
+}
+
+#[test]
+fn test_distribution_config() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    test_data.contract_client.mock_all_auths().set_pool(
+        &test_data.staking_asset_client.address,
+        &test_data.lock_period,
+        &test_data.min_deposit,
+    );
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .toggle_pool(&test_data.staking_asset_client.address, &true);
+
+    let depositor: Address = Address::generate(&e);
+    let outsider: Address = Address::generate(&e);
+    test_data
+        .staking_asset_stellar
+        .mock_all_auths()
+        .mint(&depositor, &(test_data.min_deposit as i128));
+    for distributor in [test_data.manager.clone(), outsider.clone()] {
+        test_data
+            .rewards_asset_stellar
+            .mock_all_auths()
+            .mint(&distributor, &1000_0000000);
+    }
+
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.staking_asset_client.address,
+        &depositor,
+        &test_data.min_deposit,
+        &test_data.lock_period,
+    );
+
+    let invalid_config_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_set_distribution_config(&test_data.staking_asset_client.address, &0, &Vec::new(&e))
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        invalid_config_error,
+        ContractErrors::InvalidDistributionConfig.into()
+    );
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .set_distribution_config(
+            &test_data.staking_asset_client.address,
+            &10_0000000,
+            &vec![&e, test_data.manager.clone()],
+        );
+
+    let below_min_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_distribute(
+            &test_data.manager,
+            &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
+            &9_0000000,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(below_min_error, ContractErrors::CantDistributeReward.into());
+
+    let not_allowed_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_distribute(
+            &outsider,
+            &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
+            &10_0000000,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        not_allowed_error,
+        ContractErrors::DistributorNotAllowed.into()
+    );
+
+    let not_allowed_error = test_data
+        .contract_client
+        .mock_all_auths()
+        .try_fund_rewards(
+            &outsider,
+            &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
+            &100_0000000,
+            &3600,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        not_allowed_error,
+        ContractErrors::DistributorNotAllowed.into()
+    );
+
+    test_data.contract_client.mock_all_auths().distribute(
+        &test_data.manager,
+        &test_data.staking_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &10_0000000,
+    );
+    test_data.contract_client.mock_all_auths().fund_rewards(
+        &test_data.manager,
+        &test_data.staking_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &100_0000000,
+        &3600,
+    );
+
+    assert_eq!(
+        test_data
+            .contract_client
+            .distributed(&test_data.staking_asset_client.address)
+            .get(test_data.rewards_asset_client.address.clone()),
+        Some(110_0000000)
+    );
+
+    // Removing the distributors lets anyone distribute again
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .set_distribution_config(
+            &test_data.staking_asset_client.address,
+            &10_0000000,
+            &Vec::new(&e),
+        );
+
+    test_data.contract_client.mock_all_auths().distribute(
+        &outsider,
+        &test_data.staking_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &10_0000000,
+    );
+
+    assert_eq!(
+        test_data
+            .contract_client
+            .distributed(&test_data.staking_asset_client.address)
+            .get(test_data.rewards_asset_client.address.clone()),
+        Some(120_0000000)
+    );
+}
+
 // TODO: test with multiple deposits and multiple distributions
@@ -0,0 +1,351 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::tests::test_utils::{create_test_data, init_contract, TestData};
+use soroban_sdk::testutils::{Address as _, Ledger, LedgerInfo};
+use soroban_sdk::{Address, Env, Vec};
+
+// A small xorshift generator so the runs are random but reproducible
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn range(&mut self, min: u128, max: u128) -> u128 {
+        min + (self.next() as u128) % (max - min)
+    }
+}
+
+fn set_timestamp(e: &Env, timestamp: u64) {
+    e.ledger().set(LedgerInfo {
+        timestamp,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+}
+
+fn total_balance(client: &soroban_sdk::token::Client, depositors: &Vec<Address>) -> u128 {
+    depositors
+        .iter()
+        .map(|depositor| client.balance(&depositor) as u128)
+        .sum()
+}
+
+const DEPOSITORS_FUNDS: u128 = 1_000_000_0000000;
+
+// Runs random deposits, top ups, claims, early withdrawals, distributions and reward schedules and checks the
+// contract never owes more than it holds, at the end every deposit is withdrawn and the dust must be all that's left
+fn run_random_rewards(seed: u64, compounding: bool) {
+    let e: Env = Env::default();
+    e.mock_all_auths();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    let mut rng: Rng = Rng(seed);
+    let deposit_asset: Address = test_data.staking_asset_client.address.clone();
+    let reward_asset: Address = test_data.rewards_asset_client.address.clone();
+    let max_lock: u64 = test_data.lock_period * 4;
+
+    test_data.contract_client.set_pool(
+        &deposit_asset,
+        &test_data.lock_period,
+        &test_data.min_deposit,
+    );
+    test_data.contract_client.toggle_pool(&deposit_asset, &true);
+
+    // Compounding pools can't have a lock boost, the lock durations still change when each deposit unlocks
+    let max_boost: u128 = if compounding { 1_0000000 } else { 2_5000000 };
+    test_data
+        .contract_client
+        .set_lock_boost(&deposit_asset, &max_lock, &max_boost);
+
+    // Without a treasury the penalties are shared with the remaining depositors in the deposit asset
+    test_data
+        .contract_client
+        .set_early_withdraw(&deposit_asset, &5000000, &None);
+    test_data
+        .contract_client
+        .set_compounding(&deposit_asset, &compounding);
+
+    let mut depositors: Vec<Address> = Vec::new(&e);
+    let mut has_deposit: std::vec::Vec<bool> = std::vec::Vec::new();
+    for _ in 0..5 {
+        let depositor: Address = Address::generate(&e);
+        test_data
+            .staking_asset_stellar
+            .mint(&depositor, &(DEPOSITORS_FUNDS as i128));
+        depositors.push_back(depositor);
+        has_deposit.push(false);
+    }
+    test_data
+        .rewards_asset_stellar
+        .mint(&test_data.manager, &1_000_000_0000000);
+    test_data
+        .staking_asset_stellar
+        .mint(&test_data.manager, &1_000_000_0000000);
+
+    let mut timestamp: u64 = 0;
+    let mut distributed: u128 = 0;
+    let mut distributed_deposit_asset: u128 = 0;
+    for _ in 0..40 {
+        let index: u32 = (rng.next() % depositors.len() as u64) as u32;
+        let depositor: Address = depositors.get(index).unwrap();
+
+        match rng.next() % 6 {
+            0 => {
+                let amount: u128 = rng.range(100_0000000, 5_000_0000000);
+                if test_data
+                    .contract_client
+                    .try_distribute(&test_data.manager, &deposit_asset, &reward_asset, &amount)
+                    .is_ok()
+                {
+                    distributed += amount;
+                }
+            }
+            1 => {
+                let amount: u128 = rng.range(100_0000000, 5_000_0000000);
+                if test_data
+                    .contract_client
+                    .try_distribute(&test_data.manager, &deposit_asset, &deposit_asset, &amount)
+                    .is_ok()
+                {
+                    distributed_deposit_asset += amount;
+                }
+            }
+            2 => {
+                let amount: u128 = rng.range(100_0000000, 5_000_0000000);
+                test_data.contract_client.fund_rewards(
+                    &test_data.manager,
+                    &deposit_asset,
+                    &reward_asset,
+                    &amount,
+                    &(rng.range(3600, 3600 * 24 * 14) as u64),
+                );
+                distributed += amount;
+            }
+            3 => {
+                if has_deposit[index as usize] {
+                    test_data.contract_client.claim(&deposit_asset, &depositor);
+                }
+            }
+            4 => {
+                if has_deposit[index as usize] {
+                    test_data
+                        .contract_client
+                        .early_withdraw(&deposit_asset, &depositor);
+                    has_deposit[index as usize] = false;
+                }
+            }
+            _ => {
+                let lock_duration: u64 = if has_deposit[index as usize] {
+                    max_lock
+                } else {
+                    rng.range(test_data.lock_period as u128, max_lock as u128) as u64
+                };
+
+                test_data.contract_client.deposit(
+                    &deposit_asset,
+                    &depositor,
+                    &rng.range(test_data.min_deposit, 10_000_0000000),
+                    &lock_duration,
+                );
+                has_deposit[index as usize] = true;
+            }
+        }
+
+        timestamp += rng.next() % (3600 * 24);
+        set_timestamp(&e, timestamp);
+
+        // What the depositors can take out at any moment must be in the contract
+        let mut owed: u128 = 0;
+        let mut owed_deposit_asset: u128 = 0;
+        for (i, depositor) in depositors.iter().enumerate() {
+            if !has_deposit[i] {
+                continue;
+            }
+
+            let rewards = test_data
+                .contract_client
+                .pending_rewards(&deposit_asset, &depositor);
+            owed += rewards.get(reward_asset.clone()).unwrap_or(0);
+            owed_deposit_asset += rewards.get(deposit_asset.clone()).unwrap_or(0)
+                + test_data
+                    .contract_client
+                    .deposit_balance(&deposit_asset, &depositor);
+        }
+
+        assert!(
+            owed <= test_data
+                .rewards_asset_client
+                .balance(&test_data.contract_client.address) as u128
+        );
+        assert!(
+            owed_deposit_asset
+                <= test_data
+                    .staking_asset_client
+                    .balance(&test_data.contract_client.address) as u128
+        );
+        assert!(total_balance(&test_data.rewards_asset_client, &depositors) <= distributed);
+    }
+
+    // Every deposit is unlocked and every reward schedule is finished
+    set_timestamp(&e, timestamp + max_lock + 3600 * 24 * 14);
+    for (i, depositor) in depositors.iter().enumerate() {
+        if has_deposit[i] {
+            test_data
+                .contract_client
+                .withdraw(&deposit_asset, &depositor);
+        }
+    }
+
+    let paid: u128 = total_balance(&test_data.rewards_asset_client, &depositors);
+    let paid_deposit_asset: u128 =
+        total_balance(&test_data.staking_asset_client, &depositors) - DEPOSITORS_FUNDS * 5;
+    assert!(paid <= distributed);
+    assert!(paid_deposit_asset <= distributed_deposit_asset);
+
+    // Once every deposit is withdrawn everything left in the contract is dust and it can be recovered completely
+    let dust: u128 = test_data
+        .contract_client
+        .dust(&deposit_asset, &reward_asset);
+    let dust_deposit_asset: u128 = test_data
+        .contract_client
+        .dust(&deposit_asset, &deposit_asset);
+    assert_eq!(paid + dust, distributed);
+    assert_eq!(
+        paid_deposit_asset + dust_deposit_asset,
+        distributed_deposit_asset
+    );
+
+    let treasury: Address = Address::generate(&e);
+    assert_eq!(
+        test_data
+            .contract_client
+            .sweep_dust(&deposit_asset, &reward_asset, &treasury),
+        dust
+    );
+    assert_eq!(
+        test_data
+            .contract_client
+            .sweep_dust(&deposit_asset, &deposit_asset, &treasury),
+        dust_deposit_asset
+    );
+    assert_eq!(
+        test_data.rewards_asset_client.balance(&treasury) as u128,
+        dust
+    );
+    assert_eq!(
+        test_data
+            .rewards_asset_client
+            .balance(&test_data.contract_client.address),
+        0
+    );
+    assert_eq!(
+        test_data
+            .staking_asset_client
+            .balance(&test_data.contract_client.address),
+        0
+    );
+    assert_eq!(
+        test_data
+            .contract_client
+            .sweep_dust(&deposit_asset, &reward_asset, &treasury),
+        0
+    );
+}
+
+#[test]
+fn test_rewards_never_exceed_distributed() {
+    for seed in [1, 7, 42, 1337, 0xdead_beef] {
+        run_random_rewards(seed, false);
+    }
+}
+
+#[test]
+fn test_compounding_rewards_never_exceed_distributed() {
+    for seed in [3, 11, 99, 2024, 0xcafe_babe] {
+        run_random_rewards(seed, true);
+    }
+}
+
+#[test]
+fn test_streamed_rewards_without_depositors_go_to_dust() {
+    let e: Env = Env::default();
+    e.mock_all_auths();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    let deposit_asset: Address = test_data.staking_asset_client.address.clone();
+    let reward_asset: Address = test_data.rewards_asset_client.address.clone();
+
+    test_data.contract_client.set_pool(
+        &deposit_asset,
+        &test_data.lock_period,
+        &test_data.min_deposit,
+    );
+    test_data.contract_client.toggle_pool(&deposit_asset, &true);
+    test_data
+        .rewards_asset_stellar
+        .mint(&test_data.manager, &700_0000000);
+
+    // 700 units over 7000 seconds without any deposit in the pool
+    test_data.contract_client.fund_rewards(
+        &test_data.manager,
+        &deposit_asset,
+        &reward_asset,
+        &700_0000000,
+        &7000,
+    );
+
+    set_timestamp(&e, 3500);
+
+    let depositor: Address = Address::generate(&e);
+    test_data
+        .staking_asset_stellar
+        .mint(&depositor, &(test_data.min_deposit as i128));
+    test_data.contract_client.deposit(
+        &deposit_asset,
+        &depositor,
+        &test_data.min_deposit,
+        &test_data.lock_period,
+    );
+
+    assert_eq!(
+        test_data
+            .contract_client
+            .dust(&deposit_asset, &reward_asset),
+        350_0000000
+    );
+}
+
+#[test]
+fn test_sweep_dust_requires_manager() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    test_data.contract_client.mock_all_auths().set_pool(
+        &test_data.staking_asset_client.address,
+        &test_data.lock_period,
+        &test_data.min_deposit,
+    );
+
+    assert!(test_data
+        .contract_client
+        .try_sweep_dust(
+            &test_data.staking_asset_client.address,
+            &test_data.rewards_asset_client.address,
+            &test_data.manager,
+        )
+        .is_err());
+}
@@ -0,0 +1,183 @@
+#![cfg(test)]
+
+use crate::storage::deposits::{Deposit, DepositsStorageFunc, DepositsStorageKeys, LegacyDeposit};
+use crate::storage::pools::{LegacyPool, Pool, PoolDataKeys, PoolsDataFunc};
+use crate::storage::voting::{MAX_VOTING_LOCK, WEEK};
+use crate::tests::test_utils::{create_test_data, init_contract, TestData};
+use soroban_sdk::testutils::{Address as _, Ledger, LedgerInfo};
+use soroban_sdk::{Address, Env, Map, Symbol, Val};
+
+// Saves a pool and two deposits with the layout they had before the upgrade, 100 units were distributed to the pool
+// with the old 1e7 factor so each deposit has 50 units of rewards pending
+fn set_legacy_state(e: &Env, test_data: &TestData, depositors: &[Address; 2]) {
+    e.as_contract(&test_data.contract_client.address, || {
+        e.storage().persistent().set(
+            &PoolDataKeys::Pool(test_data.staking_asset_client.address.clone()),
+            &LegacyPool {
+                active: true,
+                asset: test_data.staking_asset_client.address.clone(),
+                balance: test_data.min_deposit * 2,
+                deposits: 2,
+                factor: (100_0000000 * 1_0000000) / (test_data.min_deposit * 2),
+                lock_period: test_data.lock_period,
+                min_deposit: test_data.min_deposit,
+            },
+        );
+
+        for depositor in depositors.iter() {
+            e.storage().persistent().set(
+                &DepositsStorageKeys::Deposit((
+                    test_data.staking_asset_client.address.clone(),
+                    depositor.clone(),
+                )),
+                &LegacyDeposit {
+                    amount: test_data.min_deposit,
+                    unlocks_at: test_data.lock_period,
+                    snapshot: 0,
+                },
+            );
+        }
+    });
+
+    test_data.staking_asset_stellar.mock_all_auths().mint(
+        &test_data.contract_client.address,
+        &((test_data.min_deposit * 2) as i128),
+    );
+    test_data
+        .rewards_asset_stellar
+        .mock_all_auths()
+        .mint(&test_data.contract_client.address, &100_0000000);
+}
+
+#[test]
+fn test_reading_legacy_storage() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    let depositors: [Address; 2] = [Address::generate(&e), Address::generate(&e)];
+    set_legacy_state(&e, &test_data, &depositors);
+
+    e.as_contract(&test_data.contract_client.address, || {
+        let pool: Pool = e
+            ._pools()
+            .pool(&test_data.staking_asset_client.address)
+            .unwrap();
+        assert_eq!(pool.balance, test_data.min_deposit * 2);
+        assert_eq!(pool.principal, test_data.min_deposit * 2);
+        assert_eq!(pool.shares, test_data.min_deposit * 2);
+        assert_eq!(pool.max_lock_period, test_data.lock_period);
+
+        let deposit: Deposit = e
+            ._deposits()
+            .get(&test_data.staking_asset_client.address, &depositors[0])
+            .unwrap();
+        assert_eq!(deposit.amount, test_data.min_deposit);
+        assert_eq!(deposit.shares, test_data.min_deposit);
+        assert_eq!(deposit.weight, test_data.min_deposit);
+        assert_eq!(deposit.locked_at, 0);
+        assert_eq!(deposit.unlocks_at, test_data.lock_period);
+    });
+
+    // The deposits from before the voting power existed are counted once they are checkpointed
+    assert_eq!(
+        test_data.contract_client.voting_power(&depositors[0], &0),
+        0
+    );
+    assert_eq!(test_data.contract_client.total_voting_power(&0), 0);
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .set_voting_weight(&test_data.staking_asset_client.address, &1_0000000);
+    for depositor in depositors.iter() {
+        test_data
+            .contract_client
+            .checkpoint(depositor, &test_data.staking_asset_client.address);
+    }
+
+    let slope: u128 = (test_data.min_deposit * 1_0000000) / (MAX_VOTING_LOCK as u128);
+    let end: u64 = (test_data.lock_period / WEEK) * WEEK;
+    assert_eq!(
+        test_data.contract_client.voting_power(&depositors[0], &0),
+        (slope * (end as u128)) / 1_0000000
+    );
+    assert_eq!(
+        test_data.contract_client.total_voting_power(&0),
+        (slope * 2 * (end as u128)) / 1_0000000
+    );
+
+    // The rewards pending from before the upgrade are kept
+    for depositor in depositors.iter() {
+        assert_eq!(
+            test_data
+                .contract_client
+                .pending_rewards(&test_data.staking_asset_client.address, depositor)
+                .get(test_data.rewards_asset_client.address.clone()),
+            Some(50_0000000)
+        );
+    }
+
+    test_data
+        .rewards_asset_stellar
+        .mock_all_auths()
+        .mint(&test_data.manager, &100_0000000);
+    test_data.contract_client.mock_all_auths().distribute(
+        &test_data.manager,
+        &test_data.staking_asset_client.address,
+        &test_data.rewards_asset_client.address,
+        &100_0000000,
+    );
+
+    // The pool is saved with the current layout once it's updated
+    e.as_contract(&test_data.contract_client.address, || {
+        let stored: Map<Symbol, Val> = e
+            .storage()
+            .persistent()
+            .get(&PoolDataKeys::Pool(
+                test_data.staking_asset_client.address.clone(),
+            ))
+            .unwrap();
+        assert!(stored.contains_key(Symbol::new(&e, "factors")));
+        assert!(!stored.contains_key(Symbol::new(&e, "factor")));
+    });
+
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .claim(&test_data.staking_asset_client.address, &depositors[0]);
+
+    e.ledger().set(LedgerInfo {
+        timestamp: test_data.lock_period,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+
+    for depositor in depositors.iter() {
+        test_data
+            .contract_client
+            .mock_all_auths()
+            .withdraw(&test_data.staking_asset_client.address, depositor);
+
+        assert_eq!(
+            test_data.rewards_asset_client.balance(depositor),
+            100_0000000
+        );
+        assert_eq!(
+            test_data.staking_asset_client.balance(depositor) as u128,
+            test_data.min_deposit
+        );
+    }
+
+    assert_eq!(
+        test_data
+            .rewards_asset_client
+            .balance(&test_data.contract_client.address),
+        0
+    );
+}
@@ -54,7 +54,12 @@ pub fn test_setting_a_pool() {
         assert_eq!(&pool.active, &false);
         assert_eq!(&pool.balance, &0);
         assert_eq!(&pool.deposits, &0);
-        assert_eq!(&pool.factor, &0);
+        assert_eq!(
+            &pool
+                .factors
+                .get(test_data.rewards_asset_client.address.clone()),
+            &Some(0)
+        );
     });
 
     test_data.contract_client.mock_all_auths().set_pool(
@@ -75,7 +80,12 @@ pub fn test_setting_a_pool() {
         assert_eq!(&pool.active, &false);
         assert_eq!(&pool.balance, &0);
         assert_eq!(&pool.deposits, &0);
-        assert_eq!(&pool.factor, &0);
+        assert_eq!(
+            &pool
+                .factors
+                .get(test_data.rewards_asset_client.address.clone()),
+            &Some(0)
+        );
     });
 }
 
@@ -171,6 +181,7 @@ pub fn test_remove_pool() {
         &test_data.staking_asset_client.address,
         &depositor,
         &test_data.min_deposit,
+        &test_data.lock_period,
     );
 
     let cant_remove_pool_error = test_data
@@ -4,7 +4,7 @@ use crate::contract::{LockingPoolContract, LockingPoolContractClient};
 use soroban_sdk::testutils::Address as _;
 use soroban_sdk::{token, Address, Env};
 
-fn create_token_contract<'a>(
+pub fn create_token_contract<'a>(
     e: &Env,
     admin: &Address,
 ) -> (token::Client<'a>, token::StellarAssetClient<'a>) {
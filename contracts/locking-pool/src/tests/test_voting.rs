@@ -0,0 +1,355 @@
+#![cfg(test)]
+
+use crate::storage::voting::{MAX_VOTING_LOCK, WEEK};
+use crate::tests::test_utils::{create_test_data, init_contract, TestData};
+use soroban_sdk::testutils::{Address as _, Ledger, LedgerInfo};
+use soroban_sdk::{Address, Env};
+
+fn slope(amount: u128) -> u128 {
+    (amount * 1_0000000) / (MAX_VOTING_LOCK as u128)
+}
+
+fn set_timestamp(e: &Env, timestamp: u64) {
+    e.ledger().set(LedgerInfo {
+        timestamp,
+        protocol_version: 22,
+        sequence_number: e.ledger().sequence(),
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: 1,
+        min_persistent_entry_ttl: 1,
+        max_entry_ttl: u32::MAX,
+    });
+}
+
+#[test]
+fn test_voting_power() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    test_data.contract_client.mock_all_auths().set_pool(
+        &test_data.staking_asset_client.address,
+        &WEEK,
+        &test_data.min_deposit,
+    );
+    test_data.contract_client.mock_all_auths().set_lock_boost(
+        &test_data.staking_asset_client.address,
+        &(WEEK * 4),
+        &1_0000000,
+    );
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .toggle_pool(&test_data.staking_asset_client.address, &true);
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .set_voting_weight(&test_data.staking_asset_client.address, &1_0000000);
+
+    let long_depositor: Address = Address::generate(&e);
+    let short_depositor: Address = Address::generate(&e);
+    for depositor in [long_depositor.clone(), short_depositor.clone()] {
+        test_data
+            .staking_asset_stellar
+            .mock_all_auths()
+            .mint(&depositor, &(test_data.min_deposit as i128));
+    }
+
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.staking_asset_client.address,
+        &long_depositor,
+        &test_data.min_deposit,
+        &(WEEK * 4),
+    );
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.staking_asset_client.address,
+        &short_depositor,
+        &test_data.min_deposit,
+        &WEEK,
+    );
+
+    let slope: u128 = slope(test_data.min_deposit);
+    let initial_total: u128 = (slope * ((WEEK * 5) as u128)) / 1_0000000;
+
+    assert_eq!(
+        test_data.contract_client.voting_power(&long_depositor, &0),
+        (slope * ((WEEK * 4) as u128)) / 1_0000000
+    );
+    assert_eq!(
+        test_data.contract_client.voting_power(&short_depositor, &0),
+        (slope * (WEEK as u128)) / 1_0000000
+    );
+    assert_eq!(
+        test_data.contract_client.total_voting_power(&0),
+        initial_total
+    );
+
+    // The power decays until the deposits unlock
+    assert_eq!(
+        test_data
+            .contract_client
+            .voting_power(&short_depositor, &WEEK),
+        0
+    );
+    assert_eq!(
+        test_data.contract_client.total_voting_power(&(WEEK * 2)),
+        (slope * ((WEEK * 2) as u128)) / 1_0000000
+    );
+
+    set_timestamp(&e, WEEK * 2);
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .withdraw(&test_data.staking_asset_client.address, &short_depositor);
+
+    set_timestamp(&e, WEEK * 4);
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .withdraw(&test_data.staking_asset_client.address, &long_depositor);
+
+    // The historical values are kept after the deposits are withdrawn
+    assert_eq!(
+        test_data
+            .contract_client
+            .voting_power(&long_depositor, &(WEEK * 4)),
+        0
+    );
+    assert_eq!(test_data.contract_client.total_voting_power(&(WEEK * 4)), 0);
+    assert_eq!(
+        test_data
+            .contract_client
+            .voting_power(&long_depositor, &WEEK),
+        (slope * ((WEEK * 3) as u128)) / 1_0000000
+    );
+    assert_eq!(
+        test_data.contract_client.total_voting_power(&0),
+        initial_total
+    );
+}
+
+#[test]
+fn test_voting_lock_is_capped() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    // The lock period of the pool is longer than the max voting lock
+    test_data.contract_client.mock_all_auths().set_pool(
+        &test_data.staking_asset_client.address,
+        &(MAX_VOTING_LOCK + WEEK * 10),
+        &test_data.min_deposit,
+    );
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .toggle_pool(&test_data.staking_asset_client.address, &true);
+    test_data
+        .contract_client
+        .mock_all_auths()
+        .set_voting_weight(&test_data.staking_asset_client.address, &1_0000000);
+
+    let depositor: Address = Address::generate(&e);
+    test_data
+        .staking_asset_stellar
+        .mock_all_auths()
+        .mint(&depositor, &(test_data.min_deposit as i128));
+    test_data.contract_client.mock_all_auths().deposit(
+        &test_data.staking_asset_client.address,
+        &depositor,
+        &test_data.min_deposit,
+        &(MAX_VOTING_LOCK + WEEK * 10),
+    );
+
+    let power: u128 = (slope(test_data.min_deposit) * (MAX_VOTING_LOCK as u128)) / 1_0000000;
+    assert_eq!(
+        test_data.contract_client.voting_power(&depositor, &0),
+        power
+    );
+    assert_eq!(test_data.contract_client.total_voting_power(&0), power);
+    assert_eq!(
+        test_data
+            .contract_client
+            .total_voting_power(&(MAX_VOTING_LOCK / 2)),
+        power / 2
+    );
+    assert_eq!(
+        test_data
+            .contract_client
+            .total_voting_power(&MAX_VOTING_LOCK),
+        0
+    );
+}
+
+#[test]
+fn test_voting_weight_of_each_pool() {
+    let e: Env = Env::default();
+    e.mock_all_auths();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    let first_asset: Address = test_data.staking_asset_client.address.clone();
+    let second_asset: Address = test_data.rewards_asset_client.address.clone();
+    for asset in [first_asset.clone(), second_asset.clone()] {
+        test_data
+            .contract_client
+            .set_pool(&asset, &(WEEK * 4), &test_data.min_deposit);
+        test_data.contract_client.toggle_pool(&asset, &true);
+    }
+
+    let depositor: Address = Address::generate(&e);
+    test_data
+        .staking_asset_stellar
+        .mint(&depositor, &(test_data.min_deposit as i128));
+    test_data
+        .rewards_asset_stellar
+        .mint(&depositor, &(test_data.min_deposit as i128));
+
+    // Pools without a voting weight don't give voting power
+    test_data.contract_client.deposit(
+        &first_asset,
+        &depositor,
+        &test_data.min_deposit,
+        &(WEEK * 4),
+    );
+    assert_eq!(test_data.contract_client.voting_weight(&first_asset), 0);
+    assert_eq!(test_data.contract_client.voting_power(&depositor, &0), 0);
+
+    // A unit of the second asset is worth ten units of the first one
+    test_data
+        .contract_client
+        .set_voting_weight(&first_asset, &1_0000000);
+    test_data
+        .contract_client
+        .set_voting_weight(&second_asset, &10_0000000);
+    test_data
+        .contract_client
+        .checkpoint(&depositor, &first_asset);
+    test_data.contract_client.deposit(
+        &second_asset,
+        &depositor,
+        &test_data.min_deposit,
+        &(WEEK * 4),
+    );
+
+    let first_slope: u128 = slope(test_data.min_deposit);
+    let second_slope: u128 = slope(test_data.min_deposit * 10);
+    let first_power: u128 = (first_slope * ((WEEK * 4) as u128)) / 1_0000000;
+    let total_power: u128 = ((first_slope + second_slope) * ((WEEK * 4) as u128)) / 1_0000000;
+    assert_eq!(
+        test_data.contract_client.voting_power(&depositor, &0),
+        total_power
+    );
+    assert_eq!(
+        test_data.contract_client.total_voting_power(&0),
+        total_power
+    );
+
+    // The locks keep their weight until they are checkpointed again
+    test_data
+        .contract_client
+        .set_voting_weight(&second_asset, &0);
+    assert_eq!(
+        test_data.contract_client.voting_power(&depositor, &0),
+        total_power
+    );
+    test_data
+        .contract_client
+        .checkpoint(&depositor, &second_asset);
+    assert_eq!(
+        test_data.contract_client.voting_power(&depositor, &0),
+        first_power
+    );
+    assert_eq!(
+        test_data.contract_client.total_voting_power(&0),
+        first_power
+    );
+}
+
+#[test]
+fn test_set_voting_weight_requires_manager() {
+    let e: Env = Env::default();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    test_data.contract_client.mock_all_auths().set_pool(
+        &test_data.staking_asset_client.address,
+        &WEEK,
+        &test_data.min_deposit,
+    );
+
+    assert!(test_data
+        .contract_client
+        .try_set_voting_weight(&test_data.staking_asset_client.address, &1_0000000)
+        .is_err());
+}
+
+#[test]
+fn test_checkpoint_total_in_batches() {
+    let e: Env = Env::default();
+    e.mock_all_auths();
+    let test_data: TestData = create_test_data(&e);
+    init_contract(&test_data);
+
+    let asset: Address = test_data.staking_asset_client.address.clone();
+    test_data
+        .contract_client
+        .set_pool(&asset, &WEEK, &test_data.min_deposit);
+    test_data
+        .contract_client
+        .set_lock_boost(&asset, &(WEEK * 8), &1_0000000);
+    test_data.contract_client.toggle_pool(&asset, &true);
+    test_data
+        .contract_client
+        .set_voting_weight(&asset, &1_0000000);
+
+    let depositor: Address = Address::generate(&e);
+    test_data
+        .staking_asset_stellar
+        .mint(&depositor, &(test_data.min_deposit as i128 * 2));
+    test_data
+        .contract_client
+        .deposit(&asset, &depositor, &test_data.min_deposit, &(WEEK * 8));
+
+    let slope: u128 = slope(test_data.min_deposit);
+
+    // After a long time without updates the total can be advanced a few weeks at a time
+    set_timestamp(&e, WEEK * 10 + 100);
+    assert_eq!(test_data.contract_client.checkpoint_total(&3), WEEK * 3);
+    assert_eq!(test_data.contract_client.checkpoint_total(&3), WEEK * 6);
+    assert_eq!(test_data.contract_client.checkpoint_total(&3), WEEK * 9);
+    assert_eq!(
+        test_data.contract_client.checkpoint_total(&3),
+        WEEK * 10 + 100
+    );
+    assert_eq!(
+        test_data.contract_client.checkpoint_total(&3),
+        WEEK * 10 + 100
+    );
+
+    // The history is the same as without the intermediate checkpoints
+    assert_eq!(
+        test_data.contract_client.total_voting_power(&(WEEK * 2)),
+        (slope * ((WEEK * 6) as u128)) / 1_0000000
+    );
+    assert_eq!(
+        test_data.contract_client.total_voting_power(&(WEEK * 7)),
+        (slope * (WEEK as u128)) / 1_0000000
+    );
+    assert_eq!(test_data.contract_client.total_voting_power(&(WEEK * 9)), 0);
+
+    // The deposit unlocked so the next update starts from an empty total
+    test_data.contract_client.withdraw(&asset, &depositor);
+    test_data
+        .contract_client
+        .deposit(&asset, &depositor, &test_data.min_deposit, &(WEEK * 8));
+
+    let end: u64 = ((WEEK * 18 + 100) / WEEK) * WEEK;
+    assert_eq!(
+        test_data
+            .contract_client
+            .total_voting_power(&(WEEK * 10 + 100)),
+        (slope * ((end - (WEEK * 10 + 100)) as u128)) / 1_0000000
+    );
+}
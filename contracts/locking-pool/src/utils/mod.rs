@@ -1 +1,4 @@
 pub mod core;
+pub mod rewards;
+pub mod voting;
+pub mod withdrawals;
@@ -0,0 +1,150 @@
+use crate::errors::ContractErrors;
+use crate::storage::deposits::Deposit;
+use crate::storage::pools::{Pool, RewardStream, FACTOR_PRECISION};
+use soroban_sdk::{panic_with_error, token, Address, Env, Map};
+
+pub fn shares_for_amount(pool: &Pool, amount: u128) -> u128 {
+    if pool.shares == 0 || pool.principal == 0 {
+        return amount;
+    }
+
+    (amount * pool.shares) / pool.principal
+}
+
+pub fn shares_value(pool: &Pool, shares: u128) -> u128 {
+    if pool.shares == 0 {
+        return 0;
+    }
+
+    (shares * pool.principal) / pool.shares
+}
+
+// Adds the reward to the depositors, if the pool is compounding and the reward is in the deposit asset it's added to
+// the principal so the value of the shares grows. The part of the reward that can't be added to the factor is carried
+// to the next reward instead of being lost.
+pub fn add_reward(pool: &mut Pool, asset: &Address, amount: u128) {
+    if pool.compounding && asset == &pool.asset {
+        pool.principal += amount;
+    } else {
+        let factor: u128 = pool.factors.get(asset.clone()).unwrap_or(0);
+        let scaled: u128 =
+            amount * FACTOR_PRECISION + pool.remainders.get(asset.clone()).unwrap_or(0);
+
+        pool.factors
+            .set(asset.clone(), factor + scaled / pool.balance);
+        pool.remainders.set(asset.clone(), scaled % pool.balance);
+    }
+}
+
+// If the pool has a list of distributors only they can send rewards to it
+pub fn validate_distributor(e: &Env, pool: &Pool, caller: &Address) {
+    if !pool.distributors.is_empty() && !pool.distributors.contains(caller) {
+        panic_with_error!(&e, &ContractErrors::DistributorNotAllowed);
+    }
+}
+
+pub fn record_distribution(pool: &mut Pool, asset: &Address, amount: u128) {
+    let distributed: u128 = pool.distributed.get(asset.clone()).unwrap_or(0);
+    pool.distributed.set(asset.clone(), distributed + amount);
+}
+
+// Moves the scaled `amount` to the dust of the asset
+pub fn add_dust(pool: &mut Pool, asset: &Address, amount: u128) {
+    if amount == 0 {
+        return;
+    }
+
+    let dust: u128 = pool.dust.get(asset.clone()).unwrap_or(0);
+    pool.dust.set(asset.clone(), dust + amount);
+}
+
+// Calculates the weight of a deposit locked for `lock_duration`, the duration must be already validated
+pub fn calculate_weight(pool: &Pool, amount: u128, lock_duration: u64) -> u128 {
+    if pool.max_lock_period <= pool.lock_period {
+        return amount;
+    }
+
+    let boost: u128 = 1_0000000
+        + ((pool.max_boost - 1_0000000) * ((lock_duration - pool.lock_period) as u128))
+            / ((pool.max_lock_period - pool.lock_period) as u128);
+
+    (amount * boost) / 1_0000000
+}
+
+// Adds to the factors the rewards streamed since the last update, if the pool has no balance the rewards streamed
+// in that time are not assigned to anyone and go to the dust
+pub fn accrue_rewards(e: &Env, pool: &mut Pool) {
+    let now: u64 = e.ledger().timestamp();
+
+    for (asset, stream) in pool.streams.iter() {
+        let until: u64 = now.min(stream.period_finish);
+        if until <= stream.last_update {
+            continue;
+        }
+
+        let emitted: u128 = stream.rate * ((until - stream.last_update) as u128);
+        if pool.balance > 0 {
+            add_reward(pool, &asset, emitted);
+        } else {
+            add_dust(pool, &asset, emitted * FACTOR_PRECISION);
+        }
+
+        pool.streams.set(
+            asset,
+            RewardStream {
+                rate: stream.rate,
+                period_finish: stream.period_finish,
+                last_update: until,
+            },
+        );
+    }
+}
+
+// Calculates the pending rewards of the deposit for each one of the reward assets of the pool
+pub fn calculate_rewards(e: &Env, pool: &Pool, deposit: &Deposit) -> Map<Address, u128> {
+    let mut rewards: Map<Address, u128> = Map::new(e);
+
+    for (asset, factor) in pool.factors.iter() {
+        let snapshot: u128 = deposit.snapshots.get(asset.clone()).unwrap_or(0);
+        rewards.set(
+            asset,
+            (deposit.weight * (factor - snapshot)) / FACTOR_PRECISION,
+        );
+    }
+
+    rewards
+}
+
+// Same as `calculate_rewards` but it also adds the rounding of each reward to the dust of the pool, it must only be
+// used when the rewards are going to be paid
+pub fn settle_rewards(e: &Env, pool: &mut Pool, deposit: &Deposit) -> Map<Address, u128> {
+    let mut rewards: Map<Address, u128> = Map::new(e);
+
+    for (asset, factor) in pool.factors.iter() {
+        let snapshot: u128 = deposit.snapshots.get(asset.clone()).unwrap_or(0);
+        let scaled: u128 = deposit.weight * (factor - snapshot);
+
+        add_dust(pool, &asset, scaled % FACTOR_PRECISION);
+        rewards.set(asset, scaled / FACTOR_PRECISION);
+    }
+
+    rewards
+}
+
+pub fn send_rewards(e: &Env, to: &Address, rewards: &Map<Address, u128>) {
+    for (asset, reward) in rewards.iter() {
+        if reward == 0 {
+            continue;
+        }
+
+        let result = token::Client::new(e, &asset).try_transfer(
+            &e.current_contract_address(),
+            to,
+            &(reward as i128),
+        );
+
+        if result.is_err() {
+            panic_with_error!(&e, &ContractErrors::RewardsWithdrawFailed);
+        }
+    }
+}
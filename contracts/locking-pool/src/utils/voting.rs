@@ -0,0 +1,229 @@
+use crate::storage::deposits::Deposit;
+use crate::storage::voting::{
+    AccountCheckpoint, TotalCheckpoint, VotingLock, VotingStorageFunc, MAX_VOTING_LOCK, WEEK,
+};
+use soroban_sdk::{Address, Env, Map};
+
+// The voting lock of the deposit, None if it already ended or the pool has no voting weight. Locks longer than
+// `MAX_VOTING_LOCK` only give voting power for `MAX_VOTING_LOCK`
+pub fn voting_lock(e: &Env, pool_asset: &Address, deposit: &Deposit) -> Option<VotingLock> {
+    let now: u64 = e.ledger().timestamp();
+    let end: u64 = (deposit.unlocks_at.min(now + MAX_VOTING_LOCK) / WEEK) * WEEK;
+    let weight: u128 = e._voting().pool_weight(pool_asset);
+    if end <= now || weight == 0 {
+        return None;
+    }
+
+    Some(VotingLock {
+        slope: (deposit.amount * weight) / (MAX_VOTING_LOCK as u128),
+        end,
+    })
+}
+
+// Moves the total checkpoint towards `timestamp` applying the slope changes of the weeks in between, it stops after
+// `max_weeks` weeks so the returned checkpoint can be before `timestamp`
+pub fn advance_total_checkpoint_by(
+    e: &Env,
+    checkpoint: &TotalCheckpoint,
+    timestamp: u64,
+    max_weeks: u32,
+) -> TotalCheckpoint {
+    let mut updated: TotalCheckpoint = checkpoint.clone();
+    let mut week: u64 = (checkpoint.timestamp / WEEK) * WEEK;
+
+    for _ in 0..max_weeks {
+        if updated.timestamp >= timestamp {
+            break;
+        }
+
+        week += WEEK;
+        let until: u64 = week.min(timestamp);
+        updated.bias = updated
+            .bias
+            .saturating_sub(updated.slope * ((until - updated.timestamp) as u128));
+        updated.timestamp = until;
+
+        if until == week {
+            updated.slope = updated.slope.saturating_sub(e._voting().slope_change(week));
+        }
+    }
+
+    updated
+}
+
+// Moves the total checkpoint to `timestamp`
+pub fn advance_total_checkpoint(
+    e: &Env,
+    checkpoint: &TotalCheckpoint,
+    timestamp: u64,
+) -> TotalCheckpoint {
+    // Locks end at most `MAX_VOTING_LOCK` (208 weeks) after the last checkpoint, so we don't need to check more than that
+    let updated: TotalCheckpoint = advance_total_checkpoint_by(e, checkpoint, timestamp, 255);
+    if updated.timestamp >= timestamp {
+        return updated;
+    }
+
+    TotalCheckpoint {
+        timestamp,
+        bias: 0,
+        slope: 0,
+    }
+}
+
+// Saves the total checkpoint advanced by up to `max_weeks` weeks towards the current time and returns the timestamp
+// it reached, if nobody used the pools in a long time this can be called a few times so the next updates don't need
+// to go through every week since the last checkpoint
+pub fn checkpoint_total(e: &Env, max_weeks: u32) -> u64 {
+    let now: u64 = e.ledger().timestamp();
+    let total_checkpoints: u32 = e._voting().total_checkpoints();
+    if total_checkpoints == 0 {
+        return now;
+    }
+
+    let checkpoint: TotalCheckpoint = e._voting().total_checkpoint(total_checkpoints - 1).unwrap();
+    if checkpoint.timestamp >= now {
+        return checkpoint.timestamp;
+    }
+
+    let updated: TotalCheckpoint = advance_total_checkpoint_by(e, &checkpoint, now, max_weeks);
+    if updated.timestamp > checkpoint.timestamp {
+        e._voting()
+            .set_total_checkpoint(total_checkpoints, &updated);
+    }
+
+    updated.timestamp
+}
+
+// Replaces the lock the account has in the pool (None if the deposit was removed) and saves the new checkpoints
+pub fn checkpoint_lock(
+    e: &Env,
+    account: &Address,
+    pool_asset: &Address,
+    new_lock: &Option<VotingLock>,
+) {
+    let now: u64 = e.ledger().timestamp();
+
+    let account_checkpoints: u32 = e._voting().account_checkpoints(account);
+    let last_account_checkpoint: Option<AccountCheckpoint> = if account_checkpoints > 0 {
+        e._voting()
+            .account_checkpoint(account, account_checkpoints - 1)
+    } else {
+        None
+    };
+
+    let total_checkpoints: u32 = e._voting().total_checkpoints();
+    let last_total_checkpoint: Option<TotalCheckpoint> = if total_checkpoints > 0 {
+        e._voting().total_checkpoint(total_checkpoints - 1)
+    } else {
+        None
+    };
+
+    let mut locks: Map<Address, VotingLock> = Map::new(e);
+    if let Some(checkpoint) = last_account_checkpoint.clone() {
+        for (asset, lock) in checkpoint.locks.iter() {
+            if lock.end > now {
+                locks.set(asset, lock);
+            }
+        }
+    }
+
+    let mut total: TotalCheckpoint = match last_total_checkpoint.clone() {
+        None => TotalCheckpoint {
+            timestamp: now,
+            bias: 0,
+            slope: 0,
+        },
+        Some(checkpoint) => advance_total_checkpoint(e, &checkpoint, now),
+    };
+
+    if let Some(old_lock) = locks.get(pool_asset.clone()) {
+        total.bias -= old_lock.slope * ((old_lock.end - now) as u128);
+        total.slope -= old_lock.slope;
+        e._voting().set_slope_change(
+            old_lock.end,
+            e._voting().slope_change(old_lock.end) - old_lock.slope,
+        );
+        locks.remove(pool_asset.clone());
+    }
+
+    if let Some(lock) = new_lock.clone() {
+        total.bias += lock.slope * ((lock.end - now) as u128);
+        total.slope += lock.slope;
+        e._voting()
+            .set_slope_change(lock.end, e._voting().slope_change(lock.end) + lock.slope);
+        locks.set(pool_asset.clone(), lock);
+    }
+
+    // Multiple updates in the same ledger overwrite the same checkpoint
+    let total_index: u32 = match last_total_checkpoint {
+        Some(checkpoint) if checkpoint.timestamp == now => total_checkpoints - 1,
+        _ => total_checkpoints,
+    };
+    e._voting().set_total_checkpoint(total_index, &total);
+
+    let account_index: u32 = match last_account_checkpoint {
+        Some(checkpoint) if checkpoint.timestamp == now => account_checkpoints - 1,
+        _ => account_checkpoints,
+    };
+    e._voting().set_account_checkpoint(
+        account,
+        account_index,
+        &AccountCheckpoint {
+            timestamp: now,
+            locks,
+        },
+    );
+}
+
+pub fn account_voting_power(e: &Env, account: &Address, timestamp: u64) -> u128 {
+    // We look for the last checkpoint saved before the timestamp
+    let mut low: u32 = 0;
+    let mut high: u32 = e._voting().account_checkpoints(account);
+    while low < high {
+        let middle: u32 = (low + high) / 2;
+        if e._voting()
+            .account_checkpoint(account, middle)
+            .unwrap()
+            .timestamp
+            <= timestamp
+        {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+
+    if low == 0 {
+        return 0;
+    }
+
+    let checkpoint: AccountCheckpoint = e._voting().account_checkpoint(account, low - 1).unwrap();
+    let mut power: u128 = 0;
+    for (_, lock) in checkpoint.locks.iter() {
+        if lock.end > timestamp {
+            power += lock.slope * ((lock.end - timestamp) as u128);
+        }
+    }
+
+    power / 1_0000000
+}
+
+pub fn total_voting_power(e: &Env, timestamp: u64) -> u128 {
+    let mut low: u32 = 0;
+    let mut high: u32 = e._voting().total_checkpoints();
+    while low < high {
+        let middle: u32 = (low + high) / 2;
+        if e._voting().total_checkpoint(middle).unwrap().timestamp <= timestamp {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+
+    if low == 0 {
+        return 0;
+    }
+
+    let checkpoint: TotalCheckpoint = e._voting().total_checkpoint(low - 1).unwrap();
+    advance_total_checkpoint(e, &checkpoint, timestamp).bias / 1_0000000
+}
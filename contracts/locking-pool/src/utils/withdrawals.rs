@@ -0,0 +1,105 @@
+use crate::errors::ContractErrors;
+use crate::storage::deposits::{Deposit, DepositsStorageFunc};
+use crate::storage::pools::Pool;
+use crate::storage::pools::FACTOR_PRECISION;
+use crate::utils::rewards::{
+    accrue_rewards, add_dust, add_reward, send_rewards, settle_rewards, shares_value,
+};
+use crate::utils::voting::checkpoint_lock;
+use soroban_sdk::{panic_with_error, token, Address, Env, Map};
+
+// The penalty of withdrawing the deposit now, it goes linearly from the `early_withdraw_penalty` of the pool when the
+// deposit is locked to 0 when it unlocks
+pub fn calculate_penalty(e: &Env, pool: &Pool, deposit: &Deposit) -> u128 {
+    let now: u64 = e.ledger().timestamp();
+    if now >= deposit.unlocks_at || deposit.unlocks_at <= deposit.locked_at {
+        return 0;
+    }
+
+    let remaining: u128 = (deposit.unlocks_at - now) as u128;
+    let duration: u128 = (deposit.unlocks_at - deposit.locked_at) as u128;
+
+    (shares_value(pool, deposit.shares) * pool.early_withdraw_penalty * remaining)
+        / (duration * 1_0000000)
+}
+
+// Removes the deposit from the pool and sends the principal (minus the penalty) and the rewards to the depositor.
+// The penalty is sent to the treasury of the pool or, if there is no treasury, added to the factor of the deposit
+// asset (or to the principal if the pool is compounding) so it's shared with the remaining depositors.
+// The pool is not saved.
+pub fn withdraw_deposit(
+    e: &Env,
+    pool: &mut Pool,
+    depositor: &Address,
+    deposit: &Deposit,
+    penalty: u128,
+) {
+    e._deposits().remove(&pool.asset, depositor);
+    checkpoint_lock(e, depositor, &pool.asset, &None);
+
+    accrue_rewards(e, pool);
+    let rewards: Map<Address, u128> = settle_rewards(e, pool, deposit);
+
+    let value: u128 = shares_value(pool, deposit.shares);
+
+    pool.deposits -= 1;
+    pool.balance -= deposit.weight;
+    pool.principal -= value;
+    pool.shares -= deposit.shares;
+
+    if pool.deposits == 0 && pool.balance == 0 {
+        for asset in pool.factors.keys().iter() {
+            pool.factors.set(asset.clone(), 0);
+
+            // No one is left to receive the carried rewards
+            let remainder: u128 = pool.remainders.get(asset.clone()).unwrap_or(0);
+            add_dust(pool, &asset, remainder);
+            pool.remainders.set(asset, 0);
+        }
+    }
+
+    // The rounding of the share values stays in the principal until the last share is withdrawn
+    if pool.shares == 0 && pool.principal > 0 {
+        let asset: Address = pool.asset.clone();
+        add_dust(pool, &asset, pool.principal * FACTOR_PRECISION);
+        pool.principal = 0;
+    }
+
+    send_rewards(e, depositor, &rewards);
+
+    let mut amount: u128 = value - penalty;
+    if penalty > 0 {
+        match pool.penalty_treasury.clone() {
+            Some(treasury) => {
+                let result = token::Client::new(e, &pool.asset).try_transfer(
+                    &e.current_contract_address(),
+                    &treasury,
+                    &(penalty as i128),
+                );
+
+                if result.is_err() {
+                    panic_with_error!(&e, &ContractErrors::FundsWithdrawFailed);
+                }
+            }
+            None => {
+                // If there is no one left to receive the penalty the depositor doesn't pay it
+                if pool.balance == 0 {
+                    amount += penalty;
+                } else {
+                    let asset: Address = pool.asset.clone();
+                    add_reward(pool, &asset, penalty);
+                }
+            }
+        }
+    }
+
+    let result = token::Client::new(e, &pool.asset).try_transfer(
+        &e.current_contract_address(),
+        depositor,
+        &(amount as i128),
+    );
+
+    if result.is_err() {
+        panic_with_error!(&e, &ContractErrors::FundsWithdrawFailed);
+    }
+}
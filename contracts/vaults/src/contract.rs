@@ -1,24 +1,60 @@
-use crate::errors::SCErrors;
+use crate::errors::{SCErrors, SCErrorsExt};
 
+use crate::storage::auctions::{Auction, AuctionConfig, AuctionsFunc};
 use crate::storage::core::{CoreFunc, CoreState};
 use crate::storage::currencies::{CurrenciesDataKeys, CurrenciesFunc, Currency};
+use crate::storage::fees::{FeeSchedule, FeesDistribution, FeesFunc, MAX_FEE};
+use crate::storage::flash_mint::{FlashMintConfig, FlashMintFunc};
+use crate::storage::gas_compensation::{GasCompensation, GasCompensationFunc};
+use crate::storage::operators::{OperatorApproval, OperatorsFunc};
+use crate::storage::protections::{
+    ProtectionAction, ProtectionFunc, ProtectionOrder, MAX_PROTECTION_ORDERS,
+};
+use crate::storage::stability_pool::{
+    StabilityDeposit, StabilityGains, StabilityPool, StabilityPoolFunc,
+};
+use crate::storage::transfers::{TransferOffer, TransfersFunc};
 use crate::storage::vaults::{
-    OptionalVaultKey, Vault, VaultIndexKey, VaultKey, VaultsFunc, VaultsInfo,
+    LiquidationTarget, OptionalVaultKey, Redistribution, Vault, VaultIndexKey, VaultKey,
+    VaultsFunc, VaultsInfo,
+};
+use crate::utils::auctions::{
+    auction_price, emit_auction_event, is_auction_expired, validate_auction_config,
 };
 use crate::utils::currencies::get_currency_rate;
-use crate::utils::indexes::calculate_user_vault_index;
+use crate::utils::fees::{get_fee_schedule, validate_fee_schedule};
+use crate::utils::gas_compensation::{release_gas_reserve, take_gas_reserve};
+use crate::utils::liquidations::{
+    liquidate_vault, new_liquidation, settle_flash_liquidation, settle_liquidation, Liquidation,
+};
+use crate::utils::operators::{require_vault_auth, VaultPermission};
 use crate::utils::payments::{
-    burn_stablecoin, calc_fee, deposit_collateral, mint_stablecoin, pay_fee, withdraw_collateral,
+    burn_stablecoin, calc_fee, deposit_collateral, flush_pending_distribution, mint_stablecoin,
+    pay_fee, withdraw_collateral,
 };
+use crate::utils::protections::{
+    escrow_protection_order, release_protection_escrow, validate_protection_order,
+};
+use crate::utils::stability_pool::{
+    can_absorb_debt, collateral_gain, compounded_deposit, new_stability_deposit,
+    new_stability_pool, settle_stability_deposit,
+};
+use crate::utils::swaps::swap_with_adapter;
+use crate::utils::transfers::{transfer_vault, validate_transfer_offer};
 use crate::utils::vaults::{
-    calculate_deposit_ratio, can_be_liquidated, create_and_insert_vault, get_vaults, search_vault,
-    validate_prev_keys, withdraw_vault,
+    apply_redistribution, calculate_deposit_ratio, calculate_vault_index, can_be_liquidated,
+    create_and_insert_vault, get_redistribution, get_vaults, new_vault_id, redistribute_vault,
+    remove_vault_id, search_vault, validate_prev_keys, withdraw_vault,
+};
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, symbol_short, token, Address, Bytes, BytesN, Env,
+    Symbol, Vec,
 };
-use soroban_sdk::{contract, contractimpl, panic_with_error, Address, BytesN, Env, Symbol, Vec};
 
+use crate::flash_mint_receiver;
 use crate::oracle::PriceData;
 use crate::utils::validations::{
-    assert_col_rate_under_min, assert_regular_vault_updates_validations,
+    assert_col_rate_under_min, assert_regular_vault_updates_validations, assert_swap_col_rate,
 };
 
 // TODO: Explain each function here
@@ -40,6 +76,39 @@ pub trait VaultsContractTrait {
 
     fn set_fee(e: Env, new_fee: u128);
 
+    // Fees methods
+    fn set_fee_schedule(e: Env, denomination: Option<Symbol>, fee_schedule: FeeSchedule);
+    fn remove_fee_schedule(e: Env, denomination: Symbol);
+    fn get_fee_schedule(e: Env, denomination: Symbol) -> FeeSchedule;
+    fn set_fees_distribution(e: Env, fees_distribution: FeesDistribution);
+    fn remove_fees_distribution(e: Env);
+    fn get_fees_distribution(e: Env) -> Option<FeesDistribution>;
+    fn get_pending_fees_distribution(e: Env) -> u128;
+
+    // Gas compensation methods
+    fn set_gas_compensation(e: Env, gas_compensation: GasCompensation);
+    fn remove_gas_compensation(e: Env);
+    fn get_gas_compensation(e: Env) -> Option<GasCompensation>;
+    fn get_gas_reserve(
+        e: Env,
+        user: Address,
+        denomination: Symbol,
+        id: u32,
+    ) -> Option<GasCompensation>;
+
+    // Flash mint methods
+    fn set_flash_mint_config(e: Env, denomination: Symbol, flash_mint_config: FlashMintConfig);
+    fn remove_flash_mint_config(e: Env, denomination: Symbol);
+    fn get_flash_mint_config(e: Env, denomination: Symbol) -> Option<FlashMintConfig>;
+    fn flash_mint(
+        e: Env,
+        caller: Address,
+        receiver: Address,
+        denomination: Symbol,
+        amount: u128,
+        data: Bytes,
+    );
+
     fn upgrade(e: Env, hash: BytesN<32>);
     fn set_panic(e: Env, status: bool);
 
@@ -68,8 +137,9 @@ pub trait VaultsContractTrait {
         initial_debt: u128,
         collateral_amount: u128,
         denomination: Symbol,
-    );
-    fn get_vault(e: Env, caller: Address, denomination: Symbol) -> Vault;
+    ) -> u32;
+    fn get_vault(e: Env, caller: Address, denomination: Symbol, id: u32) -> Vault;
+    fn get_vault_ids(e: Env, account: Address, denomination: Symbol) -> Vec<u32>;
     fn get_vault_from_key(e: Env, vault_key: VaultKey) -> Vault;
     fn get_vaults(
         e: Env,
@@ -85,6 +155,14 @@ pub trait VaultsContractTrait {
         new_prev_key: OptionalVaultKey,
         amount: u128,
     );
+    fn increase_collateral_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        amount: u128,
+    );
     fn withdraw_collateral(
         e: Env,
         prev_key: OptionalVaultKey,
@@ -92,6 +170,14 @@ pub trait VaultsContractTrait {
         new_prev_key: OptionalVaultKey,
         amount: u128,
     );
+    fn withdraw_collateral_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        amount: u128,
+    );
     fn increase_debt(
         e: Env,
         prev_key: OptionalVaultKey,
@@ -99,6 +185,14 @@ pub trait VaultsContractTrait {
         new_prev_key: OptionalVaultKey,
         amount: u128,
     );
+    fn increase_debt_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        amount: u128,
+    );
     fn pay_debt(
         e: Env,
         prev_key: OptionalVaultKey,
@@ -106,7 +200,154 @@ pub trait VaultsContractTrait {
         new_prev_key: OptionalVaultKey,
         amount: u128,
     );
-    fn transfer_debt(e: Env, prev_key: OptionalVaultKey, vault_key: VaultKey, destination: Address);
+    fn pay_debt_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        amount: u128,
+    );
+    fn transfer_debt(
+        e: Env,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        destination: Address,
+    ) -> u32;
+
+    fn merge_vaults(
+        e: Env,
+        source_prev_key: OptionalVaultKey,
+        source_key: VaultKey,
+        target_prev_key: OptionalVaultKey,
+        target_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+    );
+    fn split_vault(
+        e: Env,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        destination: Address,
+        debt_amount: u128,
+        collateral_amount: u128,
+        destination_prev_key: OptionalVaultKey,
+    ) -> u32;
+
+    // Transfer offers
+    fn offer_vault_transfer(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+        offer: TransferOffer,
+    );
+    fn cancel_vault_transfer(e: Env, owner: Address, denomination: Symbol, id: u32);
+    fn get_vault_transfer_offer(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+    ) -> Option<TransferOffer>;
+    fn accept_vault_transfer(
+        e: Env,
+        recipient: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        max_debt: u128,
+        min_collateral: u128,
+    ) -> u32;
+
+    // Operators
+    fn approve_operator(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+        operator: Address,
+        approval: OperatorApproval,
+    );
+    fn revoke_operator(e: Env, owner: Address, denomination: Symbol, id: u32, operator: Address);
+    fn get_operator_approval(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+        operator: Address,
+    ) -> Option<OperatorApproval>;
+
+    // Protection orders
+    fn add_protection_order(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+        order: ProtectionOrder,
+    );
+    fn cancel_protection_order(e: Env, owner: Address, denomination: Symbol, id: u32, index: u32);
+    fn get_protection_orders(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+    ) -> Vec<ProtectionOrder>;
+    fn execute_protection(
+        e: Env,
+        keeper: Address,
+        vault_owner: Address,
+        denomination: Symbol,
+        id: u32,
+        prev_key: OptionalVaultKey,
+        new_prev_key: OptionalVaultKey,
+    );
+
+    // Leverage
+    fn set_swap_adapter(e: Env, swap_adapter: Address);
+    fn remove_swap_adapter(e: Env);
+    fn get_swap_adapter(e: Env) -> Option<Address>;
+    fn new_vault_leveraged(
+        e: Env,
+        prev_key: OptionalVaultKey,
+        caller: Address,
+        initial_debt: u128,
+        collateral_amount: u128,
+        denomination: Symbol,
+        min_collateral_out: u128,
+    ) -> u32;
+    fn leverage(
+        e: Env,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        debt_amount: u128,
+        min_collateral_out: u128,
+    );
+    fn leverage_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        debt_amount: u128,
+        min_collateral_out: u128,
+    );
+    fn deleverage(
+        e: Env,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        collateral_amount: u128,
+        min_debt_out: u128,
+    );
+    fn deleverage_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        collateral_amount: u128,
+        min_debt_out: u128,
+    );
 
     // Redeeming
     fn redeem(
@@ -124,6 +365,53 @@ pub trait VaultsContractTrait {
         denomination: Symbol,
         total_vaults_to_liquidate: u32,
     ) -> Vec<Vault>;
+    fn liquidate_vaults(
+        e: Env,
+        liquidator: Address,
+        denomination: Symbol,
+        targets: Vec<LiquidationTarget>,
+        max_debt: u128,
+    ) -> Vec<Vault>;
+    fn liquidate_up_to(
+        e: Env,
+        liquidator: Address,
+        denomination: Symbol,
+        total_vaults_to_liquidate: u32,
+        max_debt: u128,
+    ) -> Vec<Vault>;
+    fn liquidate_with_flash_mint(
+        e: Env,
+        liquidator: Address,
+        receiver: Address,
+        denomination: Symbol,
+        total_vaults_to_liquidate: u32,
+        data: Bytes,
+    ) -> Vec<Vault>;
+    fn redistribute(
+        e: Env,
+        caller: Address,
+        denomination: Symbol,
+        total_vaults_to_redistribute: u32,
+    ) -> Vec<Vault>;
+    fn get_redistribution(e: Env, denomination: Symbol) -> Redistribution;
+
+    // Auctions
+    fn set_auction_config(e: Env, denomination: Symbol, auction_config: AuctionConfig);
+    fn remove_auction_config(e: Env, denomination: Symbol);
+    fn get_auction_config(e: Env, denomination: Symbol) -> Option<AuctionConfig>;
+    fn bid(e: Env, bidder: Address, auction_id: u64, collateral_amount: u128, max_price: u128);
+    fn restart_auction(e: Env, caller: Address, auction_id: u64);
+    fn redistribute_auction(e: Env, caller: Address, auction_id: u64);
+    fn get_auction(e: Env, auction_id: u64) -> Auction;
+    fn get_auction_price(e: Env, auction_id: u64) -> u128;
+
+    // Stability pool
+    fn stability_deposit(e: Env, caller: Address, denomination: Symbol, amount: u128);
+    fn stability_withdraw(e: Env, caller: Address, denomination: Symbol, amount: u128);
+    fn stability_claim(e: Env, caller: Address, denomination: Symbol);
+    fn get_stability_pool(e: Env, denomination: Symbol) -> StabilityPool;
+    fn get_stability_deposit(e: Env, caller: Address, denomination: Symbol) -> StabilityDeposit;
+    fn get_stability_gains(e: Env, caller: Address, denomination: Symbol) -> StabilityGains;
 }
 
 #[contract]
@@ -148,7 +436,7 @@ impl VaultsContractTrait for VaultsContract {
         }
 
         // The protocol should not have a fee higher than 1%
-        if fee > 100000 {
+        if fee > MAX_FEE {
             panic_with_error!(&e, &SCErrors::InvalidFee);
         }
 
@@ -193,10 +481,200 @@ impl VaultsContractTrait for VaultsContract {
         e.bump_instance();
         let mut core_state: CoreState = e.core_state().unwrap();
         core_state.admin.require_auth();
+
+        if new_fee > MAX_FEE {
+            panic_with_error!(&e, &SCErrors::InvalidFee);
+        }
+
         core_state.fee = new_fee;
         e.set_core_state(&core_state);
     }
 
+    // If the denomination is None, the schedule is set as the default for all the denominations without their own schedule
+    fn set_fee_schedule(e: Env, denomination: Option<Symbol>, fee_schedule: FeeSchedule) {
+        e.bump_instance();
+        e.core_state().unwrap().admin.require_auth();
+
+        validate_fee_schedule(&e, &fee_schedule);
+
+        match denomination {
+            None => e.set_default_fee_schedule(&fee_schedule),
+            Some(denomination) => {
+                if e.currency(&denomination).is_none() {
+                    panic_with_error!(&e, &SCErrors::CurrencyDoesntExist);
+                }
+
+                e.set_fee_schedule(&denomination, &fee_schedule);
+            }
+        }
+    }
+
+    fn remove_fee_schedule(e: Env, denomination: Symbol) {
+        e.bump_instance();
+        e.core_state().unwrap().admin.require_auth();
+        e.remove_fee_schedule(&denomination);
+    }
+
+    fn get_fee_schedule(e: Env, denomination: Symbol) -> FeeSchedule {
+        e.bump_instance();
+        get_fee_schedule(&e, &e.core_state().unwrap(), &denomination)
+    }
+
+    fn set_fees_distribution(e: Env, fees_distribution: FeesDistribution) {
+        e.bump_instance();
+        let core_state: CoreState = e.core_state().unwrap();
+        core_state.admin.require_auth();
+
+        if fees_distribution.share > 1_0000000 {
+            panic_with_error!(&e, &SCErrors::InvalidFeeShare);
+        }
+
+        flush_pending_distribution(&e, &core_state);
+        e.set_fees_distribution(&fees_distribution);
+    }
+
+    fn remove_fees_distribution(e: Env) {
+        e.bump_instance();
+        let core_state: CoreState = e.core_state().unwrap();
+        core_state.admin.require_auth();
+        flush_pending_distribution(&e, &core_state);
+        e.remove_fees_distribution();
+    }
+
+    fn get_fees_distribution(e: Env) -> Option<FeesDistribution> {
+        e.bump_instance();
+        e.fees_distribution()
+    }
+
+    fn get_pending_fees_distribution(e: Env) -> u128 {
+        e.bump_instance();
+        e.pending_distribution()
+    }
+
+    // The compensation is taken from the owner on `new_vault`, returned when the vault is closed
+    // and paid to whoever liquidates (or redistributes) the vault
+    fn set_gas_compensation(e: Env, gas_compensation: GasCompensation) {
+        e.bump_instance();
+        e.core_state().unwrap().admin.require_auth();
+
+        if gas_compensation.amount == 0 {
+            panic_with_error!(&e, &SCErrors::InvalidGasCompensation);
+        }
+
+        e.set_gas_compensation(&gas_compensation);
+    }
+
+    fn remove_gas_compensation(e: Env) {
+        e.bump_instance();
+        e.core_state().unwrap().admin.require_auth();
+        e.remove_gas_compensation();
+    }
+
+    fn get_gas_compensation(e: Env) -> Option<GasCompensation> {
+        e.bump_instance();
+        e.gas_compensation()
+    }
+
+    fn get_gas_reserve(
+        e: Env,
+        user: Address,
+        denomination: Symbol,
+        id: u32,
+    ) -> Option<GasCompensation> {
+        e.bump_instance();
+        let (_, vault_key, _) = search_vault(&e, &user, &denomination, id);
+        e.gas_reserve(&vault_key)
+    }
+
+    fn set_flash_mint_config(e: Env, denomination: Symbol, flash_mint_config: FlashMintConfig) {
+        e.bump_instance();
+        e.core_state().unwrap().admin.require_auth();
+
+        if e.currency(&denomination).is_none() {
+            panic_with_error!(&e, &SCErrors::CurrencyDoesntExist);
+        }
+
+        if flash_mint_config.fee > MAX_FEE {
+            panic_with_error!(&e, &SCErrors::InvalidFee);
+        }
+
+        e.set_flash_mint_config(&denomination, &flash_mint_config);
+    }
+
+    fn remove_flash_mint_config(e: Env, denomination: Symbol) {
+        e.bump_instance();
+        e.core_state().unwrap().admin.require_auth();
+        e.remove_flash_mint_config(&denomination);
+    }
+
+    fn get_flash_mint_config(e: Env, denomination: Symbol) -> Option<FlashMintConfig> {
+        e.bump_instance();
+        e.flash_mint_config(&denomination)
+    }
+
+    // Mints `amount` to the receiver and calls its `on_flash_mint` callback, before the callback returns the receiver
+    // must send back to this contract the amount plus the fee, both are burned after the callback.
+    // Soroban doesn't allow reentrancy, so the receiver can't call this contract during the callback, liquidators
+    // should use `liquidate_with_flash_mint` instead.
+    fn flash_mint(
+        e: Env,
+        caller: Address,
+        receiver: Address,
+        denomination: Symbol,
+        amount: u128,
+        data: Bytes,
+    ) {
+        e.bump_instance();
+        caller.require_auth();
+
+        let core_state: CoreState = e.core_state().unwrap();
+        if core_state.panic_mode {
+            panic_with_error!(&e, &SCErrors::PanicModeEnabled);
+        }
+
+        let currency: Currency = e
+            .currency(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+
+        if !currency.active {
+            panic_with_error!(&e, &SCErrors::CurrencyIsInactive);
+        }
+
+        let flash_mint_config: FlashMintConfig = e
+            .flash_mint_config(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::FlashMintIsDisabled));
+
+        if amount == 0 || amount > flash_mint_config.max_amount {
+            panic_with_error!(&e, &SCErrors::InvalidFlashMintAmount);
+        }
+
+        let fee: u128 = calc_fee(&flash_mint_config.fee, &amount);
+        let stablecoin: token::Client = token::Client::new(&e, &currency.contract);
+        let initial_balance: i128 = stablecoin.balance(&e.current_contract_address());
+
+        mint_stablecoin(&e, &currency, &receiver, amount as i128);
+
+        flash_mint_receiver::Client::new(&e, &receiver).on_flash_mint(
+            &caller,
+            &denomination,
+            &amount,
+            &fee,
+            &data,
+        );
+
+        let repaid: i128 = stablecoin.balance(&e.current_contract_address()) - initial_balance;
+        if repaid < (amount + fee) as i128 {
+            panic_with_error!(&e, &SCErrors::FlashMintNotRepaid);
+        }
+
+        burn_stablecoin(
+            &e,
+            &currency,
+            &e.current_contract_address(),
+            (amount + fee) as i128,
+        );
+    }
+
     fn upgrade(e: Env, hash: BytesN<32>) {
         e.bump_instance();
         e.core_state().unwrap().admin.require_auth();
@@ -246,6 +724,7 @@ impl VaultsContractTrait for VaultsContract {
         e.bump_vault_index(&VaultIndexKey {
             user: target_key.account.clone(),
             denomination: target_key.denomination.clone(),
+            id: target_key.id,
         });
     }
 
@@ -342,7 +821,7 @@ impl VaultsContractTrait for VaultsContract {
         initial_debt: u128,
         collateral_amount: u128,
         denomination: Symbol,
-    ) {
+    ) -> u32 {
         e.bump_instance();
         caller.require_auth();
         let currency: Currency = e
@@ -353,15 +832,6 @@ impl VaultsContractTrait for VaultsContract {
             panic_with_error!(&e, &SCErrors::CurrencyIsInactive);
         }
 
-        if e.vault_index(&VaultIndexKey {
-            user: caller.clone(),
-            denomination: denomination.clone(),
-        })
-        .is_some()
-        {
-            panic_with_error!(&e, &SCErrors::UserAlreadyHasDenominationVault);
-        }
-
         let core_state: CoreState = e.core_state().unwrap();
 
         let rate: PriceData = get_currency_rate(&e, &core_state, &denomination);
@@ -371,8 +841,16 @@ impl VaultsContractTrait for VaultsContract {
             panic_with_error!(&e, &SCErrors::PanicModeEnabled);
         }
 
-        let fee: u128 = calc_fee(&core_state.fee, &collateral_amount);
-        let vault_col: u128 = collateral_amount - fee;
+        let fee_schedule: FeeSchedule = get_fee_schedule(&e, &core_state, &denomination);
+
+        // The opening fee is either taken from the collateral or added to the debt and minted to the treasury
+        let (collateral_fee, debt_fee): (u128, u128) = if fee_schedule.stablecoin_fees {
+            (0, calc_fee(&fee_schedule.open, &initial_debt))
+        } else {
+            (calc_fee(&fee_schedule.open, &collateral_amount), 0)
+        };
+        let vault_col: u128 = collateral_amount - collateral_fee;
+        let vault_debt: u128 = initial_debt + debt_fee;
 
         let mut vaults_info: VaultsInfo = e
             .vaults_info(&denomination)
@@ -383,17 +861,18 @@ impl VaultsContractTrait for VaultsContract {
         }
 
         let deposit_collateral_rate: u128 =
-            calculate_deposit_ratio(&(rate.price as u128), &vault_col, &initial_debt);
+            calculate_deposit_ratio(&(rate.price as u128), &vault_col, &vault_debt);
 
         if deposit_collateral_rate < vaults_info.opening_col_rate {
             panic_with_error!(&e, &SCErrors::InvalidOpeningCollateralRatio);
         }
 
-        let new_vault_index: u128 = calculate_user_vault_index(initial_debt, vault_col);
+        let new_vault_index: u128 = calculate_vault_index(&e, &denomination, vault_debt, vault_col);
         let new_vault_key: VaultKey = VaultKey {
             index: new_vault_index.clone(),
             account: caller.clone(),
             denomination: denomination.clone(),
+            id: new_vault_id(&e, &caller, &denomination),
         };
 
         // In case prev value is not None, we confirm it exists and its index is not higher than the new Vault index
@@ -420,28 +899,39 @@ impl VaultsContractTrait for VaultsContract {
             &vaults_info.lowest_key,
             &new_vault_key,
             &prev_key,
-            initial_debt.clone(),
+            vault_debt.clone(),
             vault_col.clone(),
         );
 
+        take_gas_reserve(&e, &caller, &new_vault_key);
+
         vaults_info.lowest_key = updated_lowest_key;
         vaults_info.total_vaults = vaults_info.total_vaults + 1;
-        vaults_info.total_debt = vaults_info.total_debt + initial_debt;
+        vaults_info.total_debt = vaults_info.total_debt + vault_debt;
         vaults_info.total_col = vaults_info.total_col + vault_col;
         e.set_vaults_info(&vaults_info);
 
         deposit_collateral(&e, &core_state, &caller, vault_col as i128);
         mint_stablecoin(&e, &currency, &caller, initial_debt as i128);
-        pay_fee(&e, &core_state, &caller, fee as i128);
 
-        e.bump_vault(&new_vault_key);
-        e.bump_vault_index(&new_vault_index_key);
+        if debt_fee > 0 {
+            mint_stablecoin(&e, &currency, &core_state.treasury, debt_fee as i128);
+        }
+
+        if collateral_fee > 0 {
+            pay_fee(&e, &core_state, &caller, collateral_fee as i128);
+        }
+
+        e.bump_vault(&new_vault_key);
+        e.bump_vault_index(&new_vault_index_key);
+
+        new_vault_key.id
     }
 
-    fn get_vault(e: Env, user: Address, denomination: Symbol) -> Vault {
+    fn get_vault(e: Env, user: Address, denomination: Symbol, id: u32) -> Vault {
         e.bump_instance();
 
-        let (user_vault, vault_key, vault_index_key) = search_vault(&e, &user, &denomination);
+        let (user_vault, vault_key, vault_index_key) = search_vault(&e, &user, &denomination, id);
 
         e.bump_vault(&vault_key);
         e.bump_vault_index(&vault_index_key);
@@ -449,6 +939,11 @@ impl VaultsContractTrait for VaultsContract {
         user_vault
     }
 
+    fn get_vault_ids(e: Env, account: Address, denomination: Symbol) -> Vec<u32> {
+        e.bump_instance();
+        e.account_vaults(&account, &denomination).ids
+    }
+
     fn get_vault_from_key(e: Env, vault_key: VaultKey) -> Vault {
         e.bump_instance();
 
@@ -459,12 +954,15 @@ impl VaultsContractTrait for VaultsContract {
         let vault_index_key: VaultIndexKey = VaultIndexKey {
             user: vault_key.account.clone(),
             denomination: vault_key.denomination.clone(),
+            id: vault_key.id,
         };
 
         e.bump_vault(&vault_key.clone());
         e.bump_vault_index(&vault_index_key);
 
-        e.vault(&vault_key).unwrap()
+        let mut vault: Vault = e.vault(&vault_key).unwrap();
+        apply_redistribution(&e, &mut vault);
+        vault
     }
 
     fn get_vaults(
@@ -508,9 +1006,22 @@ impl VaultsContractTrait for VaultsContract {
         vault_key: VaultKey,
         new_prev_key: OptionalVaultKey,
         amount: u128,
+    ) {
+        let owner: Address = vault_key.account.clone();
+        Self::increase_collateral_as_operator(e, owner, prev_key, vault_key, new_prev_key, amount);
+    }
+
+    // Same as `increase_collateral` but it can also be called by an operator approved by the owner of the vault
+    fn increase_collateral_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        amount: u128,
     ) {
         e.bump_instance();
-        vault_key.account.require_auth();
+        require_vault_auth(&e, &caller, &vault_key, VaultPermission::Deposit);
 
         let currency: Currency = e
             .currency(&vault_key.denomination)
@@ -521,19 +1032,25 @@ impl VaultsContractTrait for VaultsContract {
         }
 
         let core_state: CoreState = e.core_state().unwrap();
+        let fee_schedule: FeeSchedule = get_fee_schedule(&e, &core_state, &vault_key.denomination);
 
-        if amount < (core_state.fee * 10) {
+        if amount < (fee_schedule.deposit * 10) {
             panic_with_error!(&e, &SCErrors::InvalidMinCollateralAmount);
         }
 
-        let fee: u128 = calc_fee(&core_state.fee, &amount);
+        let fee: u128 = calc_fee(&fee_schedule.deposit, &amount);
         let collateral: u128 = amount - fee;
 
-        deposit_collateral(&e, &core_state, &vault_key.account, collateral as i128);
-        pay_fee(&e, &core_state, &vault_key.account, fee as i128);
+        // If an operator is adding the collateral, the operator is the one paying for it
+        deposit_collateral(&e, &core_state, &caller, collateral as i128);
+        pay_fee(&e, &core_state, &caller, fee as i128);
 
-        let (target_vault, target_vault_key, _) =
-            search_vault(&e, &vault_key.account, &vault_key.denomination);
+        let (target_vault, target_vault_key, _) = search_vault(
+            &e,
+            &vault_key.account,
+            &vault_key.denomination,
+            vault_key.id,
+        );
 
         let mut vaults_info: VaultsInfo = e.vaults_info(&target_vault_key.denomination).unwrap();
 
@@ -563,12 +1080,15 @@ impl VaultsContractTrait for VaultsContract {
         let new_vault_initial_debt: u128 = target_vault.total_debt.clone();
         let new_vault_collateral_amount: u128 = target_vault.total_collateral.clone() + collateral;
         let new_vault_key: VaultKey = VaultKey {
-            index: calculate_user_vault_index(
+            index: calculate_vault_index(
+                &e,
+                &target_vault.denomination,
                 new_vault_initial_debt.clone(),
                 new_vault_collateral_amount.clone(),
             ),
             account: target_vault.account,
             denomination: target_vault.denomination,
+            id: target_vault.id,
         };
 
         let (_, updated_target_vault_key, updated_target_vault_index_key, updated_lowest_key) =
@@ -595,9 +1115,22 @@ impl VaultsContractTrait for VaultsContract {
         vault_key: VaultKey,
         new_prev_key: OptionalVaultKey,
         amount: u128,
+    ) {
+        let owner: Address = vault_key.account.clone();
+        Self::withdraw_collateral_as_operator(e, owner, prev_key, vault_key, new_prev_key, amount);
+    }
+
+    // Same as `withdraw_collateral` but it can also be called by an operator approved by the owner of the vault
+    fn withdraw_collateral_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        amount: u128,
     ) {
         e.bump_instance();
-        vault_key.account.require_auth();
+        require_vault_auth(&e, &caller, &vault_key, VaultPermission::Withdraw(amount));
 
         let currency: Currency = e
             .currency(&vault_key.denomination)
@@ -607,8 +1140,12 @@ impl VaultsContractTrait for VaultsContract {
             panic_with_error!(&e, &SCErrors::CurrencyIsInactive);
         }
 
-        let (target_vault, target_vault_key, _) =
-            search_vault(&e, &vault_key.account, &vault_key.denomination);
+        let (target_vault, target_vault_key, _) = search_vault(
+            &e,
+            &vault_key.account,
+            &vault_key.denomination,
+            vault_key.id,
+        );
 
         let core_state: CoreState = e.core_state().unwrap();
 
@@ -654,16 +1191,26 @@ impl VaultsContractTrait for VaultsContract {
             &vaults_info.opening_col_rate,
         );
 
-        // We send the remaining collateral to the owner of the Vault
-        withdraw_collateral(&e, &core_state, &vault_key.account, amount as i128);
+        // We send the requested collateral minus the withdraw fee to the owner of the Vault
+        let fee: u128 = calc_fee(
+            &get_fee_schedule(&e, &core_state, &target_vault.denomination).withdraw,
+            &amount,
+        );
+        withdraw_collateral(&e, &core_state, &vault_key.account, (amount - fee) as i128);
+        if fee > 0 {
+            pay_fee(&e, &core_state, &e.current_contract_address(), fee as i128);
+        }
 
         let new_vault_key: VaultKey = VaultKey {
-            index: calculate_user_vault_index(
+            index: calculate_vault_index(
+                &e,
+                &target_vault.denomination,
                 target_vault.total_debt.clone(),
                 new_collateral_amount.clone(),
             ),
             account: target_vault.account,
             denomination: target_vault.denomination,
+            id: target_vault.id,
         };
 
         let (_, updated_target_vault_key, updated_target_vault_index_key, updated_lowest_key) =
@@ -690,9 +1237,22 @@ impl VaultsContractTrait for VaultsContract {
         vault_key: VaultKey,
         new_prev_key: OptionalVaultKey,
         amount: u128,
+    ) {
+        let owner: Address = vault_key.account.clone();
+        Self::increase_debt_as_operator(e, owner, prev_key, vault_key, new_prev_key, amount);
+    }
+
+    // Same as `increase_debt` but it can also be called by an operator approved by the owner of the vault
+    fn increase_debt_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        amount: u128,
     ) {
         e.bump_instance();
-        vault_key.account.require_auth();
+        require_vault_auth(&e, &caller, &vault_key, VaultPermission::Mint(amount));
 
         let currency: Currency = e
             .currency(&vault_key.denomination)
@@ -702,8 +1262,12 @@ impl VaultsContractTrait for VaultsContract {
             panic_with_error!(&e, &SCErrors::CurrencyIsInactive);
         }
 
-        let (target_vault, target_vault_key, _) =
-            search_vault(&e, &vault_key.account, &vault_key.denomination);
+        let (target_vault, target_vault_key, _) = search_vault(
+            &e,
+            &vault_key.account,
+            &vault_key.denomination,
+            vault_key.id,
+        );
 
         let core_state: CoreState = e.core_state().unwrap();
 
@@ -739,7 +1303,16 @@ impl VaultsContractTrait for VaultsContract {
             vaults_info.lowest_key = target_vault.next_key.clone();
         }
 
-        let new_debt_amount: u128 = target_vault.total_debt + amount;
+        // When fees are charged in stablecoin, the borrowing fee is added to the debt and minted to the treasury
+        let fee_schedule: FeeSchedule =
+            get_fee_schedule(&e, &core_state, &target_vault.denomination);
+        let debt_fee: u128 = if fee_schedule.stablecoin_fees {
+            calc_fee(&fee_schedule.open, &amount)
+        } else {
+            0
+        };
+
+        let new_debt_amount: u128 = target_vault.total_debt + amount + debt_fee;
 
         assert_col_rate_under_min(
             &e,
@@ -751,13 +1324,20 @@ impl VaultsContractTrait for VaultsContract {
 
         mint_stablecoin(&e, &currency, &target_vault.account, amount as i128);
 
+        if debt_fee > 0 {
+            mint_stablecoin(&e, &currency, &core_state.treasury, debt_fee as i128);
+        }
+
         let new_vault_key: VaultKey = VaultKey {
-            index: calculate_user_vault_index(
+            index: calculate_vault_index(
+                &e,
+                &target_vault.denomination,
                 new_debt_amount.clone(),
                 target_vault.total_collateral.clone(),
             ),
             account: target_vault.account,
             denomination: target_vault.denomination,
+            id: target_vault.id,
         };
 
         let (_, updated_target_vault_key, updated_target_vault_index_key, updated_lowest_key) =
@@ -771,7 +1351,7 @@ impl VaultsContractTrait for VaultsContract {
             );
 
         vaults_info.lowest_key = updated_lowest_key;
-        vaults_info.total_debt = vaults_info.total_debt + amount;
+        vaults_info.total_debt = vaults_info.total_debt + amount + debt_fee;
         e.set_vaults_info(&vaults_info);
 
         e.bump_vault(&updated_target_vault_key);
@@ -784,16 +1364,33 @@ impl VaultsContractTrait for VaultsContract {
         vault_key: VaultKey,
         new_prev_key: OptionalVaultKey,
         amount: u128,
+    ) {
+        let owner: Address = vault_key.account.clone();
+        Self::pay_debt_as_operator(e, owner, prev_key, vault_key, new_prev_key, amount);
+    }
+
+    // Same as `pay_debt` but it can also be called by an operator approved by the owner of the vault
+    fn pay_debt_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        amount: u128,
     ) {
         e.bump_instance();
-        vault_key.account.require_auth();
+        require_vault_auth(&e, &caller, &vault_key, VaultPermission::Repay);
 
         let currency: Currency = e
             .currency(&vault_key.denomination)
             .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
 
-        let (target_vault, target_vault_key, _) =
-            search_vault(&e, &vault_key.account, &vault_key.denomination);
+        let (target_vault, target_vault_key, _) = search_vault(
+            &e,
+            &vault_key.account,
+            &vault_key.denomination,
+            vault_key.id,
+        );
 
         let mut vaults_info: VaultsInfo = e.vaults_info(&target_vault_key.denomination).unwrap();
 
@@ -819,7 +1416,7 @@ impl VaultsContractTrait for VaultsContract {
 
         let core_state: CoreState = e.core_state().unwrap();
 
-        burn_stablecoin(&e, &currency, &target_vault.account, amount as i128);
+        burn_stablecoin(&e, &currency, &caller, amount as i128);
 
         if target_vault.total_debt == amount {
             // If the amount is equal to the debt it means it is paid in full, so we release the collateral and remove the vault
@@ -832,7 +1429,10 @@ impl VaultsContractTrait for VaultsContract {
             vaults_info.total_vaults = vaults_info.total_vaults - 1;
             vaults_info.total_col = vaults_info.total_col - target_vault.total_collateral;
 
-            let fee: u128 = calc_fee(&core_state.fee, &target_vault.total_collateral);
+            let fee: u128 = calc_fee(
+                &get_fee_schedule(&e, &core_state, &target_vault.denomination).close,
+                &target_vault.total_collateral,
+            );
 
             withdraw_collateral(
                 &e,
@@ -844,6 +1444,13 @@ impl VaultsContractTrait for VaultsContract {
             pay_fee(&e, &core_state, &e.current_contract_address(), fee as i128);
 
             withdraw_vault(&e, &target_vault, &prev_key);
+            remove_vault_id(
+                &e,
+                &target_vault.account,
+                &target_vault.denomination,
+                target_vault.id,
+            );
+            release_gas_reserve(&e, &target_vault_key, &target_vault.account);
 
             // If the target vault is the lowest, we update the lowest value
             if lowest_key == target_vault_key {
@@ -856,8 +1463,12 @@ impl VaultsContractTrait for VaultsContract {
                 panic_with_error!(&e, &SCErrors::InvalidMinDebtAmount);
             }
             let new_vault_collateral: u128 = target_vault.total_collateral.clone();
-            let new_vault_index: u128 =
-                calculate_user_vault_index(new_vault_debt.clone(), new_vault_collateral.clone());
+            let new_vault_index: u128 = calculate_vault_index(
+                &e,
+                &target_vault.denomination,
+                new_vault_debt.clone(),
+                new_vault_collateral.clone(),
+            );
 
             withdraw_vault(&e, &target_vault, &prev_key);
 
@@ -874,6 +1485,7 @@ impl VaultsContractTrait for VaultsContract {
                         index: new_vault_index.clone(),
                         account: target_vault.account.clone(),
                         denomination: target_vault.denomination.clone(),
+                        id: target_vault.id,
                     },
                     &new_prev_key,
                     new_vault_debt.clone(),
@@ -895,23 +1507,74 @@ impl VaultsContractTrait for VaultsContract {
         prev_key: OptionalVaultKey,
         vault_key: VaultKey,
         destination: Address,
-    ) {
+    ) -> u32 {
         e.bump_instance();
         vault_key.account.require_auth();
 
-        if e.vault_index(&VaultIndexKey {
-            user: destination.clone(),
-            denomination: vault_key.denomination.clone(),
-        })
-        .is_some()
+        // The new owner takes the debt so it needs to agree with the transfer
+        if destination != vault_key.account {
+            destination.require_auth();
+        }
+
+        transfer_vault(&e, &prev_key, &vault_key, &destination).id
+    }
+
+    // Moves the debt and collateral of the source vault into the target vault, both vaults must have the same owner.
+    // The gas reserve of the source vault is returned to the owner.
+    //
+    // **Arguments:**
+    // - `source_prev_key` - The key of the vault that comes BEFORE the source vault
+    // - `target_prev_key` - The key of the vault that comes BEFORE the target vault, it can be the source vault
+    // - `new_prev_key` - The key of the vault that will come BEFORE the merged vault once the source vault is removed
+    fn merge_vaults(
+        e: Env,
+        source_prev_key: OptionalVaultKey,
+        source_key: VaultKey,
+        target_prev_key: OptionalVaultKey,
+        target_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+    ) {
+        e.bump_instance();
+        source_key.account.require_auth();
+
+        if source_key.account != target_key.account
+            || source_key.denomination != target_key.denomination
+            || source_key.id == target_key.id
         {
-            panic_with_error!(&e, &SCErrors::UserAlreadyHasDenominationVault);
+            panic_with_error!(&e, &SCErrorsExt::InvalidVaultMerge);
         }
 
-        let (mut target_vault, mut target_vault_key, _) =
-            search_vault(&e, &vault_key.account, &vault_key.denomination);
+        let currency: Currency = e
+            .currency(&source_key.denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
 
-        let mut vaults_info: VaultsInfo = e.vaults_info(&target_vault_key.denomination).unwrap();
+        if !currency.active {
+            panic_with_error!(&e, &SCErrors::CurrencyIsInactive);
+        }
+
+        let core_state: CoreState = e.core_state().unwrap();
+
+        let rate: PriceData = get_currency_rate(&e, &core_state, &source_key.denomination);
+
+        // If price of the collateral hasn't been updated in more than 20 minutes or the protocol is in panic mode we throw
+        if core_state.panic_mode || rate.timestamp < e.ledger().timestamp().saturating_sub(1200) {
+            panic_with_error!(&e, &SCErrors::PanicModeEnabled);
+        }
+
+        let (source_vault, source_vault_key, _) = search_vault(
+            &e,
+            &source_key.account,
+            &source_key.denomination,
+            source_key.id,
+        );
+        let (mut target_vault, target_vault_key, _) = search_vault(
+            &e,
+            &target_key.account,
+            &target_key.denomination,
+            target_key.id,
+        );
+
+        let mut vaults_info: VaultsInfo = e.vaults_info(&source_key.denomination).unwrap();
 
         let lowest_key = match vaults_info.lowest_key.clone() {
             // It should be impossible to reach this case, but just in case we panic if it happens.
@@ -919,57 +1582,134 @@ impl VaultsContractTrait for VaultsContract {
             OptionalVaultKey::Some(key) => key,
         };
 
+        assert_regular_vault_updates_validations(
+            &e,
+            &source_vault,
+            &source_vault_key,
+            &source_prev_key,
+            &source_key,
+            &new_prev_key,
+            &lowest_key,
+        );
+
         assert_regular_vault_updates_validations(
             &e,
             &target_vault,
             &target_vault_key,
-            &prev_key,
-            &vault_key,
-            &prev_key,
+            &target_prev_key,
+            &target_key,
+            &new_prev_key,
             &lowest_key,
         );
 
-        // We remove the vault so we can update it to the new owner
-        withdraw_vault(&e, &target_vault, &prev_key);
+        withdraw_vault(&e, &source_vault, &source_prev_key);
+        remove_vault_id(
+            &e,
+            &source_vault.account,
+            &source_vault.denomination,
+            source_vault.id,
+        );
+        release_gas_reserve(&e, &source_vault_key, &source_vault.account);
 
-        // If the target vault is the lowest, we update the lowest value
-        if lowest_key == target_vault_key {
+        if lowest_key == source_vault_key {
+            vaults_info.lowest_key = source_vault.next_key.clone();
+        }
+
+        // Removing the source vault could have changed the next key of the target vault and, if the source vault
+        // was the one before the target vault, the target vault now comes after the prev of the source vault
+        target_vault.next_key = e.vault(&target_vault_key).unwrap().next_key;
+        let updated_target_prev_key: OptionalVaultKey =
+            if target_prev_key == OptionalVaultKey::Some(source_vault_key.clone()) {
+                source_prev_key
+            } else {
+                target_prev_key
+            };
+
+        withdraw_vault(&e, &target_vault, &updated_target_prev_key);
+
+        if vaults_info.lowest_key == OptionalVaultKey::Some(target_vault_key.clone()) {
             vaults_info.lowest_key = target_vault.next_key.clone();
         }
 
-        target_vault.account = destination.clone();
-        target_vault_key.account = destination;
+        let new_debt_amount: u128 = target_vault.total_debt + source_vault.total_debt;
+        let new_collateral_amount: u128 =
+            target_vault.total_collateral + source_vault.total_collateral;
+
+        let new_vault_key: VaultKey = VaultKey {
+            index: calculate_vault_index(
+                &e,
+                &target_vault.denomination,
+                new_debt_amount.clone(),
+                new_collateral_amount.clone(),
+            ),
+            account: target_vault.account,
+            denomination: target_vault.denomination,
+            id: target_vault.id,
+        };
 
         let (_, updated_target_vault_key, updated_target_vault_index_key, updated_lowest_key) =
             create_and_insert_vault(
                 &e,
                 &vaults_info.lowest_key,
-                &target_vault_key,
-                &prev_key,
-                target_vault.total_debt.clone(),
-                target_vault.total_collateral.clone(),
+                &new_vault_key,
+                &new_prev_key,
+                new_debt_amount.clone(),
+                new_collateral_amount.clone(),
             );
 
         vaults_info.lowest_key = updated_lowest_key;
-
+        vaults_info.total_vaults = vaults_info.total_vaults - 1;
         e.set_vaults_info(&vaults_info);
 
         e.bump_vault(&updated_target_vault_key);
         e.bump_vault_index(&updated_target_vault_index_key);
     }
 
-    fn redeem(
+    // Moves part of the debt and collateral of the vault to a new vault owned by `destination`, both vaults must
+    // be over the opening collateral ratio and the min debt. The gas reserve of the new vault is paid by the owner.
+    //
+    // **Arguments:**
+    // - `new_prev_key` - The key of the vault that will come BEFORE the updated vault
+    // - `destination_prev_key` - The key of the vault that will come BEFORE the new vault once the updated vault is
+    // inserted, if it's the updated vault it must use its new index
+    //
+    // **Returns:**
+    // - The id of the new vault
+    fn split_vault(
         e: Env,
-        caller: Address,
-        denomination: Symbol,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
         new_prev_key: OptionalVaultKey,
-        amount: u128,
-    ) {
+        destination: Address,
+        debt_amount: u128,
+        collateral_amount: u128,
+        destination_prev_key: OptionalVaultKey,
+    ) -> u32 {
         e.bump_instance();
-        caller.require_auth();
+        vault_key.account.require_auth();
+
+        // The new vault has debt so its owner needs to agree with the split
+        if destination != vault_key.account {
+            destination.require_auth();
+        }
+
+        let (target_vault, target_vault_key, _) = search_vault(
+            &e,
+            &vault_key.account,
+            &vault_key.denomination,
+            vault_key.id,
+        );
+
+        if debt_amount == 0
+            || debt_amount >= target_vault.total_debt
+            || collateral_amount == 0
+            || collateral_amount >= target_vault.total_collateral
+        {
+            panic_with_error!(&e, &SCErrorsExt::InvalidVaultSplit);
+        }
 
         let currency: Currency = e
-            .currency(&denomination)
+            .currency(&vault_key.denomination)
             .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
 
         if !currency.active {
@@ -977,8 +1717,15 @@ impl VaultsContractTrait for VaultsContract {
         }
 
         let core_state: CoreState = e.core_state().unwrap();
-        let rate: PriceData = get_currency_rate(&e, &core_state, &denomination);
-        let mut vaults_info: VaultsInfo = e.vaults_info(&denomination).unwrap();
+
+        let rate: PriceData = get_currency_rate(&e, &core_state, &target_vault.denomination);
+
+        // If price of the collateral hasn't been updated in more than 20 minutes or the protocol is in panic mode we throw
+        if core_state.panic_mode || rate.timestamp < e.ledger().timestamp().saturating_sub(1200) {
+            panic_with_error!(&e, &SCErrors::PanicModeEnabled);
+        }
+
+        let mut vaults_info: VaultsInfo = e.vaults_info(&target_vault.denomination).unwrap();
 
         let lowest_key = match vaults_info.lowest_key.clone() {
             // It should be impossible to reach this case, but just in case we panic if it happens.
@@ -986,84 +1733,1073 @@ impl VaultsContractTrait for VaultsContract {
             OptionalVaultKey::Some(key) => key,
         };
 
-        validate_prev_keys(
+        assert_regular_vault_updates_validations(
             &e,
+            &target_vault,
+            &target_vault_key,
+            &prev_key,
+            &vault_key,
+            &new_prev_key,
             &lowest_key,
-            &Vec::from_array(&e, [new_prev_key.clone()]),
         );
 
-        let lowest_vault: Vault = e.vault(&lowest_key).unwrap();
-
-        if amount > lowest_vault.total_debt {
-            panic_with_error!(&e, SCErrors::DepositAmountIsMoreThanTotalDebt);
-        }
-
-        burn_stablecoin(&e, &currency, &caller, amount as i128);
+        validate_prev_keys(
+            &e,
+            &vault_key,
+            &Vec::from_array(&e, [destination_prev_key.clone()]),
+        );
 
-        // We withdraw the caller redeemed collateral and pay the fee to the protocol
-        let collateral_to_redeem: u128 = (amount * 10000000) / (rate.price as u128);
+        let new_debt_amount: u128 = target_vault.total_debt - debt_amount;
+        let new_collateral_amount: u128 = target_vault.total_collateral - collateral_amount;
 
-        // redeem fee is 1% or 2.5% based on the condition, half is sent to the treasury and half to the vault owner
-        let fee: u128 = if amount < lowest_vault.total_debt
-            && lowest_vault.next_key != OptionalVaultKey::None
+        if new_debt_amount < vaults_info.min_debt_creation
+            || debt_amount < vaults_info.min_debt_creation
         {
-            calc_fee(&250000, &collateral_to_redeem)
-        } else {
-            calc_fee(&100000, &collateral_to_redeem)
-        };
-        let collateral_to_withdraw: u128 = collateral_to_redeem - fee; // This is the amount the depositor will receive
-        let vault_owner_side: u128 = fee / 2;
-        let treasury_side: u128 = fee.saturating_sub(vault_owner_side);
+            panic_with_error!(&e, &SCErrors::InvalidMinDebtAmount);
+        }
 
-        // We release the collateral to the depositor and pay the vault owner fee and protocol fee
-        withdraw_collateral(&e, &core_state, &caller, collateral_to_withdraw as i128);
-        withdraw_collateral(
+        assert_col_rate_under_min(
             &e,
-            &core_state,
-            &lowest_vault.account,
-            vault_owner_side as i128,
+            &rate.price,
+            &new_debt_amount,
+            &new_collateral_amount,
+            &vaults_info.opening_col_rate,
         );
-        pay_fee(
+
+        assert_col_rate_under_min(
             &e,
-            &core_state,
-            &e.current_contract_address(),
-            treasury_side as i128,
+            &rate.price,
+            &debt_amount,
+            &collateral_amount,
+            &vaults_info.opening_col_rate,
         );
 
-        vaults_info.total_col = vaults_info.total_col - collateral_to_redeem;
-        vaults_info.total_debt = vaults_info.total_debt - amount;
-
-        // If the amount is equal to the debt it means it is a full redeem, so we release the collateral and remove the vault
-        if amount == lowest_vault.total_debt {
-            // If new_prev_key is not None, we panic because we are removing the vault
-            if let OptionalVaultKey::Some(_) = new_prev_key {
-                panic_with_error!(&e, &SCErrors::NextPrevVaultShouldBeNone);
-            }
+        withdraw_vault(&e, &target_vault, &prev_key);
 
-            withdraw_vault(&e, &lowest_vault, &OptionalVaultKey::None);
-            vaults_info.total_vaults = vaults_info.total_vaults - 1;
-            vaults_info.lowest_key = lowest_vault.next_key.clone();
+        // If the target vault is the lowest, we update the lowest value
+        if lowest_key == target_vault_key {
+            vaults_info.lowest_key = target_vault.next_key.clone();
+        }
 
-            // We update the total_col again because we are reducing the collateral in the remaining of the collateral from the removed vault
-            vaults_info.total_col =
-                vaults_info.total_col - (lowest_vault.total_collateral - collateral_to_redeem);
+        let updated_vault_key: VaultKey = VaultKey {
+            index: calculate_vault_index(
+                &e,
+                &target_vault.denomination,
+                new_debt_amount.clone(),
+                new_collateral_amount.clone(),
+            ),
+            account: target_vault.account.clone(),
+            denomination: target_vault.denomination.clone(),
+            id: target_vault.id,
+        };
 
-            // We send the remaining collateral to the owner of the Vault
-            withdraw_collateral(
+        let (_, updated_target_vault_key, updated_target_vault_index_key, updated_lowest_key) =
+            create_and_insert_vault(
                 &e,
-                &core_state,
-                &lowest_vault.account,
-                (lowest_vault.total_collateral - collateral_to_redeem) as i128,
-            );
-        } else {
+                &vaults_info.lowest_key,
+                &updated_vault_key,
+                &new_prev_key,
+                new_debt_amount.clone(),
+                new_collateral_amount.clone(),
+            );
+
+        vaults_info.lowest_key = updated_lowest_key;
+
+        let new_vault_key: VaultKey = VaultKey {
+            index: calculate_vault_index(
+                &e,
+                &target_vault.denomination,
+                debt_amount.clone(),
+                collateral_amount.clone(),
+            ),
+            account: destination.clone(),
+            denomination: target_vault.denomination.clone(),
+            id: new_vault_id(&e, &destination, &target_vault.denomination),
+        };
+
+        let (_, new_vault_key, new_vault_index_key, updated_lowest_key) = create_and_insert_vault(
+            &e,
+            &vaults_info.lowest_key,
+            &new_vault_key,
+            &destination_prev_key,
+            debt_amount.clone(),
+            collateral_amount.clone(),
+        );
+
+        take_gas_reserve(&e, &target_vault.account, &new_vault_key);
+
+        vaults_info.lowest_key = updated_lowest_key;
+        vaults_info.total_vaults = vaults_info.total_vaults + 1;
+        e.set_vaults_info(&vaults_info);
+
+        e.bump_vault(&updated_target_vault_key);
+        e.bump_vault_index(&updated_target_vault_index_key);
+        e.bump_vault(&new_vault_key);
+        e.bump_vault_index(&new_vault_index_key);
+
+        new_vault_key.id
+    }
+
+    // Offers the vault to the `recipient`, only one offer can exist per vault so a new offer replaces the previous one.
+    // The offer is removed if the vault is closed or transferred.
+    fn offer_vault_transfer(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+        offer: TransferOffer,
+    ) {
+        e.bump_instance();
+        owner.require_auth();
+
+        validate_transfer_offer(&e, &owner, &offer);
+
+        // Offers can only be created for an existing vault
+        search_vault(&e, &owner, &denomination, id);
+
+        e.set_transfer_offer(&owner, &denomination, id, &offer);
+        e.bump_transfer_offer(&owner, &denomination, id);
+    }
+
+    fn cancel_vault_transfer(e: Env, owner: Address, denomination: Symbol, id: u32) {
+        e.bump_instance();
+        owner.require_auth();
+
+        if e.transfer_offer(&owner, &denomination, id).is_none() {
+            panic_with_error!(&e, &SCErrorsExt::TransferOfferDoesntExist);
+        }
+
+        e.remove_transfer_offer(&owner, &denomination, id);
+    }
+
+    fn get_vault_transfer_offer(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+    ) -> Option<TransferOffer> {
+        e.bump_instance();
+        e.transfer_offer(&owner, &denomination, id)
+    }
+
+    // Accepts the offer of the vault, the recipient pays the price of the offer (if any) to the owner and becomes
+    // the new owner of the vault.
+    //
+    // **Arguments:**
+    // - `max_debt` - The max debt the recipient accepts to take, the debt could have increased since the offer was made
+    // - `min_collateral` - The min collateral the vault must have, it could have been withdrawn since the offer was made
+    //
+    // **Returns:**
+    // - The new id of the vault
+    fn accept_vault_transfer(
+        e: Env,
+        recipient: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        max_debt: u128,
+        min_collateral: u128,
+    ) -> u32 {
+        e.bump_instance();
+        recipient.require_auth();
+
+        let offer: TransferOffer = e
+            .transfer_offer(&vault_key.account, &vault_key.denomination, vault_key.id)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrorsExt::TransferOfferDoesntExist));
+
+        if offer.recipient != recipient {
+            panic_with_error!(&e, &SCErrorsExt::TransferOfferDoesntExist);
+        }
+
+        if offer.expiration <= e.ledger().timestamp() {
+            panic_with_error!(&e, &SCErrorsExt::TransferOfferIsExpired);
+        }
+
+        let (target_vault, _, _) = search_vault(
+            &e,
+            &vault_key.account,
+            &vault_key.denomination,
+            vault_key.id,
+        );
+
+        if target_vault.total_debt > max_debt || target_vault.total_collateral < min_collateral {
+            panic_with_error!(&e, &SCErrorsExt::TransferTermsNotMet);
+        }
+
+        if offer.price > 0 {
+            token::Client::new(&e, &offer.asset).transfer(
+                &recipient,
+                &vault_key.account,
+                &(offer.price as i128),
+            );
+        }
+
+        transfer_vault(&e, &prev_key, &vault_key, &recipient).id
+    }
+
+    fn approve_operator(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+        operator: Address,
+        approval: OperatorApproval,
+    ) {
+        e.bump_instance();
+        owner.require_auth();
+
+        e.set_operator_approval(&owner, &denomination, id, &operator, &approval);
+        e.bump_operator_approval(&owner, &denomination, id, &operator);
+    }
+
+    fn revoke_operator(e: Env, owner: Address, denomination: Symbol, id: u32, operator: Address) {
+        e.bump_instance();
+        owner.require_auth();
+        e.remove_operator_approval(&owner, &denomination, id, &operator);
+    }
+
+    fn get_operator_approval(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+        operator: Address,
+    ) -> Option<OperatorApproval> {
+        e.bump_instance();
+        e.operator_approval(&owner, &denomination, id, &operator)
+    }
+
+    fn add_protection_order(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+        order: ProtectionOrder,
+    ) {
+        e.bump_instance();
+        owner.require_auth();
+
+        validate_protection_order(&e, &order);
+
+        // Orders can only be added to an existing vault
+        search_vault(&e, &owner, &denomination, id);
+
+        let mut orders: Vec<ProtectionOrder> = e.protection_orders(&owner, &denomination, id);
+        if orders.len() >= MAX_PROTECTION_ORDERS {
+            panic_with_error!(&e, &SCErrorsExt::InvalidProtectionOrder);
+        }
+
+        let currency: Currency = e
+            .currency(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+        let core_state: CoreState = e.core_state().unwrap();
+
+        escrow_protection_order(&e, &core_state, &currency, &owner, &order);
+
+        orders.push_back(order);
+        e.set_protection_orders(&owner, &denomination, id, &orders);
+    }
+
+    fn cancel_protection_order(e: Env, owner: Address, denomination: Symbol, id: u32, index: u32) {
+        e.bump_instance();
+        owner.require_auth();
+
+        let mut orders: Vec<ProtectionOrder> = e.protection_orders(&owner, &denomination, id);
+        let order: ProtectionOrder = orders
+            .get(index)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrorsExt::ProtectionOrderDoesntExist));
+
+        orders.remove(index);
+        e.set_protection_orders(&owner, &denomination, id, &orders);
+
+        let currency: Currency = e.currency(&denomination).unwrap();
+        let core_state: CoreState = e.core_state().unwrap();
+        release_protection_escrow(&e, &core_state, &currency, &order, &owner, order.amount);
+    }
+
+    fn get_protection_orders(
+        e: Env,
+        owner: Address,
+        denomination: Symbol,
+        id: u32,
+    ) -> Vec<ProtectionOrder> {
+        e.bump_instance();
+        e.protection_orders(&owner, &denomination, id)
+    }
+
+    // Executes the first order of the vault whose trigger rate is above the current collateral ratio of the vault
+    fn execute_protection(
+        e: Env,
+        keeper: Address,
+        vault_owner: Address,
+        denomination: Symbol,
+        id: u32,
+        prev_key: OptionalVaultKey,
+        new_prev_key: OptionalVaultKey,
+    ) {
+        e.bump_instance();
+        keeper.require_auth();
+
+        let currency: Currency = e
+            .currency(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+
+        let (target_vault, target_vault_key, _) = search_vault(&e, &vault_owner, &denomination, id);
+
+        let core_state: CoreState = e.core_state().unwrap();
+
+        let rate: PriceData = get_currency_rate(&e, &core_state, &denomination);
+
+        // If price of the collateral hasn't been updated in more than 20 minutes or the protocol is in panic mode we throw
+        if core_state.panic_mode || rate.timestamp < e.ledger().timestamp().saturating_sub(1200) {
+            panic_with_error!(&e, &SCErrors::PanicModeEnabled);
+        }
+
+        let current_rate: u128 = calculate_deposit_ratio(
+            &(rate.price as u128),
+            &target_vault.total_collateral,
+            &target_vault.total_debt,
+        );
+
+        let mut orders: Vec<ProtectionOrder> = e.protection_orders(&vault_owner, &denomination, id);
+        let order_index: u32 = orders
+            .iter()
+            .position(|order| current_rate < order.trigger_rate)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrorsExt::ProtectionIsNotTriggered))
+            as u32;
+        let order: ProtectionOrder = orders.get(order_index).unwrap();
+
+        let mut vaults_info: VaultsInfo = e.vaults_info(&denomination).unwrap();
+
+        // The debt can't go under the min debt, the part of the escrow that can't be used goes back to the owner
+        let repaid: u128 = match order.action {
+            ProtectionAction::Repay => (order.amount - order.tip).min(
+                target_vault
+                    .total_debt
+                    .saturating_sub(vaults_info.min_debt_creation),
+            ),
+            ProtectionAction::AddCollateral => 0,
+        };
+
+        // A repay order can't do anything to a vault that is already at the min debt, so the keeper can't take the tip
+        if matches!(order.action, ProtectionAction::Repay) && repaid == 0 {
+            panic_with_error!(&e, &SCErrorsExt::ProtectionIsNotTriggered);
+        }
+
+        let lowest_key = match vaults_info.lowest_key.clone() {
+            // It should be impossible to reach this case, but just in case we panic if it happens.
+            OptionalVaultKey::None => panic_with_error!(&e, &SCErrors::ThereAreNoVaults),
+            OptionalVaultKey::Some(key) => key,
+        };
+
+        assert_regular_vault_updates_validations(
+            &e,
+            &target_vault,
+            &target_vault_key,
+            &prev_key,
+            &target_vault_key,
+            &new_prev_key,
+            &lowest_key,
+        );
+
+        orders.remove(order_index);
+        e.set_protection_orders(&vault_owner, &denomination, id, &orders);
+
+        withdraw_vault(&e, &target_vault, &prev_key);
+
+        // If the target vault is the lowest, we update the lowest value
+        if lowest_key == target_vault_key {
+            vaults_info.lowest_key = target_vault.next_key.clone();
+        }
+
+        release_protection_escrow(&e, &core_state, &currency, &order, &keeper, order.tip);
+
+        let mut new_debt_amount: u128 = target_vault.total_debt;
+        let mut new_collateral_amount: u128 = target_vault.total_collateral;
+
+        match order.action {
+            ProtectionAction::Repay => {
+                burn_stablecoin(&e, &currency, &e.current_contract_address(), repaid as i128);
+
+                release_protection_escrow(
+                    &e,
+                    &core_state,
+                    &currency,
+                    &order,
+                    &vault_owner,
+                    order.amount - order.tip - repaid,
+                );
+
+                new_debt_amount = new_debt_amount - repaid;
+                vaults_info.total_debt = vaults_info.total_debt - repaid;
+            }
+            ProtectionAction::AddCollateral => {
+                let collateral: u128 = order.amount - order.tip;
+                let fee: u128 = calc_fee(
+                    &get_fee_schedule(&e, &core_state, &denomination).deposit,
+                    &collateral,
+                );
+
+                if fee > 0 {
+                    pay_fee(&e, &core_state, &e.current_contract_address(), fee as i128);
+                }
+
+                new_collateral_amount = new_collateral_amount + collateral - fee;
+                vaults_info.total_col = vaults_info.total_col + collateral - fee;
+            }
+        }
+
+        let new_vault_key: VaultKey = VaultKey {
+            index: calculate_vault_index(
+                &e,
+                &denomination,
+                new_debt_amount.clone(),
+                new_collateral_amount.clone(),
+            ),
+            account: vault_owner,
+            denomination,
+            id,
+        };
+
+        let (_, updated_target_vault_key, updated_target_vault_index_key, updated_lowest_key) =
+            create_and_insert_vault(
+                &e,
+                &vaults_info.lowest_key,
+                &new_vault_key,
+                &new_prev_key,
+                new_debt_amount.clone(),
+                new_collateral_amount.clone(),
+            );
+
+        vaults_info.lowest_key = updated_lowest_key;
+        e.set_vaults_info(&vaults_info);
+
+        e.bump_vault(&updated_target_vault_key);
+        e.bump_vault_index(&updated_target_vault_index_key);
+    }
+
+    fn set_swap_adapter(e: Env, swap_adapter: Address) {
+        e.bump_instance();
+        e.core_state().unwrap().admin.require_auth();
+        e.set_swap_adapter(&swap_adapter);
+    }
+
+    fn remove_swap_adapter(e: Env) {
+        e.bump_instance();
+        e.core_state().unwrap().admin.require_auth();
+        e.remove_swap_adapter();
+    }
+
+    fn get_swap_adapter(e: Env) -> Option<Address> {
+        e.bump_instance();
+        e.swap_adapter()
+    }
+
+    // Opens a vault like `new_vault` but the `initial_debt` is sold for collateral using the swap adapter instead of
+    // being sent to the caller, the vault is created with `collateral_amount` plus what the swap returned.
+    // The opening fee is charged on all that collateral (or on the debt if the denomination uses stablecoin fees)
+    // and the vault must be over the opening collateral ratio like any new vault.
+    // `prev_key` must be valid for the final index, which can be calculated using `min_collateral_out`.
+    fn new_vault_leveraged(
+        e: Env,
+        prev_key: OptionalVaultKey,
+        caller: Address,
+        initial_debt: u128,
+        collateral_amount: u128,
+        denomination: Symbol,
+        min_collateral_out: u128,
+    ) -> u32 {
+        e.bump_instance();
+        caller.require_auth();
+        let currency: Currency = e
+            .currency(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+
+        if !currency.active {
+            panic_with_error!(&e, &SCErrors::CurrencyIsInactive);
+        }
+
+        let swap_adapter: Address = e
+            .swap_adapter()
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::SwapAdapterIsNotSet));
+
+        let core_state: CoreState = e.core_state().unwrap();
+
+        let rate: PriceData = get_currency_rate(&e, &core_state, &denomination);
+
+        // If price of the collateral hasn't been updated in more than 20 minutes or the protocol is in panic mode we throw
+        if core_state.panic_mode || rate.timestamp < e.ledger().timestamp().saturating_sub(1200) {
+            panic_with_error!(&e, &SCErrors::PanicModeEnabled);
+        }
+
+        let mut vaults_info: VaultsInfo = e
+            .vaults_info(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::VaultsInfoHasNotStarted));
+
+        if vaults_info.min_debt_creation > initial_debt {
+            panic_with_error!(e, &SCErrors::InvalidMinDebtAmount);
+        }
+
+        // The new stablecoins go directly to the adapter and the collateral comes back to this contract
+        mint_stablecoin(&e, &currency, &swap_adapter, initial_debt as i128);
+        let collateral_out: u128 = swap_with_adapter(
+            &e,
+            &swap_adapter,
+            &currency.contract,
+            &core_state.col_token,
+            initial_debt,
+            min_collateral_out,
+        );
+
+        let fee_schedule: FeeSchedule = get_fee_schedule(&e, &core_state, &denomination);
+
+        // The opening fee is either taken from the collateral or added to the debt and minted to the treasury
+        let (collateral_fee, debt_fee): (u128, u128) = if fee_schedule.stablecoin_fees {
+            (0, calc_fee(&fee_schedule.open, &initial_debt))
+        } else {
+            (
+                calc_fee(&fee_schedule.open, &(collateral_amount + collateral_out)),
+                0,
+            )
+        };
+        let vault_col: u128 = collateral_amount + collateral_out - collateral_fee;
+        let vault_debt: u128 = initial_debt + debt_fee;
+
+        let deposit_collateral_rate: u128 =
+            calculate_deposit_ratio(&(rate.price as u128), &vault_col, &vault_debt);
+
+        if deposit_collateral_rate < vaults_info.opening_col_rate {
+            panic_with_error!(&e, &SCErrors::InvalidOpeningCollateralRatio);
+        }
+
+        let new_vault_index: u128 = calculate_vault_index(&e, &denomination, vault_debt, vault_col);
+        let new_vault_key: VaultKey = VaultKey {
+            index: new_vault_index.clone(),
+            account: caller.clone(),
+            denomination: denomination.clone(),
+            id: new_vault_id(&e, &caller, &denomination),
+        };
+
+        match prev_key.clone() {
+            OptionalVaultKey::None => {}
+            OptionalVaultKey::Some(value) => {
+                if new_vault_index < value.index {
+                    panic_with_error!(&e, &SCErrors::InvalidPrevVaultIndex);
+                }
+
+                if e.vault(&value).is_none() {
+                    panic_with_error!(&e, &SCErrors::PrevVaultDoesntExist);
+                }
+
+                if value.denomination != denomination {
+                    panic_with_error!(&e, &SCErrors::InvalidPrevKeyDenomination);
+                }
+            }
+        }
+
+        let (_, new_vault_key, new_vault_index_key, updated_lowest_key) = create_and_insert_vault(
+            &e,
+            &vaults_info.lowest_key,
+            &new_vault_key,
+            &prev_key,
+            vault_debt.clone(),
+            vault_col.clone(),
+        );
+
+        take_gas_reserve(&e, &caller, &new_vault_key);
+
+        vaults_info.lowest_key = updated_lowest_key;
+        vaults_info.total_vaults = vaults_info.total_vaults + 1;
+        vaults_info.total_debt = vaults_info.total_debt + vault_debt;
+        vaults_info.total_col = vaults_info.total_col + vault_col;
+        e.set_vaults_info(&vaults_info);
+
+        deposit_collateral(&e, &core_state, &caller, collateral_amount as i128);
+
+        if debt_fee > 0 {
+            mint_stablecoin(&e, &currency, &core_state.treasury, debt_fee as i128);
+        }
+
+        if collateral_fee > 0 {
+            pay_fee(
+                &e,
+                &core_state,
+                &e.current_contract_address(),
+                collateral_fee as i128,
+            );
+        }
+
+        e.bump_vault(&new_vault_key);
+        e.bump_vault_index(&new_vault_index_key);
+
+        new_vault_key.id
+    }
+
+    // Mints `debt_amount` of new debt, sells it for collateral using the swap adapter and deposits the collateral
+    // in the vault. The fees are the same ones charged by `increase_debt` and `increase_collateral`.
+    // The vault must end over the opening collateral ratio, see `assert_swap_col_rate`.
+    fn leverage(
+        e: Env,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        debt_amount: u128,
+        min_collateral_out: u128,
+    ) {
+        let owner: Address = vault_key.account.clone();
+        Self::leverage_as_operator(
+            e,
+            owner,
+            prev_key,
+            vault_key,
+            new_prev_key,
+            debt_amount,
+            min_collateral_out,
+        );
+    }
+
+    // Same as `leverage` but it can also be called by an operator approved by the owner of the vault
+    fn leverage_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        debt_amount: u128,
+        min_collateral_out: u128,
+    ) {
+        e.bump_instance();
+        require_vault_auth(&e, &caller, &vault_key, VaultPermission::Mint(debt_amount));
+
+        let currency: Currency = e
+            .currency(&vault_key.denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+
+        if !currency.active {
+            panic_with_error!(&e, &SCErrors::CurrencyIsInactive);
+        }
+
+        if debt_amount == 0 {
+            panic_with_error!(&e, &SCErrors::InvalidSwapAmount);
+        }
+
+        let swap_adapter: Address = e
+            .swap_adapter()
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::SwapAdapterIsNotSet));
+
+        let (target_vault, target_vault_key, _) = search_vault(
+            &e,
+            &vault_key.account,
+            &vault_key.denomination,
+            vault_key.id,
+        );
+
+        let core_state: CoreState = e.core_state().unwrap();
+
+        let rate: PriceData = get_currency_rate(&e, &core_state, &target_vault.denomination);
+
+        // If price of the collateral hasn't been updated in more than 20 minutes or the protocol is in panic mode we throw
+        if core_state.panic_mode || rate.timestamp < e.ledger().timestamp().saturating_sub(1200) {
+            panic_with_error!(&e, &SCErrors::PanicModeEnabled);
+        }
+
+        let mut vaults_info: VaultsInfo = e.vaults_info(&target_vault.denomination).unwrap();
+
+        let lowest_key = match vaults_info.lowest_key.clone() {
+            // It should be impossible to reach this case, but just in case we panic if it happens.
+            OptionalVaultKey::None => panic_with_error!(&e, &SCErrors::ThereAreNoVaults),
+            OptionalVaultKey::Some(key) => key,
+        };
+
+        assert_regular_vault_updates_validations(
+            &e,
+            &target_vault,
+            &target_vault_key,
+            &prev_key,
+            &vault_key,
+            &new_prev_key,
+            &lowest_key,
+        );
+
+        withdraw_vault(&e, &target_vault, &prev_key);
+
+        // If the target vault is the lowest, we update the lowest value
+        if lowest_key == target_vault_key {
+            vaults_info.lowest_key = target_vault.next_key.clone();
+        }
+
+        let fee_schedule: FeeSchedule =
+            get_fee_schedule(&e, &core_state, &target_vault.denomination);
+        let debt_fee: u128 = if fee_schedule.stablecoin_fees {
+            calc_fee(&fee_schedule.open, &debt_amount)
+        } else {
+            0
+        };
+
+        // The new stablecoins go directly to the adapter and the collateral comes back to this contract
+        mint_stablecoin(&e, &currency, &swap_adapter, debt_amount as i128);
+        let collateral_out: u128 = swap_with_adapter(
+            &e,
+            &swap_adapter,
+            &currency.contract,
+            &core_state.col_token,
+            debt_amount,
+            min_collateral_out,
+        );
+
+        let collateral_fee: u128 = calc_fee(&fee_schedule.deposit, &collateral_out);
+        let new_debt_amount: u128 = target_vault.total_debt + debt_amount + debt_fee;
+        let new_collateral_amount: u128 =
+            target_vault.total_collateral + collateral_out - collateral_fee;
+
+        assert_swap_col_rate(
+            &e,
+            &rate.price,
+            &target_vault.total_debt,
+            &target_vault.total_collateral,
+            &new_debt_amount,
+            &new_collateral_amount,
+            &vaults_info.opening_col_rate,
+        );
+
+        if debt_fee > 0 {
+            mint_stablecoin(&e, &currency, &core_state.treasury, debt_fee as i128);
+        }
+
+        if collateral_fee > 0 {
+            pay_fee(
+                &e,
+                &core_state,
+                &e.current_contract_address(),
+                collateral_fee as i128,
+            );
+        }
+
+        let new_vault_key: VaultKey = VaultKey {
+            index: calculate_vault_index(
+                &e,
+                &target_vault.denomination,
+                new_debt_amount.clone(),
+                new_collateral_amount.clone(),
+            ),
+            account: target_vault.account,
+            denomination: target_vault.denomination,
+            id: target_vault.id,
+        };
+
+        let (_, updated_target_vault_key, updated_target_vault_index_key, updated_lowest_key) =
+            create_and_insert_vault(
+                &e,
+                &vaults_info.lowest_key,
+                &new_vault_key,
+                &new_prev_key,
+                new_debt_amount.clone(),
+                new_collateral_amount.clone(),
+            );
+
+        vaults_info.lowest_key = updated_lowest_key;
+        vaults_info.total_debt = vaults_info.total_debt + debt_amount + debt_fee;
+        vaults_info.total_col =
+            vaults_info.total_col + new_collateral_amount - target_vault.total_collateral;
+        e.set_vaults_info(&vaults_info);
+
+        e.bump_vault(&updated_target_vault_key);
+        e.bump_vault_index(&updated_target_vault_index_key);
+    }
+
+    // Takes `collateral_amount` from the vault, sells it (minus the withdraw fee) for stablecoins using the swap adapter
+    // and uses them to pay the debt of the vault. The vault can't be closed this way, the debt left must be at
+    // least the min debt of the denomination. The vault must end over the opening collateral ratio unless the swap
+    // doesn't lower its ratio, see `assert_swap_col_rate`.
+    fn deleverage(
+        e: Env,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        collateral_amount: u128,
+        min_debt_out: u128,
+    ) {
+        let owner: Address = vault_key.account.clone();
+        Self::deleverage_as_operator(
+            e,
+            owner,
+            prev_key,
+            vault_key,
+            new_prev_key,
+            collateral_amount,
+            min_debt_out,
+        );
+    }
+
+    // Same as `deleverage` but it can also be called by an operator approved by the owner of the vault
+    fn deleverage_as_operator(
+        e: Env,
+        caller: Address,
+        prev_key: OptionalVaultKey,
+        vault_key: VaultKey,
+        new_prev_key: OptionalVaultKey,
+        collateral_amount: u128,
+        min_debt_out: u128,
+    ) {
+        e.bump_instance();
+        require_vault_auth(
+            &e,
+            &caller,
+            &vault_key,
+            VaultPermission::Withdraw(collateral_amount),
+        );
+
+        let currency: Currency = e
+            .currency(&vault_key.denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+
+        let swap_adapter: Address = e
+            .swap_adapter()
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::SwapAdapterIsNotSet));
+
+        let (target_vault, target_vault_key, _) = search_vault(
+            &e,
+            &vault_key.account,
+            &vault_key.denomination,
+            vault_key.id,
+        );
+
+        if collateral_amount == 0 || collateral_amount >= target_vault.total_collateral {
+            panic_with_error!(&e, &SCErrors::InvalidSwapAmount);
+        }
+
+        let core_state: CoreState = e.core_state().unwrap();
+
+        let rate: PriceData = get_currency_rate(&e, &core_state, &target_vault.denomination);
+
+        // If price of the collateral hasn't been updated in more than 20 minutes or the protocol is in panic mode we throw
+        if core_state.panic_mode || rate.timestamp < e.ledger().timestamp().saturating_sub(1200) {
+            panic_with_error!(&e, &SCErrors::PanicModeEnabled);
+        }
+
+        let mut vaults_info: VaultsInfo = e.vaults_info(&target_vault.denomination).unwrap();
+
+        let lowest_key = match vaults_info.lowest_key.clone() {
+            // It should be impossible to reach this case, but just in case we panic if it happens.
+            OptionalVaultKey::None => panic_with_error!(&e, &SCErrors::ThereAreNoVaults),
+            OptionalVaultKey::Some(key) => key,
+        };
+
+        assert_regular_vault_updates_validations(
+            &e,
+            &target_vault,
+            &target_vault_key,
+            &prev_key,
+            &vault_key,
+            &new_prev_key,
+            &lowest_key,
+        );
+
+        withdraw_vault(&e, &target_vault, &prev_key);
+
+        // If the target vault is the lowest, we update the lowest value
+        if lowest_key == target_vault_key {
+            vaults_info.lowest_key = target_vault.next_key.clone();
+        }
+
+        let fee: u128 = calc_fee(
+            &get_fee_schedule(&e, &core_state, &target_vault.denomination).withdraw,
+            &collateral_amount,
+        );
+        if fee > 0 {
+            pay_fee(&e, &core_state, &e.current_contract_address(), fee as i128);
+        }
+
+        withdraw_collateral(
+            &e,
+            &core_state,
+            &swap_adapter,
+            (collateral_amount - fee) as i128,
+        );
+        let debt_out: u128 = swap_with_adapter(
+            &e,
+            &swap_adapter,
+            &core_state.col_token,
+            &currency.contract,
+            collateral_amount - fee,
+            min_debt_out,
+        );
+
+        if debt_out >= target_vault.total_debt
+            || target_vault.total_debt - debt_out < vaults_info.min_debt_creation
+        {
+            panic_with_error!(&e, &SCErrors::InvalidMinDebtAmount);
+        }
+
+        burn_stablecoin(
+            &e,
+            &currency,
+            &e.current_contract_address(),
+            debt_out as i128,
+        );
+
+        let new_debt_amount: u128 = target_vault.total_debt - debt_out;
+        let new_collateral_amount: u128 = target_vault.total_collateral - collateral_amount;
+
+        assert_swap_col_rate(
+            &e,
+            &rate.price,
+            &target_vault.total_debt,
+            &target_vault.total_collateral,
+            &new_debt_amount,
+            &new_collateral_amount,
+            &vaults_info.opening_col_rate,
+        );
+
+        let new_vault_key: VaultKey = VaultKey {
+            index: calculate_vault_index(
+                &e,
+                &target_vault.denomination,
+                new_debt_amount.clone(),
+                new_collateral_amount.clone(),
+            ),
+            account: target_vault.account,
+            denomination: target_vault.denomination,
+            id: target_vault.id,
+        };
+
+        let (_, updated_target_vault_key, updated_target_vault_index_key, updated_lowest_key) =
+            create_and_insert_vault(
+                &e,
+                &vaults_info.lowest_key,
+                &new_vault_key,
+                &new_prev_key,
+                new_debt_amount.clone(),
+                new_collateral_amount.clone(),
+            );
+
+        vaults_info.lowest_key = updated_lowest_key;
+        vaults_info.total_debt = vaults_info.total_debt - debt_out;
+        vaults_info.total_col = vaults_info.total_col - collateral_amount;
+        e.set_vaults_info(&vaults_info);
+
+        e.bump_vault(&updated_target_vault_key);
+        e.bump_vault_index(&updated_target_vault_index_key);
+    }
+
+    fn redeem(
+        e: Env,
+        caller: Address,
+        denomination: Symbol,
+        new_prev_key: OptionalVaultKey,
+        amount: u128,
+    ) {
+        e.bump_instance();
+        caller.require_auth();
+
+        let currency: Currency = e
+            .currency(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+
+        if !currency.active {
+            panic_with_error!(&e, &SCErrors::CurrencyIsInactive);
+        }
+
+        let core_state: CoreState = e.core_state().unwrap();
+        let rate: PriceData = get_currency_rate(&e, &core_state, &denomination);
+        let mut vaults_info: VaultsInfo = e.vaults_info(&denomination).unwrap();
+
+        let lowest_key = match vaults_info.lowest_key.clone() {
+            // It should be impossible to reach this case, but just in case we panic if it happens.
+            OptionalVaultKey::None => panic_with_error!(&e, &SCErrors::ThereAreNoVaults),
+            OptionalVaultKey::Some(key) => key,
+        };
+
+        validate_prev_keys(
+            &e,
+            &lowest_key,
+            &Vec::from_array(&e, [new_prev_key.clone()]),
+        );
+
+        let mut lowest_vault: Vault = e.vault(&lowest_key).unwrap();
+        apply_redistribution(&e, &mut lowest_vault);
+
+        if amount > lowest_vault.total_debt {
+            panic_with_error!(&e, SCErrors::DepositAmountIsMoreThanTotalDebt);
+        }
+
+        burn_stablecoin(&e, &currency, &caller, amount as i128);
+
+        // We withdraw the caller redeemed collateral and pay the fee to the protocol
+        let collateral_to_redeem: u128 = (amount * 10000000) / (rate.price as u128);
+
+        // redeem fee is the redemption or partial redemption rate based on the condition, half is sent to the treasury and half to the vault owner
+        let fee_schedule: FeeSchedule = get_fee_schedule(&e, &core_state, &denomination);
+        let fee: u128 = if amount < lowest_vault.total_debt
+            && lowest_vault.next_key != OptionalVaultKey::None
+        {
+            calc_fee(&fee_schedule.partial_redemption, &collateral_to_redeem)
+        } else {
+            calc_fee(&fee_schedule.redemption, &collateral_to_redeem)
+        };
+        let collateral_to_withdraw: u128 = collateral_to_redeem - fee; // This is the amount the depositor will receive
+        let vault_owner_side: u128 = fee / 2;
+        let treasury_side: u128 = fee.saturating_sub(vault_owner_side);
+
+        // We release the collateral to the depositor and pay the vault owner fee and protocol fee
+        withdraw_collateral(&e, &core_state, &caller, collateral_to_withdraw as i128);
+        withdraw_collateral(
+            &e,
+            &core_state,
+            &lowest_vault.account,
+            vault_owner_side as i128,
+        );
+        pay_fee(
+            &e,
+            &core_state,
+            &e.current_contract_address(),
+            treasury_side as i128,
+        );
+
+        vaults_info.total_col = vaults_info.total_col - collateral_to_redeem;
+        vaults_info.total_debt = vaults_info.total_debt - amount;
+
+        // If the amount is equal to the debt it means it is a full redeem, so we release the collateral and remove the vault
+        if amount == lowest_vault.total_debt {
+            // If new_prev_key is not None, we panic because we are removing the vault
+            if let OptionalVaultKey::Some(_) = new_prev_key {
+                panic_with_error!(&e, &SCErrors::NextPrevVaultShouldBeNone);
+            }
+
+            withdraw_vault(&e, &lowest_vault, &OptionalVaultKey::None);
+            remove_vault_id(
+                &e,
+                &lowest_vault.account,
+                &lowest_vault.denomination,
+                lowest_vault.id,
+            );
+            release_gas_reserve(&e, &lowest_key, &lowest_vault.account);
+            vaults_info.total_vaults = vaults_info.total_vaults - 1;
+            vaults_info.lowest_key = lowest_vault.next_key.clone();
+
+            // We update the total_col again because we are reducing the collateral in the remaining of the collateral from the removed vault
+            vaults_info.total_col =
+                vaults_info.total_col - (lowest_vault.total_collateral - collateral_to_redeem);
+
+            // We send the remaining collateral to the owner of the Vault
+            withdraw_collateral(
+                &e,
+                &core_state,
+                &lowest_vault.account,
+                (lowest_vault.total_collateral - collateral_to_redeem) as i128,
+            );
+        } else {
             // If amount is not enough to pay all the debt, we check the debt value is not lower than the minimum and if is ok we just updated the stats of the user's vault
             let new_vault_debt: u128 = lowest_vault.total_debt - amount;
             if new_vault_debt < vaults_info.min_debt_creation {
                 panic_with_error!(&e, &SCErrors::InvalidMinDebtAmount);
             }
             let new_vault_collateral: u128 = lowest_vault.total_collateral - collateral_to_redeem;
-            let new_vault_index: u128 =
-                calculate_user_vault_index(new_vault_debt.clone(), new_vault_collateral.clone());
+            let new_vault_index: u128 = calculate_vault_index(
+                &e,
+                &lowest_vault.denomination,
+                new_vault_debt.clone(),
+                new_vault_collateral.clone(),
+            );
 
             // In theory the collateral rate should not go down
             // But we still check the col rate is not under min ratio
@@ -1075,89 +2811,688 @@ impl VaultsContractTrait for VaultsContract {
                 &vaults_info.min_col_rate,
             );
 
-            withdraw_vault(&e, &lowest_vault, &OptionalVaultKey::None);
+            withdraw_vault(&e, &lowest_vault, &OptionalVaultKey::None);
+
+            // We are working with the lowest vault so we update the lowest value
+            vaults_info.lowest_key = lowest_vault.next_key.clone();
+
+            let (_, updated_target_vault_key, updated_target_vault_index_key, updated_lowest_key) =
+                create_and_insert_vault(
+                    &e,
+                    &vaults_info.lowest_key,
+                    &VaultKey {
+                        index: new_vault_index.clone(),
+                        account: lowest_vault.account.clone(),
+                        denomination: lowest_vault.denomination.clone(),
+                        id: lowest_vault.id,
+                    },
+                    &new_prev_key,
+                    new_vault_debt.clone(),
+                    new_vault_collateral.clone(),
+                );
+
+            vaults_info.lowest_key = updated_lowest_key;
+
+            e.bump_vault(&updated_target_vault_key);
+            e.bump_vault_index(&updated_target_vault_index_key);
+        }
+
+        e.set_vaults_info(&vaults_info);
+    }
+
+    fn liquidate(
+        e: Env,
+        liquidator: Address,
+        denomination: Symbol,
+        total_vaults_to_liquidate: u32,
+    ) -> Vec<Vault> {
+        e.bump_instance();
+        liquidator.require_auth();
+
+        let core_state: CoreState = e.core_state().unwrap();
+        let rate: PriceData = get_currency_rate(&e, &core_state, &denomination);
+        let currency: Currency = e
+            .currency(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+        let mut liquidation: Liquidation = new_liquidation(
+            &e,
+            &core_state,
+            &liquidator,
+            &denomination,
+            rate.price as u128,
+        );
+        let vaults_to_liquidate: Vec<Vault> = get_vaults(
+            &e,
+            &OptionalVaultKey::None,
+            &liquidation.vaults_info,
+            total_vaults_to_liquidate,
+            true,
+            rate.price as u128,
+        );
+
+        if vaults_to_liquidate.len() < total_vaults_to_liquidate {
+            panic_with_error!(&e, &SCErrors::NotEnoughVaultsToLiquidate);
+        }
+
+        for vault in vaults_to_liquidate.iter() {
+            liquidate_vault(
+                &e,
+                &mut liquidation,
+                &vault,
+                &OptionalVaultKey::None,
+                u128::MAX,
+            );
+        }
+
+        settle_liquidation(&e, &core_state, &currency, &liquidation);
+
+        vaults_to_liquidate
+    }
+
+    // Liquidates the given vaults in order, targets that no longer exist, that can't be liquidated or that would make
+    // the liquidator burn more than `max_debt` are skipped. The prev keys must be valid at the moment each target is
+    // processed, IE after the previous targets were removed.
+    fn liquidate_vaults(
+        e: Env,
+        liquidator: Address,
+        denomination: Symbol,
+        targets: Vec<LiquidationTarget>,
+        max_debt: u128,
+    ) -> Vec<Vault> {
+        e.bump_instance();
+        liquidator.require_auth();
+
+        let core_state: CoreState = e.core_state().unwrap();
+        let rate: PriceData = get_currency_rate(&e, &core_state, &denomination);
+        let currency: Currency = e
+            .currency(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+        let mut liquidation: Liquidation = new_liquidation(
+            &e,
+            &core_state,
+            &liquidator,
+            &denomination,
+            rate.price as u128,
+        );
+        let mut liquidated_vaults: Vec<Vault> = Vec::new(&e);
+
+        for target in targets.iter() {
+            if target.vault_key.denomination != denomination {
+                panic_with_error!(&e, &SCErrors::InvalidPrevKeyDenomination);
+            }
+
+            validate_prev_keys(
+                &e,
+                &target.vault_key,
+                &Vec::from_array(&e, [target.prev_key.clone()]),
+            );
+
+            let mut vault: Vault = match e.vault(&target.vault_key) {
+                Some(vault) if vault.index == target.vault_key.index => vault,
+                _ => continue,
+            };
+            apply_redistribution(&e, &mut vault);
 
-            // We are working with the lowest vault so we update the lowest value
-            vaults_info.lowest_key = lowest_vault.next_key.clone();
+            if !can_be_liquidated(&vault, &liquidation.vaults_info, &(rate.price as u128)) {
+                continue;
+            }
 
-            let (_, updated_target_vault_key, updated_target_vault_index_key, updated_lowest_key) =
-                create_and_insert_vault(
-                    &e,
-                    &vaults_info.lowest_key,
-                    &VaultKey {
-                        index: new_vault_index.clone(),
-                        account: lowest_vault.account.clone(),
-                        denomination: lowest_vault.denomination.clone(),
-                    },
-                    &new_prev_key,
-                    new_vault_debt.clone(),
-                    new_vault_collateral.clone(),
-                );
+            if liquidate_vault(&e, &mut liquidation, &vault, &target.prev_key, max_debt) {
+                liquidated_vaults.push_back(vault);
+            }
+        }
 
-            vaults_info.lowest_key = updated_lowest_key;
+        settle_liquidation(&e, &core_state, &currency, &liquidation);
 
-            e.bump_vault(&updated_target_vault_key);
-            e.bump_vault_index(&updated_target_vault_index_key);
+        liquidated_vaults
+    }
+
+    // Liquidates up to `total_vaults_to_liquidate` vaults starting from the lowest one, it stops (without panicking)
+    // once the next vault can't be liquidated or would make the liquidator burn more than `max_debt`
+    fn liquidate_up_to(
+        e: Env,
+        liquidator: Address,
+        denomination: Symbol,
+        total_vaults_to_liquidate: u32,
+        max_debt: u128,
+    ) -> Vec<Vault> {
+        e.bump_instance();
+        liquidator.require_auth();
+
+        let core_state: CoreState = e.core_state().unwrap();
+        let rate: PriceData = get_currency_rate(&e, &core_state, &denomination);
+        let currency: Currency = e
+            .currency(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+        let mut liquidation: Liquidation = new_liquidation(
+            &e,
+            &core_state,
+            &liquidator,
+            &denomination,
+            rate.price as u128,
+        );
+        let mut liquidated_vaults: Vec<Vault> = Vec::new(&e);
+
+        for _ in 0..total_vaults_to_liquidate {
+            let lowest_key: VaultKey = match liquidation.vaults_info.lowest_key.clone() {
+                OptionalVaultKey::None => break,
+                OptionalVaultKey::Some(key) => key,
+            };
+
+            let mut vault: Vault = e.vault(&lowest_key).unwrap();
+            apply_redistribution(&e, &mut vault);
+
+            if !can_be_liquidated(&vault, &liquidation.vaults_info, &(rate.price as u128)) {
+                break;
+            }
+
+            if !liquidate_vault(
+                &e,
+                &mut liquidation,
+                &vault,
+                &OptionalVaultKey::None,
+                max_debt,
+            ) {
+                break;
+            }
+
+            liquidated_vaults.push_back(vault);
         }
 
-        e.set_vaults_info(&vaults_info);
+        settle_liquidation(&e, &core_state, &currency, &liquidation);
+
+        liquidated_vaults
     }
 
-    fn liquidate(
+    // Liquidates up to `total_vaults_to_liquidate` of the lowest vaults like `liquidate_up_to`, but the stablecoins
+    // the liquidator has to burn are flash minted so no inventory is needed. The collateral goes to the `receiver`
+    // and its `on_flash_mint` callback must send back the amount plus the flash mint fee before it returns.
+    // Soroban doesn't allow reentrancy so a flash mint from `flash_mint` can't be used to call `liquidate`, this is
+    // the way to do it. The flash minted amount is capped by the max amount of the denomination.
+    fn liquidate_with_flash_mint(
         e: Env,
         liquidator: Address,
+        receiver: Address,
         denomination: Symbol,
         total_vaults_to_liquidate: u32,
+        data: Bytes,
     ) -> Vec<Vault> {
         e.bump_instance();
         liquidator.require_auth();
 
         let core_state: CoreState = e.core_state().unwrap();
+        if core_state.panic_mode {
+            panic_with_error!(&e, &SCErrors::PanicModeEnabled);
+        }
+
+        let flash_mint_config: FlashMintConfig = e
+            .flash_mint_config(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::FlashMintIsDisabled));
+
         let rate: PriceData = get_currency_rate(&e, &core_state, &denomination);
         let currency: Currency = e
             .currency(&denomination)
             .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
-        let mut vaults_info: VaultsInfo = e.vaults_info(&denomination).unwrap();
-        let mut collateral_to_withdraw: u128 = 0;
-        let mut amount_to_deposit: u128 = 0;
-        let vaults_to_liquidate: Vec<Vault> = get_vaults(
+        let mut liquidation: Liquidation = new_liquidation(
             &e,
-            &OptionalVaultKey::None,
-            &vaults_info,
-            total_vaults_to_liquidate,
-            true,
+            &core_state,
+            &receiver,
+            &denomination,
             rate.price as u128,
         );
+        let mut liquidated_vaults: Vec<Vault> = Vec::new(&e);
 
-        if vaults_to_liquidate.len() < total_vaults_to_liquidate {
+        for _ in 0..total_vaults_to_liquidate {
+            let lowest_key: VaultKey = match liquidation.vaults_info.lowest_key.clone() {
+                OptionalVaultKey::None => break,
+                OptionalVaultKey::Some(key) => key,
+            };
+
+            let mut vault: Vault = e.vault(&lowest_key).unwrap();
+            apply_redistribution(&e, &mut vault);
+
+            if !can_be_liquidated(&vault, &liquidation.vaults_info, &(rate.price as u128)) {
+                break;
+            }
+
+            if !liquidate_vault(
+                &e,
+                &mut liquidation,
+                &vault,
+                &OptionalVaultKey::None,
+                flash_mint_config.max_amount,
+            ) {
+                break;
+            }
+
+            liquidated_vaults.push_back(vault);
+        }
+
+        if liquidated_vaults.is_empty() {
             panic_with_error!(&e, &SCErrors::NotEnoughVaultsToLiquidate);
         }
 
-        for vault in vaults_to_liquidate.iter() {
+        settle_flash_liquidation(
+            &e,
+            &core_state,
+            &currency,
+            &liquidation,
+            &flash_mint_config,
+            &liquidator,
+            &data,
+        );
+
+        liquidated_vaults
+    }
+
+    // Fallback when the insolvent vaults can't be absorbed by the stability pool and nobody liquidates them:
+    // the debt and collateral (minus the liquidation fee) of each vault is shared between the remaining vaults
+    // in proportion to their debt. The remaining vaults receive their part the next time they are touched.
+    fn redistribute(
+        e: Env,
+        caller: Address,
+        denomination: Symbol,
+        total_vaults_to_redistribute: u32,
+    ) -> Vec<Vault> {
+        e.bump_instance();
+        caller.require_auth();
+
+        let core_state: CoreState = e.core_state().unwrap();
+        let rate: PriceData = get_currency_rate(&e, &core_state, &denomination);
+        if e.currency(&denomination).is_none() {
+            panic_with_error!(&e, &SCErrors::CurrencyDoesntExist);
+        }
+        let mut vaults_info: VaultsInfo = e.vaults_info(&denomination).unwrap();
+        let liquidation_fee: u128 = get_fee_schedule(&e, &core_state, &denomination).liquidation;
+        let stability_pool: Option<StabilityPool> = e.stability_pool(&denomination);
+        let mut total_fee: u128 = 0;
+        let mut redistributed_vaults: Vec<Vault> = Vec::new(&e);
+
+        for _ in 0..total_vaults_to_redistribute {
+            // Vaults are loaded one by one because each redistribution updates the pending values of the next ones
+            let lowest_key: VaultKey = match vaults_info.lowest_key.clone() {
+                OptionalVaultKey::None => {
+                    panic_with_error!(&e, &SCErrors::NotEnoughVaultsToLiquidate)
+                }
+                OptionalVaultKey::Some(key) => key,
+            };
+
+            let mut vault: Vault = e.vault(&lowest_key).unwrap();
+            apply_redistribution(&e, &mut vault);
+
             if !can_be_liquidated(&vault, &vaults_info, &(rate.price as u128)) {
-                panic_with_error!(&e, SCErrors::UserVaultCantBeLiquidated);
+                panic_with_error!(&e, SCErrors::NotEnoughVaultsToLiquidate);
+            }
+
+            if let Some(pool) = stability_pool.as_ref() {
+                if can_absorb_debt(pool, vault.total_debt) {
+                    panic_with_error!(&e, SCErrors::StabilityPoolCanAbsorbDebt);
+                }
+            }
+
+            if vaults_info.total_vaults <= 1 || vaults_info.total_debt <= vault.total_debt {
+                panic_with_error!(&e, SCErrors::NoVaultsToReceiveRedistribution);
             }
 
-            collateral_to_withdraw = collateral_to_withdraw + vault.total_collateral;
-            amount_to_deposit = amount_to_deposit + vault.total_debt;
+            let fee: u128 = calc_fee(&liquidation_fee, &vault.total_collateral);
 
+            // The debt and collateral stay in the protocol, only the fee and the vault itself are removed
+            redistribute_vault(
+                &e,
+                &denomination,
+                vaults_info.total_debt - vault.total_debt,
+                vault.total_debt,
+                vault.total_collateral - fee,
+            );
+
+            total_fee = total_fee + fee;
             vaults_info.total_vaults = vaults_info.total_vaults - 1;
-            vaults_info.total_col = vaults_info.total_col - vault.total_collateral;
-            vaults_info.total_debt = vaults_info.total_debt - vault.total_debt;
+            vaults_info.total_col = vaults_info.total_col - fee;
 
             withdraw_vault(&e, &vault, &OptionalVaultKey::None);
+            remove_vault_id(&e, &vault.account, &vault.denomination, vault.id);
+            release_gas_reserve(&e, &lowest_key, &caller);
 
-            vaults_info.lowest_key = vault.next_key;
+            vaults_info.lowest_key = vault.next_key.clone();
+            redistributed_vaults.push_back(vault);
         }
 
         e.set_vaults_info(&vaults_info);
-        burn_stablecoin(&e, &currency, &liquidator, amount_to_deposit as i128);
 
-        let fee: u128 = calc_fee(&core_state.fee, &collateral_to_withdraw);
-        let end_collateral: u128 = collateral_to_withdraw - fee;
-        withdraw_collateral(&e, &core_state, &liquidator, end_collateral as i128);
-        pay_fee(&e, &core_state, &e.current_contract_address(), fee as i128);
+        if total_fee > 0 {
+            pay_fee(
+                &e,
+                &core_state,
+                &e.current_contract_address(),
+                total_fee as i128,
+            );
+        }
+
+        redistributed_vaults
+    }
 
-        vaults_to_liquidate
+    fn get_redistribution(e: Env, denomination: Symbol) -> Redistribution {
+        e.bump_instance();
+        get_redistribution(&e, &denomination)
+    }
+
+    fn set_auction_config(e: Env, denomination: Symbol, auction_config: AuctionConfig) {
+        e.bump_instance();
+        e.core_state().unwrap().admin.require_auth();
+
+        if e.currency(&denomination).is_none() {
+            panic_with_error!(&e, &SCErrors::CurrencyDoesntExist);
+        }
+
+        validate_auction_config(&e, &auction_config);
+        e.set_auction_config(&denomination, &auction_config);
+    }
+
+    fn remove_auction_config(e: Env, denomination: Symbol) {
+        e.bump_instance();
+        e.core_state().unwrap().admin.require_auth();
+        e.remove_auction_config(&denomination);
+    }
+
+    fn get_auction_config(e: Env, denomination: Symbol) -> Option<AuctionConfig> {
+        e.bump_instance();
+        e.auction_config(&denomination)
+    }
+
+    // Buys up to `collateral_amount` of the auctioned collateral at the current price, the bidder burns the cost in stablecoin.
+    // If the cost is higher than the debt left, the bidder only pays the debt and receives the collateral it covers.
+    // Once the debt is covered the collateral left is sent back to the owner of the liquidated vault.
+    fn bid(e: Env, bidder: Address, auction_id: u64, collateral_amount: u128, max_price: u128) {
+        e.bump_instance();
+        bidder.require_auth();
+
+        let mut auction: Auction = e
+            .auction(auction_id)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::AuctionDoesntExist));
+
+        let price: u128 = auction_price(&e, &auction);
+        if price == 0 {
+            panic_with_error!(&e, &SCErrors::AuctionIsExpired);
+        }
+
+        if price > max_price {
+            panic_with_error!(&e, &SCErrors::AuctionPriceIsHigherThanMax);
+        }
+
+        if collateral_amount == 0 || collateral_amount > auction.collateral {
+            panic_with_error!(&e, &SCErrors::InvalidAuctionBid);
+        }
+
+        let core_state: CoreState = e.core_state().unwrap();
+        let currency: Currency = e.currency(&auction.denomination).unwrap();
+
+        let mut collateral_bought: u128 = collateral_amount;
+        let mut cost: u128 = (collateral_amount * price).div_ceil(1_0000000);
+        if cost >= auction.debt {
+            cost = auction.debt;
+            collateral_bought = (auction.debt * 1_0000000 / price).min(auction.collateral);
+        }
+
+        burn_stablecoin(&e, &currency, &bidder, cost as i128);
+        withdraw_collateral(&e, &core_state, &bidder, collateral_bought as i128);
+
+        auction.debt = auction.debt - cost;
+        auction.collateral = auction.collateral - collateral_bought;
+
+        if auction.debt == 0 {
+            if auction.collateral > 0 {
+                withdraw_collateral(&e, &core_state, &auction.owner, auction.collateral as i128);
+                auction.collateral = 0;
+            }
+
+            e.remove_auction(auction_id);
+            emit_auction_event(&e, symbol_short!("finish"), &auction);
+        } else {
+            e.set_auction(&auction);
+            e.bump_auction(auction_id);
+            emit_auction_event(&e, symbol_short!("bid"), &auction);
+        }
+    }
+
+    // Starts again an expired auction from the current oracle price with the current config of the denomination
+    fn restart_auction(e: Env, caller: Address, auction_id: u64) {
+        e.bump_instance();
+        caller.require_auth();
+
+        let mut auction: Auction = e
+            .auction(auction_id)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::AuctionDoesntExist));
+
+        if !is_auction_expired(&e, &auction) {
+            panic_with_error!(&e, &SCErrors::AuctionIsNotExpired);
+        }
+
+        let auction_config: AuctionConfig = e
+            .auction_config(&auction.denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::AuctionConfigDoesntExist));
+
+        let core_state: CoreState = e.core_state().unwrap();
+        let rate: PriceData = get_currency_rate(&e, &core_state, &auction.denomination);
+
+        auction.start_price = (rate.price as u128) * auction_config.premium / 1_0000000;
+        auction.min_price = (rate.price as u128) * auction_config.min_price / 1_0000000;
+        auction.started_at = e.ledger().timestamp();
+        auction.duration = auction_config.duration;
+
+        e.set_auction(&auction);
+        e.bump_auction(auction_id);
+        emit_auction_event(&e, symbol_short!("restart"), &auction);
+    }
+
+    // Shares the debt and collateral left in an expired (or sold out) auction between the vaults of the denomination
+    fn redistribute_auction(e: Env, caller: Address, auction_id: u64) {
+        e.bump_instance();
+        caller.require_auth();
+
+        let auction: Auction = e
+            .auction(auction_id)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::AuctionDoesntExist));
+
+        if auction.collateral > 0 && !is_auction_expired(&e, &auction) {
+            panic_with_error!(&e, &SCErrors::AuctionIsNotExpired);
+        }
+
+        let mut vaults_info: VaultsInfo = e.vaults_info(&auction.denomination).unwrap();
+        if vaults_info.total_vaults == 0 || vaults_info.total_debt == 0 {
+            panic_with_error!(&e, &SCErrors::NoVaultsToReceiveRedistribution);
+        }
+
+        redistribute_vault(
+            &e,
+            &auction.denomination,
+            vaults_info.total_debt,
+            auction.debt,
+            auction.collateral,
+        );
+
+        vaults_info.total_debt = vaults_info.total_debt + auction.debt;
+        vaults_info.total_col = vaults_info.total_col + auction.collateral;
+        e.set_vaults_info(&vaults_info);
+
+        e.remove_auction(auction_id);
+        emit_auction_event(&e, symbol_short!("redist"), &auction);
+    }
+
+    fn get_auction(e: Env, auction_id: u64) -> Auction {
+        e.bump_instance();
+        e.auction(auction_id)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::AuctionDoesntExist))
+    }
+
+    fn get_auction_price(e: Env, auction_id: u64) -> u128 {
+        e.bump_instance();
+        let auction: Auction = e
+            .auction(auction_id)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::AuctionDoesntExist));
+        auction_price(&e, &auction)
+    }
+
+    fn stability_deposit(e: Env, caller: Address, denomination: Symbol, amount: u128) {
+        e.bump_instance();
+        caller.require_auth();
+
+        let currency: Currency = e
+            .currency(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+
+        if !currency.active {
+            panic_with_error!(&e, &SCErrors::CurrencyIsInactive);
+        }
+
+        if amount == 0 {
+            panic_with_error!(&e, &SCErrors::InvalidStabilityDepositAmount);
+        }
+
+        let core_state: CoreState = e.core_state().unwrap();
+        let mut pool: StabilityPool = e
+            .stability_pool(&denomination)
+            .unwrap_or_else(|| new_stability_pool(&denomination));
+
+        // If the user already has a deposit, we pay the collateral earned so far and compound the deposit
+        let current_deposit: u128 = match e.stability_deposit(&caller, &denomination) {
+            None => {
+                pool.deposits = pool.deposits + 1;
+                0
+            }
+            Some(deposit) => {
+                settle_stability_deposit(&e, &core_state, &mut pool, &caller, &deposit)
+            }
+        };
+
+        token::Client::new(&e, &currency.contract).transfer(
+            &caller,
+            &e.current_contract_address(),
+            &(amount as i128),
+        );
+
+        pool.total_deposits = pool.total_deposits + amount;
+
+        e.set_stability_deposit(
+            &caller,
+            &denomination,
+            &new_stability_deposit(&pool, current_deposit + amount),
+        );
+        e.bump_stability_deposit(&caller, &denomination);
+        e.set_stability_pool(&pool);
+    }
+
+    fn stability_withdraw(e: Env, caller: Address, denomination: Symbol, amount: u128) {
+        e.bump_instance();
+        caller.require_auth();
+
+        let currency: Currency = e
+            .currency(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::CurrencyDoesntExist));
+
+        let deposit: StabilityDeposit = e
+            .stability_deposit(&caller, &denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::StabilityDepositDoesntExist));
+
+        let core_state: CoreState = e.core_state().unwrap();
+        let rate: PriceData = get_currency_rate(&e, &core_state, &denomination);
+        let vaults_info: VaultsInfo = e
+            .vaults_info(&denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::VaultsInfoHasNotStarted));
+
+        // Depositors can not leave while there are vaults the pool should absorb
+        if let OptionalVaultKey::Some(lowest_key) = vaults_info.lowest_key.clone() {
+            let mut lowest_vault: Vault = e.vault(&lowest_key).unwrap();
+            apply_redistribution(&e, &mut lowest_vault);
+            if can_be_liquidated(&lowest_vault, &vaults_info, &(rate.price as u128)) {
+                panic_with_error!(&e, &SCErrors::StabilityPoolWithdrawalsLocked);
+            }
+        }
+
+        let mut pool: StabilityPool = e.stability_pool(&denomination).unwrap();
+        let current_deposit: u128 =
+            settle_stability_deposit(&e, &core_state, &mut pool, &caller, &deposit);
+
+        if amount == 0 || amount > current_deposit {
+            panic_with_error!(&e, &SCErrors::InvalidStabilityDepositAmount);
+        }
+
+        token::Client::new(&e, &currency.contract).transfer(
+            &e.current_contract_address(),
+            &caller,
+            &(amount as i128),
+        );
+
+        pool.total_deposits = pool.total_deposits - amount;
+
+        if current_deposit == amount {
+            e.remove_stability_deposit(&caller, &denomination);
+            pool.deposits = pool.deposits - 1;
+        } else {
+            e.set_stability_deposit(
+                &caller,
+                &denomination,
+                &new_stability_deposit(&pool, current_deposit - amount),
+            );
+            e.bump_stability_deposit(&caller, &denomination);
+        }
+
+        e.set_stability_pool(&pool);
+    }
+
+    fn stability_claim(e: Env, caller: Address, denomination: Symbol) {
+        e.bump_instance();
+        caller.require_auth();
+
+        let deposit: StabilityDeposit = e
+            .stability_deposit(&caller, &denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::StabilityDepositDoesntExist));
+
+        let core_state: CoreState = e.core_state().unwrap();
+        let mut pool: StabilityPool = e.stability_pool(&denomination).unwrap();
+        let current_deposit: u128 =
+            settle_stability_deposit(&e, &core_state, &mut pool, &caller, &deposit);
+
+        if current_deposit == 0 {
+            // The deposit was fully used by the pool, there is nothing else to keep track of
+            e.remove_stability_deposit(&caller, &denomination);
+            pool.deposits = pool.deposits - 1;
+        } else {
+            e.set_stability_deposit(
+                &caller,
+                &denomination,
+                &new_stability_deposit(&pool, current_deposit),
+            );
+            e.bump_stability_deposit(&caller, &denomination);
+        }
+
+        e.set_stability_pool(&pool);
+    }
+
+    fn get_stability_pool(e: Env, denomination: Symbol) -> StabilityPool {
+        e.bump_instance();
+        e.stability_pool(&denomination)
+            .unwrap_or_else(|| new_stability_pool(&denomination))
+    }
+
+    fn get_stability_deposit(e: Env, caller: Address, denomination: Symbol) -> StabilityDeposit {
+        e.bump_instance();
+        let deposit: StabilityDeposit = e
+            .stability_deposit(&caller, &denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::StabilityDepositDoesntExist));
+        e.bump_stability_deposit(&caller, &denomination);
+        deposit
+    }
+
+    fn get_stability_gains(e: Env, caller: Address, denomination: Symbol) -> StabilityGains {
+        e.bump_instance();
+        let deposit: StabilityDeposit = e
+            .stability_deposit(&caller, &denomination)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::StabilityDepositDoesntExist));
+        let pool: StabilityPool = e.stability_pool(&denomination).unwrap();
+
+        StabilityGains {
+            deposit: compounded_deposit(&pool, &deposit),
+            collateral: collateral_gain(&e, &pool, &deposit),
+        }
     }
 }
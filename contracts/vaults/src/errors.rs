@@ -8,13 +8,15 @@ pub enum SCErrors {
     UnexpectedError = 50,
     CoreAlreadySet = 100,
     InvalidFee = 101,
+    InvalidFeeShare = 102,
+    InvalidGasCompensation = 103,
     VaultsInfoHasNotStarted = 200,
     ThereAreNoVaults = 201,
     InvalidMinDebtAmount = 300,
     InvalidMinCollateralAmount = 310,
     InvalidOpeningCollateralRatio = 400,
     VaultDoesntExist = 500,
-    UserAlreadyHasDenominationVault = 501,
+    // 501 was `UserAlreadyHasDenominationVault`, an account can now open more than one vault per denomination
     UserVaultIndexIsInvalid = 502,
     UserVaultCantBeLiquidated = 503,
     InvalidPrevVaultIndex = 504,
@@ -26,10 +28,46 @@ pub enum SCErrors {
     NextPrevVaultShouldBeNone = 510,
     NotEnoughVaultsToLiquidate = 511,
     InvalidPrevKeyDenomination = 512,
+    NoVaultsToReceiveRedistribution = 513,
     DepositAmountIsMoreThanTotalDebt = 600,
     CollateralRateUnderMinimum = 700,
     NotEnoughFundsToRedeem = 800,
     CurrencyAlreadyAdded = 900,
     CurrencyDoesntExist = 901,
     CurrencyIsInactive = 902,
+    StabilityDepositDoesntExist = 1000,
+    InvalidStabilityDepositAmount = 1001,
+    StabilityPoolWithdrawalsLocked = 1002,
+    StabilityPoolCanAbsorbDebt = 1003,
+    InvalidAuctionConfig = 1100,
+    AuctionConfigDoesntExist = 1101,
+    AuctionDoesntExist = 1102,
+    AuctionIsExpired = 1103,
+    AuctionIsNotExpired = 1104,
+    AuctionPriceIsHigherThanMax = 1105,
+    InvalidAuctionBid = 1106,
+    FlashMintIsDisabled = 1200,
+    InvalidFlashMintAmount = 1201,
+    FlashMintNotRepaid = 1202,
+    SwapAdapterIsNotSet = 1300,
+    SwapOutputIsBelowMinimum = 1301,
+    InvalidSwapAmount = 1302,
+    OperatorIsNotApproved = 1400,
+    OperatorAllowanceExceeded = 1401,
+}
+
+// Soroban doesn't allow more than 50 cases in an error enum so the errors of the newer features are added here
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum SCErrorsExt {
+    InvalidProtectionOrder = 1500,
+    ProtectionOrderDoesntExist = 1501,
+    ProtectionIsNotTriggered = 1502,
+    InvalidVaultMerge = 1600,
+    InvalidVaultSplit = 1601,
+    InvalidTransferOffer = 1700,
+    TransferOfferDoesntExist = 1701,
+    TransferOfferIsExpired = 1702,
+    TransferTermsNotMet = 1703,
 }
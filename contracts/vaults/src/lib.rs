@@ -4,6 +4,61 @@ mod oracle {
     soroban_sdk::contractimport!(file = "../../currencies_oracle.wasm");
 }
 
+mod locking_pool {
+    use soroban_sdk::{contractclient, Address, Env};
+
+    // Only the methods the vaults contract uses from the locking pool contract
+    #[allow(dead_code)]
+    #[contractclient(name = "Client")]
+    pub trait LockingPoolInterface {
+        fn distribute(
+            e: Env,
+            caller: Address,
+            deposit_asset: Address,
+            reward_asset: Address,
+            amount: u128,
+        );
+    }
+}
+
+mod flash_mint_receiver {
+    use soroban_sdk::{contractclient, Address, Bytes, Env, Symbol};
+
+    // The callback a contract needs to implement to receive flash mints.
+    // Before returning, the receiver must send back to the vaults contract the amount plus the fee.
+    #[allow(dead_code)]
+    #[contractclient(name = "Client")]
+    pub trait FlashMintReceiverInterface {
+        fn on_flash_mint(
+            e: Env,
+            initiator: Address,
+            denomination: Symbol,
+            amount: u128,
+            fee: u128,
+            data: Bytes,
+        );
+    }
+}
+
+mod swap_adapter {
+    use soroban_sdk::{contractclient, Address, Env};
+
+    // The tokens to sell are sent to the adapter before calling `swap`, the adapter must send at least
+    // `min_amount_out` of `token_out` to `to` and return the amount it sent.
+    #[allow(dead_code)]
+    #[contractclient(name = "Client")]
+    pub trait SwapAdapterInterface {
+        fn swap(
+            e: Env,
+            token_in: Address,
+            token_out: Address,
+            amount_in: u128,
+            min_amount_out: u128,
+            to: Address,
+        ) -> u128;
+    }
+}
+
 mod contract;
 mod storage;
 mod utils;
@@ -0,0 +1,117 @@
+use crate::storage::vaults::{PERSISTENT_BUMP_CONSTANT, PERSISTENT_BUMP_CONSTANT_THRESHOLD};
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+// If a denomination has a config, the vaults the stability pool can't absorb are auctioned instead of being sold
+// to the liquidator.
+// - `premium` - Multiplier (7 decimals) applied to the oracle price to get the starting price, ex: 1_2000000 = 120%
+// - `min_price` - Multiplier (7 decimals) applied to the oracle price to get the lowest price, ex: 9000000 = 90%
+// - `duration` - Seconds it takes for the price to decay linearly to the min price, after that the auction expires
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuctionConfig {
+    pub premium: u128,
+    pub min_price: u128,
+    pub duration: u64,
+}
+
+// - `debt` - The stablecoin that still needs to be burned
+// - `collateral` - The collateral that is still for sale
+// - `start_price` - The price (same format as the oracle) of the collateral when the auction started
+// - `min_price` - The price (same format as the oracle) the auction reaches when it expires
+// - `owner` - The owner of the liquidated vault, it receives the collateral left once the debt is covered
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Auction {
+    pub id: u64,
+    pub denomination: Symbol,
+    pub owner: Address,
+    pub debt: u128,
+    pub collateral: u128,
+    pub start_price: u128,
+    pub min_price: u128,
+    pub started_at: u64,
+    pub duration: u64,
+}
+
+#[contracttype]
+pub enum AuctionsDataKeys {
+    // Symbol is the denomination, not the asset code.
+    Config(Symbol),
+
+    // The id of the last auction created
+    Counter,
+
+    Auction(u64),
+}
+
+pub trait AuctionsFunc {
+    fn auction_config(&self, denomination: &Symbol) -> Option<AuctionConfig>;
+    fn set_auction_config(&self, denomination: &Symbol, auction_config: &AuctionConfig);
+    fn remove_auction_config(&self, denomination: &Symbol);
+    fn auctions_counter(&self) -> u64;
+    fn set_auctions_counter(&self, counter: u64);
+    fn bump_auction(&self, id: u64);
+    fn auction(&self, id: u64) -> Option<Auction>;
+    fn set_auction(&self, auction: &Auction);
+    fn remove_auction(&self, id: u64);
+}
+
+impl AuctionsFunc for Env {
+    fn auction_config(&self, denomination: &Symbol) -> Option<AuctionConfig> {
+        self.storage()
+            .instance()
+            .get(&AuctionsDataKeys::Config(denomination.clone()))
+    }
+
+    fn set_auction_config(&self, denomination: &Symbol, auction_config: &AuctionConfig) {
+        self.storage().instance().set(
+            &AuctionsDataKeys::Config(denomination.clone()),
+            auction_config,
+        );
+    }
+
+    fn remove_auction_config(&self, denomination: &Symbol) {
+        self.storage()
+            .instance()
+            .remove(&AuctionsDataKeys::Config(denomination.clone()));
+    }
+
+    fn auctions_counter(&self) -> u64 {
+        self.storage()
+            .instance()
+            .get(&AuctionsDataKeys::Counter)
+            .unwrap_or(0)
+    }
+
+    fn set_auctions_counter(&self, counter: u64) {
+        self.storage()
+            .instance()
+            .set(&AuctionsDataKeys::Counter, &counter);
+    }
+
+    fn bump_auction(&self, id: u64) {
+        self.storage().persistent().extend_ttl(
+            &AuctionsDataKeys::Auction(id),
+            PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+            PERSISTENT_BUMP_CONSTANT,
+        );
+    }
+
+    fn auction(&self, id: u64) -> Option<Auction> {
+        self.storage()
+            .persistent()
+            .get(&AuctionsDataKeys::Auction(id))
+    }
+
+    fn set_auction(&self, auction: &Auction) {
+        self.storage()
+            .persistent()
+            .set(&AuctionsDataKeys::Auction(auction.id), auction);
+    }
+
+    fn remove_auction(&self, id: u64) {
+        self.storage()
+            .persistent()
+            .remove(&AuctionsDataKeys::Auction(id));
+    }
+}
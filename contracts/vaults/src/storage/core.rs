@@ -19,12 +19,18 @@ pub struct CoreState {
 #[contracttype]
 pub enum CoreDataKeys {
     CoreState,
+
+    // The contract used by `leverage` and `deleverage` to swap between the stablecoins and the collateral
+    SwapAdapter,
 }
 
 pub trait CoreFunc {
     fn set_core_state(&self, core_state: &CoreState);
     fn core_state(&self) -> Option<CoreState>;
     fn bump_instance(&self);
+    fn swap_adapter(&self) -> Option<Address>;
+    fn set_swap_adapter(&self, swap_adapter: &Address);
+    fn remove_swap_adapter(&self);
 }
 
 impl CoreFunc for Env {
@@ -44,4 +50,18 @@ impl CoreFunc for Env {
             self.ledger().sequence() + INSTANCE_BUMP_CONSTANT,
         );
     }
+
+    fn swap_adapter(&self) -> Option<Address> {
+        self.storage().instance().get(&CoreDataKeys::SwapAdapter)
+    }
+
+    fn set_swap_adapter(&self, swap_adapter: &Address) {
+        self.storage()
+            .instance()
+            .set(&CoreDataKeys::SwapAdapter, swap_adapter);
+    }
+
+    fn remove_swap_adapter(&self) {
+        self.storage().instance().remove(&CoreDataKeys::SwapAdapter);
+    }
 }
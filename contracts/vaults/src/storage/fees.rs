@@ -0,0 +1,125 @@
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+// The protocol should not charge more than 1% on regular vault operations
+pub const MAX_FEE: u128 = 100000;
+
+// Redemptions can charge up to 5% because part of it goes to the redeemed vault owner
+pub const MAX_REDEMPTION_FEE: u128 = 500000;
+
+// All the rates use 7 decimals, ex: 50000 = 0.5%
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeSchedule {
+    pub open: u128,
+    pub deposit: u128,
+    pub withdraw: u128,
+    pub close: u128,
+    pub liquidation: u128,
+    // Charged when the redeem pays the full debt of the vault or the vault is the last one in the list
+    pub redemption: u128,
+    // Charged when the redeem only pays part of the debt and there are more vaults after the redeemed one
+    pub partial_redemption: u128,
+    // If true, the opening fee is charged on the debt (on `new_vault` and `increase_debt`) and minted to the treasury
+    // instead of being taken from the collateral
+    pub stablecoin_fees: bool,
+}
+
+// Where the collateral fees are routed, `share` uses 7 decimals (ex: 5000000 = 50%)
+// The rest of the fee (or all of it if the distribution fails) is sent to the treasury
+// The locking pool side is accumulated until it reaches `min_amount`, it must be at least the min distribution of the
+// locking pool or the distributions will be rejected and sent to the treasury
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeesDistribution {
+    pub locking_pool: Address,
+    pub deposit_asset: Address,
+    pub share: u128,
+    pub min_amount: u128,
+}
+
+#[contracttype]
+pub enum FeesDataKeys {
+    // The schedule used when a denomination doesn't have its own schedule
+    Default,
+
+    // Symbol is the denomination, not the asset code.
+    Denomination(Symbol),
+
+    Distribution,
+
+    // The locking pool side of the fees that hasn't been distributed yet
+    PendingDistribution,
+}
+
+pub trait FeesFunc {
+    fn default_fee_schedule(&self) -> Option<FeeSchedule>;
+    fn set_default_fee_schedule(&self, fee_schedule: &FeeSchedule);
+    fn fee_schedule(&self, denomination: &Symbol) -> Option<FeeSchedule>;
+    fn set_fee_schedule(&self, denomination: &Symbol, fee_schedule: &FeeSchedule);
+    fn remove_fee_schedule(&self, denomination: &Symbol);
+    fn fees_distribution(&self) -> Option<FeesDistribution>;
+    fn set_fees_distribution(&self, fees_distribution: &FeesDistribution);
+    fn remove_fees_distribution(&self);
+    fn pending_distribution(&self) -> u128;
+    fn set_pending_distribution(&self, amount: &u128);
+}
+
+impl FeesFunc for Env {
+    fn default_fee_schedule(&self) -> Option<FeeSchedule> {
+        self.storage().instance().get(&FeesDataKeys::Default)
+    }
+
+    fn set_default_fee_schedule(&self, fee_schedule: &FeeSchedule) {
+        self.storage()
+            .instance()
+            .set(&FeesDataKeys::Default, fee_schedule);
+    }
+
+    fn fee_schedule(&self, denomination: &Symbol) -> Option<FeeSchedule> {
+        self.storage()
+            .instance()
+            .get(&FeesDataKeys::Denomination(denomination.clone()))
+    }
+
+    fn set_fee_schedule(&self, denomination: &Symbol, fee_schedule: &FeeSchedule) {
+        self.storage().instance().set(
+            &FeesDataKeys::Denomination(denomination.clone()),
+            fee_schedule,
+        );
+    }
+
+    fn remove_fee_schedule(&self, denomination: &Symbol) {
+        self.storage()
+            .instance()
+            .remove(&FeesDataKeys::Denomination(denomination.clone()));
+    }
+
+    fn fees_distribution(&self) -> Option<FeesDistribution> {
+        self.storage().instance().get(&FeesDataKeys::Distribution)
+    }
+
+    fn set_fees_distribution(&self, fees_distribution: &FeesDistribution) {
+        self.storage()
+            .instance()
+            .set(&FeesDataKeys::Distribution, fees_distribution);
+    }
+
+    fn remove_fees_distribution(&self) {
+        self.storage()
+            .instance()
+            .remove(&FeesDataKeys::Distribution);
+    }
+
+    fn pending_distribution(&self) -> u128 {
+        self.storage()
+            .instance()
+            .get(&FeesDataKeys::PendingDistribution)
+            .unwrap_or(0)
+    }
+
+    fn set_pending_distribution(&self, amount: &u128) {
+        self.storage()
+            .instance()
+            .set(&FeesDataKeys::PendingDistribution, amount);
+    }
+}
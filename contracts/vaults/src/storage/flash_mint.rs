@@ -0,0 +1,45 @@
+use soroban_sdk::{contracttype, Env, Symbol};
+
+// Flash mints are only allowed for the denominations with a config
+// - `max_amount` - The max amount of stablecoins that can be minted in a single flash mint
+// - `fee` - Uses 7 decimals like the other fees, ex: 5000 = 0.05%
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlashMintConfig {
+    pub max_amount: u128,
+    pub fee: u128,
+}
+
+#[contracttype]
+pub enum FlashMintDataKeys {
+    // Symbol is the denomination, not the asset code.
+    // Not named `Config` because it would be the same instance key as `AuctionsDataKeys::Config`
+    FlashMint(Symbol),
+}
+
+pub trait FlashMintFunc {
+    fn flash_mint_config(&self, denomination: &Symbol) -> Option<FlashMintConfig>;
+    fn set_flash_mint_config(&self, denomination: &Symbol, flash_mint_config: &FlashMintConfig);
+    fn remove_flash_mint_config(&self, denomination: &Symbol);
+}
+
+impl FlashMintFunc for Env {
+    fn flash_mint_config(&self, denomination: &Symbol) -> Option<FlashMintConfig> {
+        self.storage()
+            .instance()
+            .get(&FlashMintDataKeys::FlashMint(denomination.clone()))
+    }
+
+    fn set_flash_mint_config(&self, denomination: &Symbol, flash_mint_config: &FlashMintConfig) {
+        self.storage().instance().set(
+            &FlashMintDataKeys::FlashMint(denomination.clone()),
+            flash_mint_config,
+        );
+    }
+
+    fn remove_flash_mint_config(&self, denomination: &Symbol) {
+        self.storage()
+            .instance()
+            .remove(&FlashMintDataKeys::FlashMint(denomination.clone()));
+    }
+}
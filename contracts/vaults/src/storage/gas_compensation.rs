@@ -0,0 +1,99 @@
+use crate::storage::vaults::{
+    VaultKey, PERSISTENT_BUMP_CONSTANT, PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+};
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+// A fixed amount of `asset` (usually the native token) taken from the owner when a vault is created.
+// The same struct is used for the config and for the reserve of each vault, so changing the config doesn't
+// affect the vaults already created.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GasCompensation {
+    pub asset: Address,
+    pub amount: u128,
+}
+
+#[contracttype]
+pub enum GasCompensationDataKeys {
+    Config,
+
+    // This tuple is the owner, the currency symbol and the id of the vault
+    Reserve((Address, Symbol, u32)),
+}
+
+pub trait GasCompensationFunc {
+    fn gas_compensation(&self) -> Option<GasCompensation>;
+    fn set_gas_compensation(&self, gas_compensation: &GasCompensation);
+    fn remove_gas_compensation(&self);
+    fn bump_gas_reserve(&self, vault_key: &VaultKey);
+    fn gas_reserve(&self, vault_key: &VaultKey) -> Option<GasCompensation>;
+    fn set_gas_reserve(&self, vault_key: &VaultKey, gas_reserve: &GasCompensation);
+    fn remove_gas_reserve(&self, vault_key: &VaultKey);
+}
+
+impl GasCompensationFunc for Env {
+    fn gas_compensation(&self) -> Option<GasCompensation> {
+        self.storage()
+            .instance()
+            .get(&GasCompensationDataKeys::Config)
+    }
+
+    fn set_gas_compensation(&self, gas_compensation: &GasCompensation) {
+        self.storage()
+            .instance()
+            .set(&GasCompensationDataKeys::Config, gas_compensation);
+    }
+
+    fn remove_gas_compensation(&self) {
+        self.storage()
+            .instance()
+            .remove(&GasCompensationDataKeys::Config);
+    }
+
+    fn bump_gas_reserve(&self, vault_key: &VaultKey) {
+        let key = GasCompensationDataKeys::Reserve((
+            vault_key.account.clone(),
+            vault_key.denomination.clone(),
+            vault_key.id,
+        ));
+
+        if self.storage().persistent().has(&key) {
+            self.storage().persistent().extend_ttl(
+                &key,
+                PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+                PERSISTENT_BUMP_CONSTANT,
+            );
+        }
+    }
+
+    fn gas_reserve(&self, vault_key: &VaultKey) -> Option<GasCompensation> {
+        self.storage()
+            .persistent()
+            .get(&GasCompensationDataKeys::Reserve((
+                vault_key.account.clone(),
+                vault_key.denomination.clone(),
+                vault_key.id,
+            )))
+    }
+
+    fn set_gas_reserve(&self, vault_key: &VaultKey, gas_reserve: &GasCompensation) {
+        self.storage().persistent().set(
+            &GasCompensationDataKeys::Reserve((
+                vault_key.account.clone(),
+                vault_key.denomination.clone(),
+                vault_key.id,
+            )),
+            gas_reserve,
+        );
+    }
+
+    fn remove_gas_reserve(&self, vault_key: &VaultKey) {
+        self.storage()
+            .persistent()
+            .remove(&GasCompensationDataKeys::Reserve((
+                vault_key.account.clone(),
+                vault_key.denomination.clone(),
+                vault_key.id,
+            )));
+    }
+}
@@ -1,3 +1,11 @@
+pub mod auctions;
 pub mod core;
 pub mod currencies;
+pub mod fees;
+pub mod flash_mint;
+pub mod gas_compensation;
+pub mod operators;
+pub mod protections;
+pub mod stability_pool;
+pub mod transfers;
 pub mod vaults;
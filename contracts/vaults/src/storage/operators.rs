@@ -0,0 +1,130 @@
+use crate::storage::vaults::{PERSISTENT_BUMP_CONSTANT, PERSISTENT_BUMP_CONSTANT_THRESHOLD};
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+// What an operator can do with a vault of the owner that approved it until `expiration` (a ledger timestamp).
+// The approval covers a single vault, an operator approved for one vault of the owner can't use another one.
+// - `deposit` - Can add collateral to the vault, paid by the operator
+// - `repay` - Can pay the debt of the vault, paid by the operator
+// - `withdraw_allowance` - Collateral the operator can still withdraw, it's always sent to the owner
+// - `mint_allowance` - Debt the operator can still take, the stablecoins are always sent to the owner
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct OperatorApproval {
+    pub deposit: bool,
+    pub repay: bool,
+    pub withdraw_allowance: u128,
+    pub mint_allowance: u128,
+    pub expiration: u64,
+}
+
+#[contracttype]
+pub enum OperatorsDataKeys {
+    // This tuple is the owner, the currency symbol of the vault, the id of the vault and the operator
+    Approval((Address, Symbol, u32, Address)),
+}
+
+pub trait OperatorsFunc {
+    fn bump_operator_approval(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        operator: &Address,
+    );
+    fn operator_approval(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        operator: &Address,
+    ) -> Option<OperatorApproval>;
+    fn set_operator_approval(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        operator: &Address,
+        approval: &OperatorApproval,
+    );
+    fn remove_operator_approval(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        operator: &Address,
+    );
+}
+
+impl OperatorsFunc for Env {
+    fn bump_operator_approval(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        operator: &Address,
+    ) {
+        self.storage().persistent().extend_ttl(
+            &OperatorsDataKeys::Approval((
+                owner.clone(),
+                denomination.clone(),
+                id,
+                operator.clone(),
+            )),
+            PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+            PERSISTENT_BUMP_CONSTANT,
+        );
+    }
+
+    fn operator_approval(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        operator: &Address,
+    ) -> Option<OperatorApproval> {
+        self.storage()
+            .persistent()
+            .get(&OperatorsDataKeys::Approval((
+                owner.clone(),
+                denomination.clone(),
+                id,
+                operator.clone(),
+            )))
+    }
+
+    fn set_operator_approval(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        operator: &Address,
+        approval: &OperatorApproval,
+    ) {
+        self.storage().persistent().set(
+            &OperatorsDataKeys::Approval((
+                owner.clone(),
+                denomination.clone(),
+                id,
+                operator.clone(),
+            )),
+            approval,
+        );
+    }
+
+    fn remove_operator_approval(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        operator: &Address,
+    ) {
+        self.storage()
+            .persistent()
+            .remove(&OperatorsDataKeys::Approval((
+                owner.clone(),
+                denomination.clone(),
+                id,
+                operator.clone(),
+            )));
+    }
+}
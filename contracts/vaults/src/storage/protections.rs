@@ -0,0 +1,89 @@
+use crate::storage::vaults::{PERSISTENT_BUMP_CONSTANT, PERSISTENT_BUMP_CONSTANT_THRESHOLD};
+use soroban_sdk::{contracttype, vec, Address, Env, Symbol, Vec};
+
+pub const MAX_PROTECTION_ORDERS: u32 = 5;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProtectionAction {
+    // Pays debt of the vault with the escrowed stablecoins
+    Repay,
+
+    // Deposits the escrowed collateral in the vault
+    AddCollateral,
+}
+
+// A protective action the owner registers for its vault, the `amount` is escrowed in this contract when the order is
+// created (stablecoins for `Repay` and collateral for `AddCollateral`).
+// - `trigger_rate` - Any keeper can execute the order once the collateral ratio (7 decimals) of the vault is below this value
+// - `tip` - Part of the `amount` paid to the keeper that executes the order
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProtectionOrder {
+    pub action: ProtectionAction,
+    pub trigger_rate: u128,
+    pub amount: u128,
+    pub tip: u128,
+}
+
+#[contracttype]
+pub enum ProtectionDataKeys {
+    // This tuple is the owner, the currency symbol and the id of the vault
+    Orders((Address, Symbol, u32)),
+}
+
+pub trait ProtectionFunc {
+    fn protection_orders(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+    ) -> Vec<ProtectionOrder>;
+    fn set_protection_orders(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        orders: &Vec<ProtectionOrder>,
+    );
+}
+
+impl ProtectionFunc for Env {
+    fn protection_orders(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+    ) -> Vec<ProtectionOrder> {
+        self.storage()
+            .persistent()
+            .get(&ProtectionDataKeys::Orders((
+                owner.clone(),
+                denomination.clone(),
+                id,
+            )))
+            .unwrap_or(vec![&self])
+    }
+
+    // If there are no orders left we remove the record instead of saving an empty list
+    fn set_protection_orders(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        orders: &Vec<ProtectionOrder>,
+    ) {
+        let key = ProtectionDataKeys::Orders((owner.clone(), denomination.clone(), id));
+
+        if orders.is_empty() {
+            self.storage().persistent().remove(&key);
+        } else {
+            self.storage().persistent().set(&key, orders);
+            self.storage().persistent().extend_ttl(
+                &key,
+                PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+                PERSISTENT_BUMP_CONSTANT,
+            );
+        }
+    }
+}
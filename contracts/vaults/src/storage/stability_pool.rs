@@ -0,0 +1,155 @@
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+pub const DAY_IN_LEDGERS: u32 = 17280;
+pub const PERSISTENT_BUMP_CONSTANT: u32 = DAY_IN_LEDGERS * 28;
+pub const PERSISTENT_BUMP_CONSTANT_THRESHOLD: u32 = DAY_IN_LEDGERS * 14;
+
+// Precision used by the product accumulator
+pub const DECIMAL_PRECISION: u128 = 1_000000000_000000000;
+
+// When the product goes under this value, it's multiplied by it and the scale is increased
+pub const SCALE_FACTOR: u128 = 1_000000000;
+
+// The pool follows the product/sum approach:
+// - `product` tracks how much of a deposit is left after the pool absorbed debt (starts at DECIMAL_PRECISION)
+// - `sum` tracks the collateral earned per unit of deposit, weighted by the product at the moment of the liquidation
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StabilityPool {
+    pub denomination: Symbol,
+    // Stablecoins currently held by the pool
+    pub total_deposits: u128,
+    // Collateral earned by the depositors that hasn't been claimed yet
+    pub total_collateral: u128,
+    pub deposits: u64,
+    pub product: u128,
+    pub sum: u128,
+    pub scale: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StabilityDeposit {
+    // The deposit amount at the moment of the last update, the current value is calculated with the snapshots
+    pub amount: u128,
+    pub product_snapshot: u128,
+    pub sum_snapshot: u128,
+    pub scale_snapshot: u32,
+}
+
+// Current state of a deposit, used by the views so integrators don't need to do the math
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StabilityGains {
+    pub deposit: u128,
+    pub collateral: u128,
+}
+
+#[contracttype]
+pub enum StabilityPoolDataKeys {
+    // Symbol is the denomination, not the asset code.
+    Pool(Symbol),
+
+    // This tuple is the depositor and the currency symbol
+    Deposit((Address, Symbol)),
+
+    // The final value of the sum for a scale that has been already closed
+    ScaleSum((Symbol, u32)),
+}
+
+pub trait StabilityPoolFunc {
+    fn stability_pool(&self, denomination: &Symbol) -> Option<StabilityPool>;
+    fn set_stability_pool(&self, stability_pool: &StabilityPool);
+    fn stability_deposit(
+        &self,
+        depositor: &Address,
+        denomination: &Symbol,
+    ) -> Option<StabilityDeposit>;
+    fn set_stability_deposit(
+        &self,
+        depositor: &Address,
+        denomination: &Symbol,
+        deposit: &StabilityDeposit,
+    );
+    fn remove_stability_deposit(&self, depositor: &Address, denomination: &Symbol);
+    fn bump_stability_deposit(&self, depositor: &Address, denomination: &Symbol);
+    fn scale_sum(&self, denomination: &Symbol, scale: u32) -> u128;
+    fn set_scale_sum(&self, denomination: &Symbol, scale: u32, sum: u128);
+}
+
+impl StabilityPoolFunc for Env {
+    fn stability_pool(&self, denomination: &Symbol) -> Option<StabilityPool> {
+        self.storage()
+            .instance()
+            .get(&StabilityPoolDataKeys::Pool(denomination.clone()))
+    }
+
+    fn set_stability_pool(&self, stability_pool: &StabilityPool) {
+        self.storage().instance().set(
+            &StabilityPoolDataKeys::Pool(stability_pool.denomination.clone()),
+            stability_pool,
+        );
+    }
+
+    fn stability_deposit(
+        &self,
+        depositor: &Address,
+        denomination: &Symbol,
+    ) -> Option<StabilityDeposit> {
+        self.storage()
+            .persistent()
+            .get(&StabilityPoolDataKeys::Deposit((
+                depositor.clone(),
+                denomination.clone(),
+            )))
+    }
+
+    fn set_stability_deposit(
+        &self,
+        depositor: &Address,
+        denomination: &Symbol,
+        deposit: &StabilityDeposit,
+    ) {
+        self.storage().persistent().set(
+            &StabilityPoolDataKeys::Deposit((depositor.clone(), denomination.clone())),
+            deposit,
+        );
+    }
+
+    fn remove_stability_deposit(&self, depositor: &Address, denomination: &Symbol) {
+        self.storage()
+            .persistent()
+            .remove(&StabilityPoolDataKeys::Deposit((
+                depositor.clone(),
+                denomination.clone(),
+            )));
+    }
+
+    fn bump_stability_deposit(&self, depositor: &Address, denomination: &Symbol) {
+        self.storage().persistent().extend_ttl(
+            &StabilityPoolDataKeys::Deposit((depositor.clone(), denomination.clone())),
+            PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+            PERSISTENT_BUMP_CONSTANT,
+        );
+    }
+
+    fn scale_sum(&self, denomination: &Symbol, scale: u32) -> u128 {
+        self.storage()
+            .persistent()
+            .get(&StabilityPoolDataKeys::ScaleSum((
+                denomination.clone(),
+                scale,
+            )))
+            .unwrap_or(0)
+    }
+
+    fn set_scale_sum(&self, denomination: &Symbol, scale: u32, sum: u128) {
+        let key = StabilityPoolDataKeys::ScaleSum((denomination.clone(), scale));
+        self.storage().persistent().set(&key, &sum);
+        self.storage().persistent().extend_ttl(
+            &key,
+            PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+            PERSISTENT_BUMP_CONSTANT,
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use crate::storage::vaults::{PERSISTENT_BUMP_CONSTANT, PERSISTENT_BUMP_CONSTANT_THRESHOLD};
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+// An offer from the owner of a vault to transfer it to `recipient`, it can be accepted until `expiration` (a ledger timestamp).
+// If the `price` is more than 0 the recipient pays it in `asset` to the owner when accepting the offer.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransferOffer {
+    pub recipient: Address,
+    pub asset: Address,
+    pub price: u128,
+    pub expiration: u64,
+}
+
+#[contracttype]
+pub enum TransfersDataKeys {
+    // This tuple is the owner, the currency symbol and the id of the vault
+    Offer((Address, Symbol, u32)),
+}
+
+pub trait TransfersFunc {
+    fn bump_transfer_offer(&self, owner: &Address, denomination: &Symbol, id: u32);
+    fn transfer_offer(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+    ) -> Option<TransferOffer>;
+    fn set_transfer_offer(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        offer: &TransferOffer,
+    );
+    fn remove_transfer_offer(&self, owner: &Address, denomination: &Symbol, id: u32);
+}
+
+impl TransfersFunc for Env {
+    fn bump_transfer_offer(&self, owner: &Address, denomination: &Symbol, id: u32) {
+        self.storage().persistent().extend_ttl(
+            &TransfersDataKeys::Offer((owner.clone(), denomination.clone(), id)),
+            PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+            PERSISTENT_BUMP_CONSTANT,
+        );
+    }
+
+    fn transfer_offer(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+    ) -> Option<TransferOffer> {
+        self.storage().persistent().get(&TransfersDataKeys::Offer((
+            owner.clone(),
+            denomination.clone(),
+            id,
+        )))
+    }
+
+    fn set_transfer_offer(
+        &self,
+        owner: &Address,
+        denomination: &Symbol,
+        id: u32,
+        offer: &TransferOffer,
+    ) {
+        self.storage().persistent().set(
+            &TransfersDataKeys::Offer((owner.clone(), denomination.clone(), id)),
+            offer,
+        );
+    }
+
+    fn remove_transfer_offer(&self, owner: &Address, denomination: &Symbol, id: u32) {
+        self.storage()
+            .persistent()
+            .remove(&TransfersDataKeys::Offer((
+                owner.clone(),
+                denomination.clone(),
+                id,
+            )));
+    }
+}
@@ -1,9 +1,13 @@
-use soroban_sdk::{contracttype, Address, Env, Symbol};
+use crate::storage::gas_compensation::GasCompensationFunc;
+use soroban_sdk::{contracttype, vec, Address, Env, FromVal, Map, Symbol, Val, Vec};
 
 pub const DAY_IN_LEDGERS: u32 = 17280;
 pub const PERSISTENT_BUMP_CONSTANT: u32 = DAY_IN_LEDGERS * 28;
 pub const PERSISTENT_BUMP_CONSTANT_THRESHOLD: u32 = DAY_IN_LEDGERS * 14;
 
+// Precision used by the redistribution accumulators
+pub const REDISTRIBUTION_PRECISION: u128 = 1_000000000;
+
 #[contracttype]
 #[derive(Clone, PartialEq, Debug)]
 pub enum OptionalVaultKey {
@@ -11,6 +15,14 @@ pub enum OptionalVaultKey {
     Some(VaultKey),
 }
 
+// A vault to liquidate with the key of the vault that comes BEFORE it (None if it's the lowest)
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiquidationTarget {
+    pub prev_key: OptionalVaultKey,
+    pub vault_key: VaultKey,
+}
+
 #[contracttype]
 #[derive(Debug, Clone)]
 pub struct VaultsInfo {
@@ -32,6 +44,7 @@ pub struct VaultKey {
     pub index: u128,
     pub account: Address,
     pub denomination: Symbol,
+    pub id: u32,
 }
 
 #[contracttype]
@@ -43,6 +56,19 @@ pub struct Vault {
     pub total_debt: u128,
     pub total_collateral: u128,
     pub denomination: Symbol,
+    pub id: u32,
+}
+
+// Global accumulators used to share the debt and collateral of insolvent vaults between the remaining vaults.
+// Each vault saves a snapshot of these values when it's inserted, the difference between the current
+// values and the snapshot is what the vault still needs to receive the next time it's touched.
+// - `debt_factor` - Multiplier applied to the debt of the vaults, it starts at REDISTRIBUTION_PRECISION
+// - `collateral_factor` - Collateral received per unit of debt, it starts at 0
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Redistribution {
+    pub debt_factor: u128,
+    pub collateral_factor: u128,
 }
 
 #[contracttype]
@@ -50,6 +76,114 @@ pub struct Vault {
 pub struct VaultIndexKey {
     pub user: Address,
     pub denomination: Symbol,
+    pub id: u32,
+}
+
+// An account can have multiple vaults of the same denomination, each one is identified by an id.
+// - `next_id` - The id the next vault of the account will have, ids are never reused
+// - `ids` - The ids of the vaults the account currently has
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountVaults {
+    pub next_id: u32,
+    pub ids: Vec<u32>,
+}
+
+// The layouts used before vaults had an id. An account could only have one vault per denomination so those vaults
+// are read as the vault with id 0, and they are saved with the current layout and keys the next time they are updated.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum LegacyOptionalVaultKey {
+    None,
+    Some(LegacyVaultKey),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LegacyVaultKey {
+    pub index: u128,
+    pub account: Address,
+    pub denomination: Symbol,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct LegacyVaultsInfo {
+    pub denomination: Symbol,
+    pub total_vaults: u64,
+    pub total_debt: u128,
+    pub total_col: u128,
+    pub lowest_key: LegacyOptionalVaultKey,
+    pub min_col_rate: u128,
+    pub min_debt_creation: u128,
+    pub opening_col_rate: u128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LegacyVault {
+    pub index: u128,
+    pub next_key: LegacyOptionalVaultKey,
+    pub account: Address,
+    pub total_debt: u128,
+    pub total_collateral: u128,
+    pub denomination: Symbol,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct LegacyVaultIndexKey {
+    pub user: Address,
+    pub denomination: Symbol,
+}
+
+#[contracttype]
+pub enum LegacyVaultsDataKeys {
+    Vault((Address, Symbol)),
+    VaultIndex(LegacyVaultIndexKey),
+}
+
+impl OptionalVaultKey {
+    pub fn from_legacy(legacy: LegacyOptionalVaultKey) -> Self {
+        match legacy {
+            LegacyOptionalVaultKey::None => OptionalVaultKey::None,
+            LegacyOptionalVaultKey::Some(key) => OptionalVaultKey::Some(VaultKey {
+                index: key.index,
+                account: key.account,
+                denomination: key.denomination,
+                id: 0,
+            }),
+        }
+    }
+}
+
+impl VaultsInfo {
+    pub fn from_legacy(legacy: LegacyVaultsInfo) -> Self {
+        VaultsInfo {
+            denomination: legacy.denomination,
+            total_vaults: legacy.total_vaults,
+            total_debt: legacy.total_debt,
+            total_col: legacy.total_col,
+            lowest_key: OptionalVaultKey::from_legacy(legacy.lowest_key),
+            min_col_rate: legacy.min_col_rate,
+            min_debt_creation: legacy.min_debt_creation,
+            opening_col_rate: legacy.opening_col_rate,
+        }
+    }
+}
+
+impl Vault {
+    pub fn from_legacy(legacy: LegacyVault) -> Self {
+        Vault {
+            index: legacy.index,
+            next_key: OptionalVaultKey::from_legacy(legacy.next_key),
+            account: legacy.account,
+            total_debt: legacy.total_debt,
+            total_collateral: legacy.total_collateral,
+            denomination: legacy.denomination,
+            id: 0,
+        }
+    }
 }
 
 #[contracttype]
@@ -58,12 +192,21 @@ pub enum VaultsDataKeys {
     // Symbol is the denomination, not the asset code.
     VaultsInfo(Symbol),
 
-    // This tuple is the owner and the currency symbol
-    Vault((Address, Symbol)),
+    // This tuple is the owner, the currency symbol and the id of the vault
+    Vault((Address, Symbol, u32)),
 
     // By using the combination of the denomination and the address (VaultIndexKey) we can get
     // the index of the vault so the user doesn't need to know the index of its own vault at all time
     VaultIndex(VaultIndexKey),
+
+    // Symbol is the denomination, not the asset code.
+    Redistribution(Symbol),
+
+    // The Redistribution values at the moment the vault was saved, the tuple is the owner, the currency symbol and the id of the vault
+    RedistributionSnapshot((Address, Symbol, u32)),
+
+    // This tuple is the owner and the currency symbol
+    AccountVaults((Address, Symbol)),
 }
 
 pub trait VaultsFunc {
@@ -77,29 +220,101 @@ pub trait VaultsFunc {
     fn set_vault_index(&self, vault_key: &VaultKey);
     fn remove_vault_index(&self, vault_index_key: &VaultIndexKey);
     fn vault_index(&self, vault_index_key: &VaultIndexKey) -> Option<u128>;
+    fn redistribution(&self, denomination: &Symbol) -> Option<Redistribution>;
+    fn set_redistribution(&self, denomination: &Symbol, redistribution: &Redistribution);
+    fn redistribution_snapshot(&self, vault_key: &VaultKey) -> Option<Redistribution>;
+    fn set_redistribution_snapshot(&self, vault_key: &VaultKey, redistribution: &Redistribution);
+    fn remove_redistribution_snapshot(&self, vault_key: &VaultKey);
+    fn account_vaults(&self, account: &Address, denomination: &Symbol) -> AccountVaults;
+    fn set_account_vaults(
+        &self,
+        account: &Address,
+        denomination: &Symbol,
+        account_vaults: &AccountVaults,
+    );
 }
 
 impl VaultsFunc for Env {
     fn bump_vault(&self, vault_key: &VaultKey) {
-        self.storage().persistent().extend_ttl(
-            &VaultsDataKeys::Vault((vault_key.account.clone(), vault_key.denomination.clone())),
-            PERSISTENT_BUMP_CONSTANT_THRESHOLD,
-            PERSISTENT_BUMP_CONSTANT,
-        );
+        let key = VaultsDataKeys::Vault((
+            vault_key.account.clone(),
+            vault_key.denomination.clone(),
+            vault_key.id,
+        ));
+
+        if vault_key.id == 0 && !self.storage().persistent().has(&key) {
+            self.storage().persistent().extend_ttl(
+                &LegacyVaultsDataKeys::Vault((
+                    vault_key.account.clone(),
+                    vault_key.denomination.clone(),
+                )),
+                PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+                PERSISTENT_BUMP_CONSTANT,
+            );
+        } else {
+            self.storage().persistent().extend_ttl(
+                &key,
+                PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+                PERSISTENT_BUMP_CONSTANT,
+            );
+        }
+
+        let snapshot_key = VaultsDataKeys::RedistributionSnapshot((
+            vault_key.account.clone(),
+            vault_key.denomination.clone(),
+            vault_key.id,
+        ));
+        if self.storage().persistent().has(&snapshot_key) {
+            self.storage().persistent().extend_ttl(
+                &snapshot_key,
+                PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+                PERSISTENT_BUMP_CONSTANT,
+            );
+        }
+
+        self.bump_gas_reserve(vault_key);
     }
 
     fn bump_vault_index(&self, vault_index_key: &VaultIndexKey) {
-        self.storage().persistent().extend_ttl(
-            &VaultsDataKeys::VaultIndex(vault_index_key.clone()),
-            PERSISTENT_BUMP_CONSTANT_THRESHOLD,
-            PERSISTENT_BUMP_CONSTANT,
-        );
+        let key = VaultsDataKeys::VaultIndex(vault_index_key.clone());
+
+        if vault_index_key.id == 0 && !self.storage().persistent().has(&key) {
+            self.storage().persistent().extend_ttl(
+                &LegacyVaultsDataKeys::VaultIndex(LegacyVaultIndexKey {
+                    user: vault_index_key.user.clone(),
+                    denomination: vault_index_key.denomination.clone(),
+                }),
+                PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+                PERSISTENT_BUMP_CONSTANT,
+            );
+        } else {
+            self.storage().persistent().extend_ttl(
+                &key,
+                PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+                PERSISTENT_BUMP_CONSTANT,
+            );
+        }
     }
 
     fn vaults_info(&self, denomination: &Symbol) -> Option<VaultsInfo> {
-        self.storage()
-            .instance()
-            .get(&VaultsDataKeys::VaultsInfo(denomination.clone()))
+        let key = VaultsDataKeys::VaultsInfo(denomination.clone());
+        let stored: Map<Symbol, Val> = self.storage().instance().get(&key)?;
+
+        // A struct with different fields can't be decoded, so we check if the lowest key has an id before reading it
+        let lowest_key: Vec<Val> =
+            Vec::from_val(self, &stored.get(Symbol::new(self, "lowest_key")).unwrap());
+        let is_legacy: bool = match lowest_key.get(1) {
+            None => false,
+            Some(vault_key) => !Map::<Symbol, Val>::from_val(self, &vault_key)
+                .contains_key(Symbol::new(self, "id")),
+        };
+
+        if is_legacy {
+            let legacy: LegacyVaultsInfo = self.storage().instance().get(&key).unwrap();
+            Some(VaultsInfo::from_legacy(legacy))
+        } else {
+            self.storage().instance().get(&key)
+        }
     }
 
     fn set_vaults_info(&self, vaults_info: &VaultsInfo) {
@@ -110,24 +325,56 @@ impl VaultsFunc for Env {
     }
 
     fn vault(&self, vault_key: &VaultKey) -> Option<Vault> {
-        self.storage().persistent().get(&VaultsDataKeys::Vault((
+        let vault: Option<Vault> = self.storage().persistent().get(&VaultsDataKeys::Vault((
             vault_key.account.clone(),
             vault_key.denomination.clone(),
-        )))
+            vault_key.id,
+        )));
+
+        if vault.is_some() || vault_key.id != 0 {
+            return vault;
+        }
+
+        self.storage()
+            .persistent()
+            .get(&LegacyVaultsDataKeys::Vault((
+                vault_key.account.clone(),
+                vault_key.denomination.clone(),
+            )))
+            .map(|legacy: LegacyVault| Vault::from_legacy(legacy))
     }
 
     fn set_vault(&self, vault: &Vault) {
         self.storage().persistent().set(
-            &VaultsDataKeys::Vault((vault.account.clone(), vault.denomination.clone())),
+            &VaultsDataKeys::Vault((vault.account.clone(), vault.denomination.clone(), vault.id)),
             vault,
         );
+
+        if vault.id == 0 {
+            self.storage()
+                .persistent()
+                .remove(&LegacyVaultsDataKeys::Vault((
+                    vault.account.clone(),
+                    vault.denomination.clone(),
+                )));
+        }
     }
 
     fn remove_vault(&self, vault_key: &VaultKey) {
         self.storage().persistent().remove(&VaultsDataKeys::Vault((
             vault_key.account.clone(),
             vault_key.denomination.clone(),
+            vault_key.id,
         )));
+
+        if vault_key.id == 0 {
+            self.storage()
+                .persistent()
+                .remove(&LegacyVaultsDataKeys::Vault((
+                    vault_key.account.clone(),
+                    vault_key.denomination.clone(),
+                )));
+        }
     }
 
     fn set_vault_index(&self, vault_key: &VaultKey) {
@@ -135,20 +382,144 @@ impl VaultsFunc for Env {
             &VaultsDataKeys::VaultIndex(VaultIndexKey {
                 user: vault_key.account.clone(),
                 denomination: vault_key.denomination.clone(),
+                id: vault_key.id,
             }),
             &vault_key.index,
         );
+
+        if vault_key.id == 0 {
+            self.storage()
+                .persistent()
+                .remove(&LegacyVaultsDataKeys::VaultIndex(LegacyVaultIndexKey {
+                    user: vault_key.account.clone(),
+                    denomination: vault_key.denomination.clone(),
+                }));
+        }
     }
 
     fn remove_vault_index(&self, vault_index_key: &VaultIndexKey) {
         self.storage()
             .persistent()
             .remove(&VaultsDataKeys::VaultIndex(vault_index_key.clone()));
+
+        if vault_index_key.id == 0 {
+            self.storage()
+                .persistent()
+                .remove(&LegacyVaultsDataKeys::VaultIndex(LegacyVaultIndexKey {
+                    user: vault_index_key.user.clone(),
+                    denomination: vault_index_key.denomination.clone(),
+                }));
+        }
     }
 
     fn vault_index(&self, vault_index_key: &VaultIndexKey) -> Option<u128> {
+        let index: Option<u128> = self
+            .storage()
+            .persistent()
+            .get(&VaultsDataKeys::VaultIndex(vault_index_key.clone()));
+
+        if index.is_some() || vault_index_key.id != 0 {
+            return index;
+        }
+
         self.storage()
             .persistent()
-            .get(&VaultsDataKeys::VaultIndex(vault_index_key.clone()))
+            .get(&LegacyVaultsDataKeys::VaultIndex(LegacyVaultIndexKey {
+                user: vault_index_key.user.clone(),
+                denomination: vault_index_key.denomination.clone(),
+            }))
+    }
+
+    fn redistribution(&self, denomination: &Symbol) -> Option<Redistribution> {
+        self.storage()
+            .instance()
+            .get(&VaultsDataKeys::Redistribution(denomination.clone()))
+    }
+
+    fn set_redistribution(&self, denomination: &Symbol, redistribution: &Redistribution) {
+        self.storage().instance().set(
+            &VaultsDataKeys::Redistribution(denomination.clone()),
+            redistribution,
+        );
+    }
+
+    fn redistribution_snapshot(&self, vault_key: &VaultKey) -> Option<Redistribution> {
+        self.storage()
+            .persistent()
+            .get(&VaultsDataKeys::RedistributionSnapshot((
+                vault_key.account.clone(),
+                vault_key.denomination.clone(),
+                vault_key.id,
+            )))
+    }
+
+    fn set_redistribution_snapshot(&self, vault_key: &VaultKey, redistribution: &Redistribution) {
+        self.storage().persistent().set(
+            &VaultsDataKeys::RedistributionSnapshot((
+                vault_key.account.clone(),
+                vault_key.denomination.clone(),
+                vault_key.id,
+            )),
+            redistribution,
+        );
+    }
+
+    fn remove_redistribution_snapshot(&self, vault_key: &VaultKey) {
+        self.storage()
+            .persistent()
+            .remove(&VaultsDataKeys::RedistributionSnapshot((
+                vault_key.account.clone(),
+                vault_key.denomination.clone(),
+                vault_key.id,
+            )));
+    }
+
+    fn account_vaults(&self, account: &Address, denomination: &Symbol) -> AccountVaults {
+        if let Some(account_vaults) =
+            self.storage()
+                .persistent()
+                .get(&VaultsDataKeys::AccountVaults((
+                    account.clone(),
+                    denomination.clone(),
+                )))
+        {
+            return account_vaults;
+        }
+
+        // Only the vaults created before the ids existed don't have this list, they are always the vault 0
+        let has_legacy_vault: bool = self.storage().persistent().has(&VaultsDataKeys::Vault((
+            account.clone(),
+            denomination.clone(),
+            0,
+        ))) || self.storage().persistent().has(
+            &LegacyVaultsDataKeys::Vault((account.clone(), denomination.clone())),
+        );
+
+        if has_legacy_vault {
+            AccountVaults {
+                next_id: 1,
+                ids: vec![&self, 0],
+            }
+        } else {
+            AccountVaults {
+                next_id: 0,
+                ids: vec![&self],
+            }
+        }
+    }
+
+    fn set_account_vaults(
+        &self,
+        account: &Address,
+        denomination: &Symbol,
+        account_vaults: &AccountVaults,
+    ) {
+        let key = VaultsDataKeys::AccountVaults((account.clone(), denomination.clone()));
+        self.storage().persistent().set(&key, account_vaults);
+        self.storage().persistent().extend_ttl(
+            &key,
+            PERSISTENT_BUMP_CONSTANT_THRESHOLD,
+            PERSISTENT_BUMP_CONSTANT,
+        );
     }
 }
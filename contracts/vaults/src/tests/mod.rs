@@ -1,11 +1,24 @@
 // pub mod both_contracts;
+pub mod test_auctions;
 pub mod test_core;
 pub mod test_currencies;
+pub mod test_fees;
+pub mod test_flash_mint;
+pub mod test_gas_compensation;
+pub mod test_legacy_storage;
+pub mod test_leverage;
 pub mod test_liquidation;
+pub mod test_merge_split;
+pub mod test_operators;
+pub mod test_protections;
 pub mod test_redeem;
+pub mod test_redistribution;
 pub mod test_runtime_verification;
+pub mod test_stability_pool;
 pub mod test_transfer_debt;
+pub mod test_transfer_offers;
 pub mod test_utils;
 pub mod test_utils_runtime_verification;
+pub mod test_vault_ids;
 pub mod test_vaults;
 pub mod test_vaults_indexes;
@@ -0,0 +1,295 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::errors::SCErrors;
+use crate::storage::auctions::{Auction, AuctionConfig};
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use crate::utils::payments::calc_fee;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{Address, Env};
+
+// Creates two vaults that can be liquidated after the price drops to 531953 and a healthy one
+fn setup_vaults(env: &Env, data: &TestData) -> (Address, Address, Address) {
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let depositor_1: Address = Address::generate(&env);
+    let depositor_2: Address = Address::generate(&env);
+    let depositor_3: Address = Address::generate(&env);
+
+    // Each new vault has a lower or equal index than the previous ones so it's always the new lowest
+    for (depositor, collateral) in [
+        (&depositor_3, 500_000_0000000u128),
+        (&depositor_2, 100_000_0000000u128),
+        (&depositor_1, 100_000_0000000u128),
+    ] {
+        data.collateral_token_admin_client
+            .mint(depositor, &(collateral as i128));
+
+        data.contract_client.new_vault(
+            &OptionalVaultKey::None,
+            depositor,
+            &5_000_0000000,
+            &collateral,
+            &data.stable_token_denomination,
+        );
+    }
+
+    data.contract_client.set_auction_config(
+        &data.stable_token_denomination,
+        &AuctionConfig {
+            premium: 1_2000000,
+            min_price: 8000000,
+            duration: 3600,
+        },
+    );
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &531953,
+    );
+
+    (depositor_1, depositor_2, depositor_3)
+}
+
+#[test]
+fn test_auction_bids() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    for invalid_config in [
+        AuctionConfig {
+            premium: 1_2000000,
+            min_price: 8000000,
+            duration: 0,
+        },
+        // The auction can't start below the oracle price
+        AuctionConfig {
+            premium: 9000000,
+            min_price: 8000000,
+            duration: 3600,
+        },
+        AuctionConfig {
+            premium: 1_2000000,
+            min_price: 0,
+            duration: 3600,
+        },
+        AuctionConfig {
+            premium: 1_2000000,
+            min_price: 1_2000001,
+            duration: 3600,
+        },
+    ] {
+        let invalid_config_error = data
+            .contract_client
+            .try_set_auction_config(&data.stable_token_denomination, &invalid_config)
+            .unwrap_err()
+            .unwrap();
+
+        assert_eq!(invalid_config_error, SCErrors::InvalidAuctionConfig.into());
+    }
+
+    let (depositor_1, _, _) = setup_vaults(&env, &data);
+
+    // The liquidator doesn't pay anything, the vault is moved into an auction
+    let liquidator: Address = Address::generate(&env);
+    data.contract_client
+        .liquidate(&liquidator, &data.stable_token_denomination, &1u32);
+
+    let vault_collateral: u128 = 100_000_0000000 - calc_fee(&data.fee, &100_000_0000000);
+    let auctioned_collateral: u128 = vault_collateral - calc_fee(&data.fee, &vault_collateral);
+
+    let auction: Auction = data.contract_client.get_auction(&1);
+    assert_eq!(auction.owner, depositor_1);
+    assert_eq!(auction.debt, 5_000_0000000);
+    assert_eq!(auction.collateral, auctioned_collateral);
+    assert_eq!(auction.start_price, 638343);
+    assert_eq!(auction.min_price, 425562);
+    assert_eq!(data.contract_client.get_auction_price(&1), 638343);
+    assert_eq!(data.collateral_token_client.balance(&liquidator), 0);
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+
+    assert_eq!(vaults_info.total_vaults, 2);
+    assert_eq!(vaults_info.total_debt, 10_000_0000000);
+
+    let bidder: Address = Address::generate(&env);
+    data.stable_token_admin_client
+        .mint(&bidder, &10_000_0000000);
+
+    let max_price_error = data
+        .contract_client
+        .try_bid(&bidder, &1, &50_000_0000000, &638342)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        max_price_error,
+        SCErrors::AuctionPriceIsHigherThanMax.into()
+    );
+
+    let invalid_bid_error = data
+        .contract_client
+        .try_bid(&bidder, &1, &(auctioned_collateral + 1), &638343)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(invalid_bid_error, SCErrors::InvalidAuctionBid.into());
+
+    data.contract_client
+        .bid(&bidder, &1, &50_000_0000000, &638343);
+
+    let first_cost: u128 = 3_191_7150000;
+    let auction: Auction = data.contract_client.get_auction(&1);
+    assert_eq!(auction.debt, 5_000_0000000 - first_cost);
+    assert_eq!(auction.collateral, auctioned_collateral - 50_000_0000000);
+    assert_eq!(
+        data.stable_token_client.balance(&bidder) as u128,
+        10_000_0000000 - first_cost
+    );
+    assert_eq!(
+        data.collateral_token_client.balance(&bidder),
+        50_000_0000000
+    );
+
+    // The price decays linearly with time towards the min price
+    env.ledger().with_mut(|ledger| ledger.timestamp += 900);
+    assert_eq!(data.contract_client.get_auction_price(&1), 585147);
+
+    // Buying the rest costs more than the debt left, so the bidder only pays the debt
+    // and the collateral left goes back to the owner of the vault
+    data.contract_client
+        .bid(&bidder, &1, &auction.collateral, &585147);
+
+    let second_collateral: u128 = auction.debt * 1_0000000 / 585147;
+    assert_eq!(
+        data.stable_token_client.balance(&bidder) as u128,
+        5_000_0000000
+    );
+    assert!(second_collateral < auction.collateral);
+    assert_eq!(
+        data.collateral_token_client.balance(&bidder) as u128,
+        50_000_0000000 + second_collateral
+    );
+    assert_eq!(
+        data.collateral_token_client.balance(&depositor_1) as u128,
+        auction.collateral - second_collateral
+    );
+
+    assert_eq!(
+        data.contract_client
+            .try_get_auction(&1)
+            .unwrap_err()
+            .unwrap(),
+        SCErrors::AuctionDoesntExist.into()
+    );
+}
+
+#[test]
+fn test_expired_auctions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let (_, depositor_2, depositor_3) = setup_vaults(&env, &data);
+    let caller: Address = Address::generate(&env);
+
+    data.contract_client
+        .liquidate(&caller, &data.stable_token_denomination, &1u32);
+
+    let auction: Auction = data.contract_client.get_auction(&1);
+
+    let not_expired_error = data
+        .contract_client
+        .try_restart_auction(&caller, &1)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(not_expired_error, SCErrors::AuctionIsNotExpired.into());
+
+    let not_expired_error = data
+        .contract_client
+        .try_redistribute_auction(&caller, &1)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(not_expired_error, SCErrors::AuctionIsNotExpired.into());
+
+    // The price never goes below the min price before the auction expires
+    env.ledger().with_mut(|ledger| ledger.timestamp += 3599);
+    assert_eq!(data.contract_client.get_auction_price(&1), 425621);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 1);
+    assert_eq!(data.contract_client.get_auction_price(&1), 0);
+
+    let expired_error = data
+        .contract_client
+        .try_bid(&caller, &1, &1_0000000, &638343)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(expired_error, SCErrors::AuctionIsExpired.into());
+
+    // Restarting uses the current price
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &500000,
+    );
+    data.contract_client.restart_auction(&caller, &1);
+
+    let restarted_auction: Auction = data.contract_client.get_auction(&1);
+    assert_eq!(restarted_auction.start_price, 600000);
+    assert_eq!(restarted_auction.min_price, 400000);
+    assert_eq!(restarted_auction.started_at, env.ledger().timestamp());
+    assert_eq!(restarted_auction.collateral, auction.collateral);
+
+    // If nobody buys it again, what is left goes to the remaining vaults
+    env.ledger().with_mut(|ledger| ledger.timestamp += 3600);
+    data.contract_client.redistribute_auction(&caller, &1);
+
+    assert_eq!(
+        data.contract_client
+            .try_get_auction(&1)
+            .unwrap_err()
+            .unwrap(),
+        SCErrors::AuctionDoesntExist.into()
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+
+    assert_eq!(vaults_info.total_vaults, 2);
+    assert_eq!(vaults_info.total_debt, 15_000_0000000);
+
+    let vault_2: Vault =
+        data.contract_client
+            .get_vault(&depositor_2, &data.stable_token_denomination, &0);
+    let vault_3: Vault =
+        data.contract_client
+            .get_vault(&depositor_3, &data.stable_token_denomination, &0);
+
+    assert_eq!(vault_2.total_debt, 7_500_0000000);
+    assert_eq!(vault_3.total_debt, 7_500_0000000);
+    assert!(vault_2.total_collateral + vault_3.total_collateral <= vaults_info.total_col);
+    assert!(vaults_info.total_col - vault_2.total_collateral - vault_3.total_collateral <= 100);
+}
@@ -0,0 +1,600 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::errors::SCErrors;
+use crate::storage::fees::{FeeSchedule, FeesDistribution};
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, create_token_contract, set_initial_state,
+    InitialVariables, TestData,
+};
+use crate::utils::indexes::calculate_user_vault_index;
+use crate::utils::payments::calc_fee;
+use locking_pool::{LockingPoolContract, LockingPoolContractClient};
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation};
+use soroban_sdk::{symbol_short, vec, Address, Env, IntoVal, Symbol};
+
+#[test]
+fn test_fee_schedule_setters() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    // Without any schedule set, the legacy fee and redemption tiers are used
+    let legacy_schedule: FeeSchedule = data
+        .contract_client
+        .get_fee_schedule(&data.stable_token_denomination);
+
+    assert_eq!(
+        legacy_schedule,
+        FeeSchedule {
+            open: data.fee,
+            deposit: data.fee,
+            withdraw: 0,
+            close: data.fee,
+            liquidation: data.fee,
+            redemption: 100000,
+            partial_redemption: 250000,
+            stablecoin_fees: false,
+        }
+    );
+
+    let default_schedule: FeeSchedule = FeeSchedule {
+        open: 100000,
+        deposit: 20000,
+        withdraw: 30000,
+        close: 40000,
+        liquidation: 50000,
+        redemption: 150000,
+        partial_redemption: 300000,
+        stablecoin_fees: false,
+    };
+
+    data.contract_client
+        .set_fee_schedule(&None, &default_schedule);
+
+    assert_eq!(
+        env.auths().first().unwrap(),
+        &(
+            data.contract_admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    data.contract_client.address.clone(),
+                    Symbol::new(&env, "set_fee_schedule"),
+                    (None::<Symbol>, default_schedule.clone()).into_val(&env),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )
+    );
+
+    assert_eq!(
+        data.contract_client
+            .get_fee_schedule(&data.stable_token_denomination),
+        default_schedule
+    );
+
+    // A denomination schedule has priority over the default one
+    let usd_schedule: FeeSchedule = FeeSchedule {
+        open: 0,
+        ..default_schedule.clone()
+    };
+
+    data.contract_client
+        .set_fee_schedule(&Some(data.stable_token_denomination.clone()), &usd_schedule);
+
+    assert_eq!(
+        data.contract_client
+            .get_fee_schedule(&data.stable_token_denomination),
+        usd_schedule
+    );
+
+    data.contract_client
+        .remove_fee_schedule(&data.stable_token_denomination);
+
+    assert_eq!(
+        data.contract_client
+            .get_fee_schedule(&data.stable_token_denomination),
+        default_schedule
+    );
+
+    // Rates over the caps are rejected
+    let invalid_fee_error = data
+        .contract_client
+        .try_set_fee_schedule(
+            &None,
+            &FeeSchedule {
+                liquidation: 100001,
+                ..default_schedule.clone()
+            },
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(invalid_fee_error, SCErrors::InvalidFee.into());
+
+    let invalid_redemption_fee_error = data
+        .contract_client
+        .try_set_fee_schedule(
+            &None,
+            &FeeSchedule {
+                partial_redemption: 500001,
+                ..default_schedule.clone()
+            },
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(invalid_redemption_fee_error, SCErrors::InvalidFee.into());
+
+    let invalid_currency_error = data
+        .contract_client
+        .try_set_fee_schedule(&Some(symbol_short!("eur")), &default_schedule)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(invalid_currency_error, SCErrors::CurrencyDoesntExist.into());
+
+    let invalid_legacy_fee_error = data
+        .contract_client
+        .try_set_fee(&100001)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(invalid_legacy_fee_error, SCErrors::InvalidFee.into());
+}
+
+#[test]
+fn test_fees_charged_per_operation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let fee_schedule: FeeSchedule = FeeSchedule {
+        open: 100000,
+        deposit: 20000,
+        withdraw: 30000,
+        close: 40000,
+        liquidation: 50000,
+        redemption: 100000,
+        partial_redemption: 250000,
+        stablecoin_fees: false,
+    };
+
+    data.contract_client
+        .set_fee_schedule(&Some(data.stable_token_denomination.clone()), &fee_schedule);
+
+    let depositor: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&depositor, &(base_variables.collateral_amount as i128 * 2));
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &depositor,
+        &base_variables.initial_debt,
+        &base_variables.collateral_amount,
+        &data.stable_token_denomination,
+    );
+
+    let open_fee: u128 = calc_fee(&fee_schedule.open, &base_variables.collateral_amount);
+    let vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
+
+    assert_eq!(
+        vault.total_collateral,
+        base_variables.collateral_amount - open_fee
+    );
+    assert_eq!(
+        data.collateral_token_client.balance(&data.treasury) as u128,
+        open_fee
+    );
+
+    let collateral_to_add: u128 = 1000_0000000;
+    data.contract_client.increase_collateral(
+        &OptionalVaultKey::None,
+        &VaultKey {
+            index: vault.index,
+            account: depositor.clone(),
+            denomination: data.stable_token_denomination.clone(),
+            id: 0,
+        },
+        &OptionalVaultKey::None,
+        &collateral_to_add,
+    );
+
+    let deposit_fee: u128 = calc_fee(&fee_schedule.deposit, &collateral_to_add);
+    let vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
+
+    assert_eq!(
+        vault.total_collateral,
+        base_variables.collateral_amount - open_fee + collateral_to_add - deposit_fee
+    );
+    assert_eq!(
+        data.collateral_token_client.balance(&data.treasury) as u128,
+        open_fee + deposit_fee
+    );
+
+    let depositor_balance: u128 = data.collateral_token_client.balance(&depositor) as u128;
+    let collateral_to_withdraw: u128 = 500_0000000;
+    data.contract_client.withdraw_collateral(
+        &OptionalVaultKey::None,
+        &VaultKey {
+            index: vault.index,
+            account: depositor.clone(),
+            denomination: data.stable_token_denomination.clone(),
+            id: 0,
+        },
+        &OptionalVaultKey::None,
+        &collateral_to_withdraw,
+    );
+
+    let withdraw_fee: u128 = calc_fee(&fee_schedule.withdraw, &collateral_to_withdraw);
+    let vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
+
+    assert_eq!(
+        vault.total_collateral,
+        base_variables.collateral_amount - open_fee + collateral_to_add
+            - deposit_fee
+            - collateral_to_withdraw
+    );
+    assert_eq!(
+        data.collateral_token_client.balance(&depositor) as u128,
+        depositor_balance + collateral_to_withdraw - withdraw_fee
+    );
+    assert_eq!(
+        data.collateral_token_client.balance(&data.treasury) as u128,
+        open_fee + deposit_fee + withdraw_fee
+    );
+
+    let depositor_balance: u128 = data.collateral_token_client.balance(&depositor) as u128;
+    data.contract_client.pay_debt(
+        &OptionalVaultKey::None,
+        &VaultKey {
+            index: vault.index,
+            account: depositor.clone(),
+            denomination: data.stable_token_denomination.clone(),
+            id: 0,
+        },
+        &OptionalVaultKey::None,
+        &vault.total_debt,
+    );
+
+    let close_fee: u128 = calc_fee(&fee_schedule.close, &vault.total_collateral);
+
+    assert_eq!(
+        data.collateral_token_client.balance(&depositor) as u128,
+        depositor_balance + vault.total_collateral - close_fee
+    );
+    assert_eq!(
+        data.collateral_token_client.balance(&data.treasury) as u128,
+        open_fee + deposit_fee + withdraw_fee + close_fee
+    );
+    assert_eq!(
+        data.collateral_token_client
+            .balance(&data.contract_client.address),
+        0
+    );
+}
+
+#[test]
+fn test_stablecoin_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let fee_schedule: FeeSchedule = FeeSchedule {
+        open: 100000,
+        deposit: 20000,
+        withdraw: 30000,
+        close: 40000,
+        liquidation: 50000,
+        redemption: 100000,
+        partial_redemption: 250000,
+        stablecoin_fees: true,
+    };
+
+    data.contract_client
+        .set_fee_schedule(&Some(data.stable_token_denomination.clone()), &fee_schedule);
+
+    let depositor: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&depositor, &(base_variables.collateral_amount as i128));
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &depositor,
+        &base_variables.initial_debt,
+        &base_variables.collateral_amount,
+        &data.stable_token_denomination,
+    );
+
+    // The collateral is untouched and the fee is added to the debt instead
+    let opening_fee: u128 = calc_fee(&fee_schedule.open, &base_variables.initial_debt);
+    let vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
+
+    assert_eq!(vault.total_collateral, base_variables.collateral_amount);
+    assert_eq!(vault.total_debt, base_variables.initial_debt + opening_fee);
+    assert_eq!(
+        vault.index,
+        calculate_user_vault_index(
+            base_variables.initial_debt + opening_fee,
+            base_variables.collateral_amount
+        )
+    );
+    assert_eq!(data.collateral_token_client.balance(&data.treasury), 0);
+    assert_eq!(
+        data.stable_token_client.balance(&data.treasury) as u128,
+        opening_fee
+    );
+    assert_eq!(
+        data.stable_token_client.balance(&depositor) as u128,
+        base_variables.initial_debt
+    );
+
+    let debt_to_add: u128 = 100_0000000;
+    data.contract_client.increase_debt(
+        &OptionalVaultKey::None,
+        &VaultKey {
+            index: vault.index,
+            account: depositor.clone(),
+            denomination: data.stable_token_denomination.clone(),
+            id: 0,
+        },
+        &OptionalVaultKey::None,
+        &debt_to_add,
+    );
+
+    let borrowing_fee: u128 = calc_fee(&fee_schedule.open, &debt_to_add);
+    let vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
+
+    assert_eq!(
+        vault.total_debt,
+        base_variables.initial_debt + opening_fee + debt_to_add + borrowing_fee
+    );
+    assert_eq!(
+        data.stable_token_client.balance(&data.treasury) as u128,
+        opening_fee + borrowing_fee
+    );
+    assert_eq!(
+        data.stable_token_client.balance(&depositor) as u128,
+        base_variables.initial_debt + debt_to_add
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+
+    assert_eq!(vaults_info.total_debt, vault.total_debt);
+    assert_eq!(vaults_info.total_col, vault.total_collateral);
+}
+
+// Registers the locking pool contract with a pool for `deposit_asset` that accepts the collateral token as reward and
+// only accepts distributions from the vaults contract
+fn create_locking_pool<'a>(
+    env: &Env,
+    data: &TestData,
+    deposit_asset: &Address,
+) -> LockingPoolContractClient<'a> {
+    let locking_pool_client =
+        LockingPoolContractClient::new(env, &env.register(LockingPoolContract, ()));
+    locking_pool_client.init(
+        &data.contract_admin,
+        &data.protocol_manager,
+        &data.collateral_token_client.address,
+    );
+    locking_pool_client.set_pool(deposit_asset, &(3600 * 24 * 7), &100_0000000);
+    locking_pool_client.toggle_pool(deposit_asset, &true);
+    locking_pool_client.set_distribution_config(
+        deposit_asset,
+        &100_0000000,
+        &vec![env, data.contract_client.address.clone()],
+    );
+
+    locking_pool_client
+}
+
+#[test]
+fn test_fees_distribution() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let deposit_asset_admin: Address = Address::generate(&env);
+    let (deposit_asset_client, deposit_asset_admin_client) =
+        create_token_contract(&env, &deposit_asset_admin);
+    let deposit_asset: Address = deposit_asset_client.address.clone();
+
+    let locking_pool_client = create_locking_pool(&env, &data, &deposit_asset);
+    let locking_pool: Address = locking_pool_client.address.clone();
+    let staker: Address = Address::generate(&env);
+    deposit_asset_admin_client.mint(&staker, &100_0000000);
+    locking_pool_client.deposit(&deposit_asset, &staker, &100_0000000, &(3600 * 24 * 7));
+
+    // A pool without depositors rejects all the distributions
+    let empty_locking_pool: Address = create_locking_pool(&env, &data, &deposit_asset).address;
+
+    assert_eq!(data.contract_client.get_fees_distribution(), None);
+
+    let invalid_share_error = data
+        .contract_client
+        .try_set_fees_distribution(&FeesDistribution {
+            locking_pool: locking_pool.clone(),
+            deposit_asset: deposit_asset.clone(),
+            share: 1_0000001,
+            min_amount: 0,
+        })
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(invalid_share_error, SCErrors::InvalidFeeShare.into());
+
+    let opening_fee: u128 = calc_fee(&data.fee, &base_variables.collateral_amount);
+
+    // All the fees go to the locking pool once two of them are accumulated
+    let fees_distribution: FeesDistribution = FeesDistribution {
+        locking_pool: locking_pool.clone(),
+        deposit_asset: deposit_asset.clone(),
+        share: 1_0000000,
+        min_amount: opening_fee + 1,
+    };
+
+    data.contract_client
+        .set_fees_distribution(&fees_distribution);
+
+    assert_eq!(
+        env.auths().first().unwrap(),
+        &(
+            data.contract_admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    data.contract_client.address.clone(),
+                    Symbol::new(&env, "set_fees_distribution"),
+                    (fees_distribution.clone(),).into_val(&env),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )
+    );
+
+    assert_eq!(
+        data.contract_client.get_fees_distribution(),
+        Some(fees_distribution.clone())
+    );
+
+    let depositor_1: Address = Address::generate(&env);
+    let depositor_2: Address = Address::generate(&env);
+    for depositor in [depositor_1.clone(), depositor_2.clone()] {
+        data.collateral_token_admin_client
+            .mint(&depositor, &(base_variables.collateral_amount as i128));
+    }
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &depositor_1,
+        &base_variables.initial_debt,
+        &base_variables.collateral_amount,
+        &data.stable_token_denomination,
+    );
+
+    assert_eq!(
+        data.contract_client.get_pending_fees_distribution(),
+        opening_fee
+    );
+    assert_eq!(data.collateral_token_client.balance(&locking_pool), 0);
+    assert_eq!(data.collateral_token_client.balance(&data.treasury), 0);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &depositor_2,
+        &base_variables.initial_debt,
+        &base_variables.collateral_amount,
+        &data.stable_token_denomination,
+    );
+
+    assert_eq!(data.contract_client.get_pending_fees_distribution(), 0);
+    assert_eq!(
+        data.collateral_token_client.balance(&locking_pool) as u128,
+        opening_fee * 2
+    );
+    assert_eq!(data.collateral_token_client.balance(&data.treasury), 0);
+    assert_eq!(
+        data.collateral_token_client
+            .balance(&data.contract_client.address) as u128,
+        (base_variables.collateral_amount - opening_fee) * 2
+    );
+
+    // The distribution moved the reward factor of the pool so the staker can claim it
+    assert_eq!(
+        locking_pool_client
+            .distributed(&deposit_asset)
+            .get(data.collateral_token_client.address.clone()),
+        Some(opening_fee * 2)
+    );
+    assert_eq!(
+        locking_pool_client
+            .pending_rewards(&deposit_asset, &staker)
+            .get(data.collateral_token_client.address.clone()),
+        Some(opening_fee * 2)
+    );
+
+    // If the locking pool rejects the distribution, the whole fee goes to the treasury
+    data.contract_client
+        .set_fees_distribution(&FeesDistribution {
+            locking_pool: empty_locking_pool.clone(),
+            deposit_asset: deposit_asset.clone(),
+            share: 5000000,
+            min_amount: 0,
+        });
+
+    let depositor_3: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&depositor_3, &(base_variables.collateral_amount as i128));
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &depositor_3,
+        &base_variables.initial_debt,
+        &base_variables.collateral_amount,
+        &data.stable_token_denomination,
+    );
+
+    assert_eq!(data.collateral_token_client.balance(&empty_locking_pool), 0);
+    assert_eq!(
+        data.collateral_token_client.balance(&data.treasury) as u128,
+        opening_fee
+    );
+
+    // The fees waiting to be distributed are sent to the treasury when the distribution is removed
+    data.contract_client
+        .set_fees_distribution(&FeesDistribution {
+            min_amount: u128::MAX,
+            ..fees_distribution.clone()
+        });
+
+    let depositor_4: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&depositor_4, &(base_variables.collateral_amount as i128));
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &depositor_4,
+        &base_variables.initial_debt,
+        &base_variables.collateral_amount,
+        &data.stable_token_denomination,
+    );
+
+    assert_eq!(
+        data.contract_client.get_pending_fees_distribution(),
+        opening_fee
+    );
+
+    data.contract_client.remove_fees_distribution();
+    assert_eq!(data.contract_client.get_fees_distribution(), None);
+    assert_eq!(data.contract_client.get_pending_fees_distribution(), 0);
+    assert_eq!(
+        data.collateral_token_client.balance(&data.treasury) as u128,
+        opening_fee * 2
+    );
+    assert_eq!(
+        data.collateral_token_client
+            .balance(&data.contract_client.address) as u128,
+        (base_variables.collateral_amount - opening_fee) * 4
+    );
+}
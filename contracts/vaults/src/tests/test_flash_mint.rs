@@ -0,0 +1,304 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::errors::SCErrors;
+use crate::storage::flash_mint::FlashMintConfig;
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use crate::utils::indexes::calculate_user_vault_index;
+use crate::utils::payments::calc_fee;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Bytes, Env};
+
+mod flash_mint_receiver_mock {
+    use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Bytes, Env, Symbol};
+
+    // Receiver that repays the flash mint, it only repays the amount (without the fee) if the data is empty
+    #[contract]
+    pub struct FlashMintReceiverMock;
+
+    #[contractimpl]
+    impl FlashMintReceiverMock {
+        pub fn init(e: Env, vaults: Address, stablecoin: Address) {
+            e.storage()
+                .instance()
+                .set(&symbol_short!("vaults"), &vaults);
+            e.storage()
+                .instance()
+                .set(&symbol_short!("stable"), &stablecoin);
+        }
+
+        pub fn on_flash_mint(
+            e: Env,
+            _initiator: Address,
+            _denomination: Symbol,
+            amount: u128,
+            fee: u128,
+            data: Bytes,
+        ) {
+            let vaults: Address = e
+                .storage()
+                .instance()
+                .get(&symbol_short!("vaults"))
+                .unwrap();
+            let stablecoin: Address = e
+                .storage()
+                .instance()
+                .get(&symbol_short!("stable"))
+                .unwrap();
+            let repayment: u128 = if data.is_empty() {
+                amount
+            } else {
+                amount + fee
+            };
+
+            token::Client::new(&e, &stablecoin).transfer(
+                &e.current_contract_address(),
+                &vaults,
+                &(repayment as i128),
+            );
+        }
+    }
+}
+
+#[test]
+fn test_flash_mint() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let caller: Address = Address::generate(&env);
+    let receiver: Address = env.register(flash_mint_receiver_mock::FlashMintReceiverMock, ());
+    flash_mint_receiver_mock::FlashMintReceiverMockClient::new(&env, &receiver).init(
+        &data.contract_client.address,
+        &data.stable_token_client.address,
+    );
+
+    let repay_fee: Bytes = Bytes::from_array(&env, &[1]);
+
+    let disabled_error = data
+        .contract_client
+        .try_flash_mint(
+            &caller,
+            &receiver,
+            &data.stable_token_denomination,
+            &1_000_0000000,
+            &repay_fee,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(disabled_error, SCErrors::FlashMintIsDisabled.into());
+
+    let flash_mint_config: FlashMintConfig = FlashMintConfig {
+        max_amount: 100_000_0000000,
+        fee: 5000,
+    };
+    data.contract_client
+        .set_flash_mint_config(&data.stable_token_denomination, &flash_mint_config);
+
+    let too_much_error = data
+        .contract_client
+        .try_flash_mint(
+            &caller,
+            &receiver,
+            &data.stable_token_denomination,
+            &100_001_0000000,
+            &repay_fee,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(too_much_error, SCErrors::InvalidFlashMintAmount.into());
+
+    // The receiver uses its own funds to pay the fee
+    let fee: u128 = calc_fee(&flash_mint_config.fee, &100_000_0000000);
+    data.stable_token_admin_client
+        .mint(&receiver, &(fee as i128));
+
+    let not_repaid_error = data
+        .contract_client
+        .try_flash_mint(
+            &caller,
+            &receiver,
+            &data.stable_token_denomination,
+            &100_000_0000000,
+            &Bytes::new(&env),
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(not_repaid_error, SCErrors::FlashMintNotRepaid.into());
+
+    data.contract_client.flash_mint(
+        &caller,
+        &receiver,
+        &data.stable_token_denomination,
+        &100_000_0000000,
+        &repay_fee,
+    );
+
+    // Both the amount and the fee are burned
+    assert_eq!(data.stable_token_client.balance(&receiver), 0);
+    assert_eq!(
+        data.stable_token_client
+            .balance(&data.contract_client.address),
+        0
+    );
+}
+
+#[test]
+fn test_liquidate_with_flash_mint() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let depositor: Address = Address::generate(&env);
+    let depositor_debt: u128 = 5_000_0000000;
+    let depositor_collateral: u128 = 100_000_0000000;
+    let other: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&depositor, &(depositor_collateral as i128));
+    data.collateral_token_admin_client
+        .mint(&other, &500_000_0000000);
+
+    let depositor_key: VaultKey = VaultKey {
+        index: calculate_user_vault_index(
+            depositor_debt,
+            depositor_collateral - calc_fee(&data.fee, &depositor_collateral),
+        ),
+        account: depositor.clone(),
+        denomination: data.stable_token_denomination.clone(),
+        id: 0,
+    };
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &depositor,
+        &depositor_debt,
+        &depositor_collateral,
+        &data.stable_token_denomination,
+    );
+    data.contract_client.new_vault(
+        &OptionalVaultKey::Some(depositor_key),
+        &other,
+        &5_000_0000000,
+        &500_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &531953,
+    );
+
+    let liquidator: Address = Address::generate(&env);
+    let receiver: Address = env.register(flash_mint_receiver_mock::FlashMintReceiverMock, ());
+    flash_mint_receiver_mock::FlashMintReceiverMockClient::new(&env, &receiver).init(
+        &data.contract_client.address,
+        &data.stable_token_client.address,
+    );
+
+    let repay_fee: Bytes = Bytes::from_array(&env, &[1]);
+
+    let disabled_error = data
+        .contract_client
+        .try_liquidate_with_flash_mint(
+            &liquidator,
+            &receiver,
+            &data.stable_token_denomination,
+            &1,
+            &repay_fee,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(disabled_error, SCErrors::FlashMintIsDisabled.into());
+
+    let flash_mint_config: FlashMintConfig = FlashMintConfig {
+        max_amount: 100_000_0000000,
+        fee: 5000,
+    };
+    data.contract_client
+        .set_flash_mint_config(&data.stable_token_denomination, &flash_mint_config);
+
+    // The receiver would sell the collateral it gets during the callback, here it already has the stablecoins
+    let fee: u128 = calc_fee(&flash_mint_config.fee, &depositor_debt);
+    data.stable_token_admin_client
+        .mint(&receiver, &((depositor_debt + fee) as i128));
+
+    let not_repaid_error = data
+        .contract_client
+        .try_liquidate_with_flash_mint(
+            &liquidator,
+            &receiver,
+            &data.stable_token_denomination,
+            &1,
+            &Bytes::new(&env),
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(not_repaid_error, SCErrors::FlashMintNotRepaid.into());
+
+    let liquidated: soroban_sdk::Vec<Vault> = data.contract_client.liquidate_with_flash_mint(
+        &liquidator,
+        &receiver,
+        &data.stable_token_denomination,
+        &1,
+        &repay_fee,
+    );
+
+    assert_eq!(liquidated.len(), 1);
+    assert_eq!(liquidated.get(0).unwrap().account, depositor);
+
+    // The receiver gets the collateral and both the debt and the fee are burned
+    let deposited_collateral: u128 =
+        depositor_collateral - calc_fee(&data.fee, &depositor_collateral);
+    assert_eq!(
+        data.collateral_token_client.balance(&receiver) as u128,
+        deposited_collateral - calc_fee(&data.fee, &deposited_collateral)
+    );
+    assert_eq!(data.stable_token_client.balance(&receiver), 0);
+    assert_eq!(
+        data.stable_token_client
+            .balance(&data.contract_client.address),
+        0
+    );
+    assert_eq!(
+        data.contract_client
+            .get_vaults_info(&data.stable_token_denomination)
+            .total_vaults,
+        1
+    );
+
+    // Nothing else can be liquidated
+    let nothing_error = data
+        .contract_client
+        .try_liquidate_with_flash_mint(
+            &liquidator,
+            &receiver,
+            &data.stable_token_denomination,
+            &1,
+            &repay_fee,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(nothing_error, SCErrors::NotEnoughVaultsToLiquidate.into());
+}
@@ -0,0 +1,158 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::errors::SCErrors;
+use crate::storage::gas_compensation::GasCompensation;
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env};
+
+#[test]
+fn test_gas_compensation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let invalid_error = data
+        .contract_client
+        .try_set_gas_compensation(&GasCompensation {
+            asset: data.native_token_client.address.clone(),
+            amount: 0,
+        })
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(invalid_error, SCErrors::InvalidGasCompensation.into());
+
+    let gas_compensation: GasCompensation = GasCompensation {
+        asset: data.native_token_client.address.clone(),
+        amount: 5_0000000,
+    };
+    data.contract_client.set_gas_compensation(&gas_compensation);
+    assert_eq!(
+        data.contract_client.get_gas_compensation(),
+        Some(gas_compensation.clone())
+    );
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let depositor_1: Address = Address::generate(&env);
+    let depositor_2: Address = Address::generate(&env);
+    let depositor_3: Address = Address::generate(&env);
+
+    // Each new vault has a lower or equal index than the previous ones so it's always the new lowest
+    for (depositor, collateral) in [
+        (&depositor_3, 500_000_0000000u128),
+        (&depositor_2, 100_000_0000000u128),
+        (&depositor_1, 100_000_0000000u128),
+    ] {
+        data.collateral_token_admin_client
+            .mint(depositor, &(collateral as i128));
+        data.native_token_admin_client.mint(depositor, &5_0000000);
+
+        data.contract_client.new_vault(
+            &OptionalVaultKey::None,
+            depositor,
+            &5_000_0000000,
+            &collateral,
+            &data.stable_token_denomination,
+        );
+
+        assert_eq!(data.native_token_client.balance(depositor), 0);
+    }
+
+    assert_eq!(
+        data.native_token_client
+            .balance(&data.contract_client.address),
+        15_0000000
+    );
+    assert_eq!(
+        data.contract_client
+            .get_gas_reserve(&depositor_1, &data.stable_token_denomination, &0),
+        Some(gas_compensation.clone())
+    );
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &531953,
+    );
+
+    // The liquidator receives the reserve of the liquidated vault
+    let liquidator: Address = Address::generate(&env);
+    data.stable_token_admin_client
+        .mint(&liquidator, &10_000_0000000);
+    data.contract_client
+        .liquidate(&liquidator, &data.stable_token_denomination, &1u32);
+
+    assert_eq!(data.native_token_client.balance(&liquidator), 5_0000000);
+
+    // The reserve follows the vault when it's transferred
+    let new_owner: Address = Address::generate(&env);
+    let vault_2: Vault =
+        data.contract_client
+            .get_vault(&depositor_2, &data.stable_token_denomination, &0);
+    data.contract_client.transfer_debt(
+        &OptionalVaultKey::None,
+        &VaultKey {
+            index: vault_2.index,
+            account: depositor_2.clone(),
+            denomination: data.stable_token_denomination.clone(),
+            id: 0,
+        },
+        &new_owner,
+    );
+
+    assert_eq!(
+        data.contract_client
+            .get_gas_reserve(&new_owner, &data.stable_token_denomination, &0),
+        Some(gas_compensation.clone())
+    );
+
+    // Closing the vault returns the reserve to the owner, even if the config changed since it was opened
+    data.contract_client.remove_gas_compensation();
+
+    let vault_3: Vault =
+        data.contract_client
+            .get_vault(&depositor_3, &data.stable_token_denomination, &0);
+    data.contract_client.pay_debt(
+        &OptionalVaultKey::Some(VaultKey {
+            index: vault_2.index,
+            account: new_owner.clone(),
+            denomination: data.stable_token_denomination.clone(),
+            id: 0,
+        }),
+        &VaultKey {
+            index: vault_3.index,
+            account: depositor_3.clone(),
+            denomination: data.stable_token_denomination.clone(),
+            id: 0,
+        },
+        &OptionalVaultKey::None,
+        &5_000_0000000,
+    );
+
+    assert_eq!(data.native_token_client.balance(&depositor_3), 5_0000000);
+
+    data.contract_client
+        .liquidate(&liquidator, &data.stable_token_denomination, &1u32);
+
+    assert_eq!(data.native_token_client.balance(&liquidator), 10_0000000);
+    assert_eq!(
+        data.native_token_client
+            .balance(&data.contract_client.address),
+        0
+    );
+}
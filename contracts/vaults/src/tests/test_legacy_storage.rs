@@ -0,0 +1,301 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{vec, Address, Env, IntoVal, Map, Symbol, Val, Vec};
+
+fn legacy_key(key: &OptionalVaultKey) -> LegacyOptionalVaultKey {
+    match key {
+        OptionalVaultKey::None => LegacyOptionalVaultKey::None,
+        OptionalVaultKey::Some(key) => LegacyOptionalVaultKey::Some(LegacyVaultKey {
+            index: key.index,
+            account: key.account.clone(),
+            denomination: key.denomination.clone(),
+        }),
+    }
+}
+
+// Rewrites the vaults of the denomination with the keys and layouts they had before vaults had an id
+fn set_legacy_state(e: &Env, data: &TestData, accounts: &[Address]) {
+    e.as_contract(&data.contract_client.address, || {
+        let vaults_info: VaultsInfo = e.vaults_info(&data.stable_token_denomination).unwrap();
+
+        for account in accounts.iter() {
+            let key: VaultsDataKeys =
+                VaultsDataKeys::Vault((account.clone(), data.stable_token_denomination.clone(), 0));
+            let vault: Vault = e.storage().persistent().get(&key).unwrap();
+
+            e.storage().persistent().remove(&key);
+            e.storage()
+                .persistent()
+                .remove(&VaultsDataKeys::VaultIndex(VaultIndexKey {
+                    user: account.clone(),
+                    denomination: data.stable_token_denomination.clone(),
+                    id: 0,
+                }));
+            e.storage()
+                .persistent()
+                .remove(&VaultsDataKeys::AccountVaults((
+                    account.clone(),
+                    data.stable_token_denomination.clone(),
+                )));
+
+            e.storage().persistent().set(
+                &LegacyVaultsDataKeys::Vault((
+                    account.clone(),
+                    data.stable_token_denomination.clone(),
+                )),
+                &LegacyVault {
+                    index: vault.index,
+                    next_key: legacy_key(&vault.next_key),
+                    account: vault.account.clone(),
+                    total_debt: vault.total_debt,
+                    total_collateral: vault.total_collateral,
+                    denomination: vault.denomination.clone(),
+                },
+            );
+            e.storage().persistent().set(
+                &LegacyVaultsDataKeys::VaultIndex(LegacyVaultIndexKey {
+                    user: account.clone(),
+                    denomination: data.stable_token_denomination.clone(),
+                }),
+                &vault.index,
+            );
+        }
+
+        e.storage().instance().set(
+            &VaultsDataKeys::VaultsInfo(data.stable_token_denomination.clone()),
+            &LegacyVaultsInfo {
+                denomination: vaults_info.denomination,
+                total_vaults: vaults_info.total_vaults,
+                total_debt: vaults_info.total_debt,
+                total_col: vaults_info.total_col,
+                lowest_key: legacy_key(&vaults_info.lowest_key),
+                min_col_rate: vaults_info.min_col_rate,
+                min_debt_creation: vaults_info.min_debt_creation,
+                opening_col_rate: vaults_info.opening_col_rate,
+            },
+        );
+    });
+}
+
+fn key_of(vault: &Vault) -> VaultKey {
+    VaultKey {
+        index: vault.index,
+        account: vault.account.clone(),
+        denomination: vault.denomination.clone(),
+        id: vault.id,
+    }
+}
+
+#[test]
+fn test_reading_legacy_vaults() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let first: Address = Address::generate(&env);
+    let second: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&first, &300_000_0000000);
+    data.collateral_token_admin_client
+        .mint(&second, &200_000_0000000);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &first,
+        &5_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+    let first_vault: Vault =
+        data.contract_client
+            .get_vault(&first, &data.stable_token_denomination, &0);
+    data.contract_client.new_vault(
+        &OptionalVaultKey::Some(key_of(&first_vault)),
+        &second,
+        &5_000_0000000,
+        &200_000_0000000,
+        &data.stable_token_denomination,
+    );
+    let first_vault: Vault =
+        data.contract_client
+            .get_vault(&first, &data.stable_token_denomination, &0);
+    let second_vault: Vault =
+        data.contract_client
+            .get_vault(&second, &data.stable_token_denomination, &0);
+
+    set_legacy_state(&env, &data, &[first.clone(), second.clone()]);
+
+    // The vaults are read as the vault 0 of each account and the links between them still work
+    assert_eq!(
+        data.contract_client
+            .get_vault(&first, &data.stable_token_denomination, &0),
+        first_vault
+    );
+    assert_eq!(
+        data.contract_client
+            .get_vault_from_key(&key_of(&second_vault)),
+        second_vault
+    );
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&first, &data.stable_token_denomination),
+        vec![&env, 0]
+    );
+    assert_eq!(
+        data.contract_client.get_vaults(
+            &OptionalVaultKey::None,
+            &data.stable_token_denomination,
+            &2,
+            &false,
+        ),
+        vec![&env, first_vault.clone(), second_vault.clone()]
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+    assert_eq!(
+        vaults_info.lowest_key,
+        OptionalVaultKey::Some(key_of(&first_vault))
+    );
+    assert_eq!(vaults_info.total_vaults, 2);
+
+    // Once a vault is updated it's saved with the current layout
+    data.contract_client.increase_collateral(
+        &OptionalVaultKey::None,
+        &key_of(&first_vault),
+        &OptionalVaultKey::None,
+        &100_000_0000000,
+    );
+
+    env.as_contract(&data.contract_client.address, || {
+        assert!(!env
+            .storage()
+            .persistent()
+            .has(&LegacyVaultsDataKeys::Vault((
+                first.clone(),
+                data.stable_token_denomination.clone(),
+            ))));
+        assert!(env.storage().persistent().has(&VaultsDataKeys::Vault((
+            first.clone(),
+            data.stable_token_denomination.clone(),
+            0,
+        ))));
+
+        let stored: Map<Symbol, Val> = env
+            .storage()
+            .instance()
+            .get(&VaultsDataKeys::VaultsInfo(
+                data.stable_token_denomination.clone(),
+            ))
+            .unwrap();
+        let lowest_key: Vec<Val> = stored
+            .get(Symbol::new(&env, "lowest_key"))
+            .unwrap()
+            .into_val(&env);
+        let lowest_key: Map<Symbol, Val> = lowest_key.get(1).unwrap().into_val(&env);
+        assert!(lowest_key.contains_key(Symbol::new(&env, "id")));
+    });
+
+    // The accounts with a vault from before the ids existed get the next id for their new vaults
+    let new_id: u32 = data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &first,
+        &5_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    assert_eq!(new_id, 1);
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&first, &data.stable_token_denomination),
+        vec![&env, 0, 1]
+    );
+
+    // A vault still saved with the old layout can be closed and its id isn't reused
+    let second_vault: Vault =
+        data.contract_client
+            .get_vault(&second, &data.stable_token_denomination, &0);
+    let prev_vault: Vault = data
+        .contract_client
+        .get_vaults(
+            &OptionalVaultKey::None,
+            &data.stable_token_denomination,
+            &3,
+            &false,
+        )
+        .iter()
+        .take_while(|vault| vault.account != second)
+        .last()
+        .unwrap();
+    data.stable_token_admin_client.mint(&second, &5_000_0000000);
+    data.contract_client.pay_debt(
+        &OptionalVaultKey::Some(key_of(&prev_vault)),
+        &key_of(&second_vault),
+        &OptionalVaultKey::None,
+        &5_000_0000000,
+    );
+
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&second, &data.stable_token_denomination)
+            .len(),
+        0
+    );
+    env.as_contract(&data.contract_client.address, || {
+        assert!(!env
+            .storage()
+            .persistent()
+            .has(&LegacyVaultsDataKeys::Vault((
+                second.clone(),
+                data.stable_token_denomination.clone(),
+            ))));
+        assert!(!env
+            .storage()
+            .persistent()
+            .has(&LegacyVaultsDataKeys::VaultIndex(LegacyVaultIndexKey {
+                user: second.clone(),
+                denomination: data.stable_token_denomination.clone(),
+            })));
+    });
+
+    data.collateral_token_admin_client
+        .mint(&second, &200_000_0000000);
+    let last_vault: Vault = data
+        .contract_client
+        .get_vaults(
+            &OptionalVaultKey::None,
+            &data.stable_token_denomination,
+            &2,
+            &false,
+        )
+        .last()
+        .unwrap();
+    assert_eq!(
+        data.contract_client.new_vault(
+            &OptionalVaultKey::Some(key_of(&last_vault)),
+            &second,
+            &5_000_0000000,
+            &200_000_0000000,
+            &data.stable_token_denomination,
+        ),
+        1
+    );
+}
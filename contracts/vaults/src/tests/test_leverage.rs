@@ -0,0 +1,433 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::errors::SCErrors;
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use crate::utils::payments::calc_fee;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env};
+
+mod swap_adapter_mock {
+    use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env};
+
+    // AMM with a fixed price (same format as the oracle) for the stablecoin, it pays from its own liquidity
+    // and doesn't check the min amount so the vaults contract is the one doing it
+    #[contract]
+    pub struct SwapAdapterMock;
+
+    #[contractimpl]
+    impl SwapAdapterMock {
+        pub fn init(e: Env, stablecoin: Address, price: u128) {
+            e.storage()
+                .instance()
+                .set(&symbol_short!("stable"), &stablecoin);
+            e.storage().instance().set(&symbol_short!("price"), &price);
+        }
+
+        pub fn swap(
+            e: Env,
+            token_in: Address,
+            token_out: Address,
+            amount_in: u128,
+            _min_amount_out: u128,
+            to: Address,
+        ) -> u128 {
+            let stablecoin: Address = e
+                .storage()
+                .instance()
+                .get(&symbol_short!("stable"))
+                .unwrap();
+            let price: u128 = e.storage().instance().get(&symbol_short!("price")).unwrap();
+            let amount_out: u128 = if token_in == stablecoin {
+                amount_in * 1_0000000 / price
+            } else {
+                amount_in * price / 1_0000000
+            };
+
+            token::Client::new(&e, &token_out).transfer(
+                &e.current_contract_address(),
+                &to,
+                &(amount_out as i128),
+            );
+
+            amount_out
+        }
+    }
+}
+
+#[test]
+fn test_leverage_and_deleverage() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let depositor: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&depositor, &100_000_0000000);
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &depositor,
+        &5_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    let vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
+    let vault_key: VaultKey = VaultKey {
+        index: vault.index,
+        account: depositor.clone(),
+        denomination: data.stable_token_denomination.clone(),
+        id: 0,
+    };
+
+    let not_set_error = data
+        .contract_client
+        .try_leverage(
+            &OptionalVaultKey::None,
+            &vault_key,
+            &OptionalVaultKey::None,
+            &2_000_0000000,
+            &0,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(not_set_error, SCErrors::SwapAdapterIsNotSet.into());
+
+    let swap_adapter: Address = env.register(swap_adapter_mock::SwapAdapterMock, ());
+    swap_adapter_mock::SwapAdapterMockClient::new(&env, &swap_adapter)
+        .init(&data.stable_token_client.address, &931953);
+    data.collateral_token_admin_client
+        .mint(&swap_adapter, &1_000_000_0000000);
+    data.stable_token_admin_client
+        .mint(&swap_adapter, &100_000_0000000);
+
+    data.contract_client.set_swap_adapter(&swap_adapter);
+    assert_eq!(
+        data.contract_client.get_swap_adapter(),
+        Some(swap_adapter.clone())
+    );
+
+    let collateral_out: u128 = 2_000_0000000 * 1_0000000 / 931953;
+
+    let slippage_error = data
+        .contract_client
+        .try_leverage(
+            &OptionalVaultKey::None,
+            &vault_key,
+            &OptionalVaultKey::None,
+            &2_000_0000000,
+            &(collateral_out + 1),
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(slippage_error, SCErrors::SwapOutputIsBelowMinimum.into());
+
+    data.contract_client.leverage(
+        &OptionalVaultKey::None,
+        &vault_key,
+        &OptionalVaultKey::None,
+        &2_000_0000000,
+        &collateral_out,
+    );
+
+    let collateral_after_leverage: u128 =
+        vault.total_collateral + collateral_out - calc_fee(&data.fee, &collateral_out);
+    let vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
+    assert_eq!(vault.total_debt, 7_000_0000000);
+    assert_eq!(vault.total_collateral, collateral_after_leverage);
+
+    // The depositor never touches the new stablecoins
+    assert_eq!(data.stable_token_client.balance(&depositor), 5_000_0000000);
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+    assert_eq!(vaults_info.total_debt, 7_000_0000000);
+    assert_eq!(vaults_info.total_col, collateral_after_leverage);
+
+    let vault_key: VaultKey = VaultKey {
+        index: vault.index,
+        account: depositor.clone(),
+        denomination: data.stable_token_denomination.clone(),
+        id: 0,
+    };
+
+    // The debt left can't be lower than the min debt
+    let min_debt_error = data
+        .contract_client
+        .try_deleverage(
+            &OptionalVaultKey::None,
+            &vault_key,
+            &OptionalVaultKey::None,
+            &30_000_0000000,
+            &0,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(min_debt_error, SCErrors::InvalidMinDebtAmount.into());
+
+    data.contract_client.deleverage(
+        &OptionalVaultKey::None,
+        &vault_key,
+        &OptionalVaultKey::None,
+        &10_000_0000000,
+        &0,
+    );
+
+    let debt_out: u128 = 10_000_0000000 * 931953 / 1_0000000;
+    let vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
+    assert_eq!(vault.total_debt, 7_000_0000000 - debt_out);
+    assert_eq!(
+        vault.total_collateral,
+        collateral_after_leverage - 10_000_0000000
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+    assert_eq!(vaults_info.total_debt, 7_000_0000000 - debt_out);
+    assert_eq!(
+        vaults_info.total_col,
+        collateral_after_leverage - 10_000_0000000
+    );
+    assert_eq!(
+        data.stable_token_client
+            .balance(&data.contract_client.address),
+        0
+    );
+}
+
+fn set_swap_adapter(env: &Env, data: &TestData, price: u128) -> Address {
+    let swap_adapter: Address = env.register(swap_adapter_mock::SwapAdapterMock, ());
+    swap_adapter_mock::SwapAdapterMockClient::new(&env, &swap_adapter)
+        .init(&data.stable_token_client.address, &price);
+    data.collateral_token_admin_client
+        .mint(&swap_adapter, &1_000_000_0000000);
+    data.stable_token_admin_client
+        .mint(&swap_adapter, &100_000_0000000);
+    data.contract_client.set_swap_adapter(&swap_adapter);
+
+    swap_adapter
+}
+
+#[test]
+fn test_new_vault_leveraged() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let depositor: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&depositor, &100_000_0000000);
+
+    let not_set_error = data
+        .contract_client
+        .try_new_vault_leveraged(
+            &OptionalVaultKey::None,
+            &depositor,
+            &5_000_0000000,
+            &100_000_0000000,
+            &data.stable_token_denomination,
+            &0,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(not_set_error, SCErrors::SwapAdapterIsNotSet.into());
+
+    set_swap_adapter(&env, &data, 931953);
+
+    let collateral_out: u128 = 5_000_0000000 * 1_0000000 / 931953;
+
+    let slippage_error = data
+        .contract_client
+        .try_new_vault_leveraged(
+            &OptionalVaultKey::None,
+            &depositor,
+            &5_000_0000000,
+            &100_000_0000000,
+            &data.stable_token_denomination,
+            &(collateral_out + 1),
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(slippage_error, SCErrors::SwapOutputIsBelowMinimum.into());
+
+    // The collateral of the depositor alone isn't enough for this debt
+    let opening_rate_error = data
+        .contract_client
+        .try_new_vault_leveraged(
+            &OptionalVaultKey::None,
+            &depositor,
+            &5_000_0000000,
+            &5_000_0000000,
+            &data.stable_token_denomination,
+            &collateral_out,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        opening_rate_error,
+        SCErrors::InvalidOpeningCollateralRatio.into()
+    );
+
+    let id: u32 = data.contract_client.new_vault_leveraged(
+        &OptionalVaultKey::None,
+        &depositor,
+        &5_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+        &collateral_out,
+    );
+
+    let total_collateral: u128 = 100_000_0000000 + collateral_out;
+    let vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &id);
+    assert_eq!(vault.total_debt, 5_000_0000000);
+    assert_eq!(
+        vault.total_collateral,
+        total_collateral - calc_fee(&data.fee, &total_collateral)
+    );
+
+    // The depositor never touches the new stablecoins
+    assert_eq!(data.stable_token_client.balance(&depositor), 0);
+    assert_eq!(data.collateral_token_client.balance(&depositor), 0);
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+    assert_eq!(vaults_info.total_vaults, 1);
+    assert_eq!(vaults_info.total_debt, 5_000_0000000);
+    assert_eq!(vaults_info.total_col, vault.total_collateral);
+}
+
+#[test]
+fn test_swaps_under_the_opening_rate() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let depositor: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&depositor, &100_000_0000000);
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &depositor,
+        &7_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    // The price drops and the vault is now between the min and the opening collateral ratio
+    let rate: u128 = 800000;
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &(rate as i128),
+    );
+
+    let vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
+    let vault_key: VaultKey = VaultKey {
+        index: vault.index,
+        account: depositor.clone(),
+        denomination: data.stable_token_denomination.clone(),
+        id: 0,
+    };
+    let ratio: u128 = rate * vault.total_collateral / vault.total_debt;
+    assert!(ratio > base_variables.min_col_rate && ratio < base_variables.opening_col_rate);
+
+    set_swap_adapter(&env, &data, rate);
+
+    // It can't take more debt
+    let leverage_error = data
+        .contract_client
+        .try_leverage(
+            &OptionalVaultKey::None,
+            &vault_key,
+            &OptionalVaultKey::None,
+            &100_0000000,
+            &0,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(leverage_error, SCErrors::CollateralRateUnderMinimum.into());
+
+    // A swap at a price bad enough to lower the ratio is rejected too
+    set_swap_adapter(&env, &data, rate / 2);
+    let bad_swap_error = data
+        .contract_client
+        .try_deleverage(
+            &OptionalVaultKey::None,
+            &vault_key,
+            &OptionalVaultKey::None,
+            &10_000_0000000,
+            &0,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(bad_swap_error, SCErrors::CollateralRateUnderMinimum.into());
+
+    // But it can be deleveraged even if it's still under the opening ratio after it
+    set_swap_adapter(&env, &data, rate);
+    data.contract_client.deleverage(
+        &OptionalVaultKey::None,
+        &vault_key,
+        &OptionalVaultKey::None,
+        &5_000_0000000,
+        &0,
+    );
+
+    let vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
+    let new_ratio: u128 = rate * vault.total_collateral / vault.total_debt;
+    assert!(new_ratio > ratio && new_ratio < base_variables.opening_col_rate);
+}
@@ -54,6 +54,7 @@ fn test_liquidation() {
         ),
         account: depositor.clone(),
         denomination: data.stable_token_denomination.clone(),
+        id: 0,
     };
     data.contract_client.new_vault(
         &OptionalVaultKey::None,
@@ -132,7 +133,7 @@ fn test_liquidation() {
     // The depositor's vault should be removed from the protocol
     let vault_doesnt_exist_result = data
         .contract_client
-        .try_get_vault(&depositor, &data.stable_token_denomination)
+        .try_get_vault(&depositor, &data.stable_token_denomination, &0)
         .unwrap_err();
 
     assert_eq!(
@@ -309,3 +310,202 @@ fn test_vaults_to_liquidate() {
 
     assert_eq!(current_vaults_to_liquidate.len(), 2);
 }
+
+// Creates three vaults (the first two can be liquidated after the price drops to 531953) and drops the price
+// The order in the list is: depositor_1, depositor_2, depositor_3
+fn setup_liquidatable_vaults(env: &Env, data: &TestData) -> (Vault, Vault, Vault) {
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let depositor_1: Address = Address::generate(&env);
+    let depositor_2: Address = Address::generate(&env);
+    let depositor_3: Address = Address::generate(&env);
+
+    for (depositor, collateral) in [
+        (&depositor_3, 500_000_0000000u128),
+        (&depositor_2, 100_000_0000000u128),
+        (&depositor_1, 100_000_0000000u128),
+    ] {
+        data.collateral_token_admin_client
+            .mint(depositor, &(collateral as i128));
+
+        data.contract_client.new_vault(
+            &OptionalVaultKey::None,
+            depositor,
+            &5_000_0000000,
+            &collateral,
+            &data.stable_token_denomination,
+        );
+    }
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &531953,
+    );
+
+    (
+        data.contract_client
+            .get_vault(&depositor_1, &data.stable_token_denomination, &0),
+        data.contract_client
+            .get_vault(&depositor_2, &data.stable_token_denomination, &0),
+        data.contract_client
+            .get_vault(&depositor_3, &data.stable_token_denomination, &0),
+    )
+}
+
+fn key_of(vault: &Vault) -> VaultKey {
+    VaultKey {
+        index: vault.index,
+        account: vault.account.clone(),
+        denomination: vault.denomination.clone(),
+        id: vault.id,
+    }
+}
+
+#[test]
+fn test_liquidate_target_vaults() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let (vault_1, vault_2, vault_3) = setup_liquidatable_vaults(&env, &data);
+
+    let liquidator: Address = Address::generate(&env);
+    data.stable_token_admin_client
+        .mint(&liquidator, &10_000_0000000);
+
+    // The second vault is liquidated, the healthy one and the one over the cap are skipped
+    let liquidated_vaults: Vec<Vault> = data.contract_client.liquidate_vaults(
+        &liquidator,
+        &data.stable_token_denomination,
+        &vec![
+            &env,
+            LiquidationTarget {
+                prev_key: OptionalVaultKey::Some(key_of(&vault_2)),
+                vault_key: key_of(&vault_3),
+            },
+            LiquidationTarget {
+                prev_key: OptionalVaultKey::Some(key_of(&vault_1)),
+                vault_key: key_of(&vault_2),
+            },
+            LiquidationTarget {
+                prev_key: OptionalVaultKey::None,
+                vault_key: key_of(&vault_1),
+            },
+        ],
+        &5_000_0000000,
+    );
+
+    assert_eq!(liquidated_vaults, vec![&env, vault_2.clone()]);
+    assert_eq!(data.stable_token_client.balance(&liquidator), 5_000_0000000);
+    assert_eq!(
+        data.collateral_token_client.balance(&liquidator) as u128,
+        vault_2.total_collateral - calc_fee(&data.fee, &vault_2.total_collateral)
+    );
+
+    let updated_vault_1: Vault =
+        data.contract_client
+            .get_vault(&vault_1.account, &data.stable_token_denomination, &0);
+
+    assert_eq!(
+        updated_vault_1.next_key,
+        OptionalVaultKey::Some(key_of(&vault_3))
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+
+    assert_eq!(vaults_info.total_vaults, 2);
+    assert_eq!(
+        vaults_info.lowest_key,
+        OptionalVaultKey::Some(key_of(&vault_1))
+    );
+
+    // A target which was already liquidated is skipped
+    let liquidated_vaults: Vec<Vault> = data.contract_client.liquidate_vaults(
+        &liquidator,
+        &data.stable_token_denomination,
+        &vec![
+            &env,
+            LiquidationTarget {
+                prev_key: OptionalVaultKey::Some(key_of(&vault_1)),
+                vault_key: key_of(&vault_2),
+            },
+            LiquidationTarget {
+                prev_key: OptionalVaultKey::None,
+                vault_key: key_of(&vault_1),
+            },
+        ],
+        &5_000_0000000,
+    );
+
+    assert_eq!(liquidated_vaults.len(), 1);
+    assert_eq!(liquidated_vaults.get(0).unwrap().account, vault_1.account);
+    assert_eq!(data.stable_token_client.balance(&liquidator), 0);
+}
+
+#[test]
+fn test_liquidate_up_to() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let (vault_1, vault_2, vault_3) = setup_liquidatable_vaults(&env, &data);
+
+    let liquidator: Address = Address::generate(&env);
+    data.stable_token_admin_client
+        .mint(&liquidator, &10_000_0000000);
+
+    // The cap only allows one vault so it stops there instead of panicking
+    let liquidated_vaults: Vec<Vault> = data.contract_client.liquidate_up_to(
+        &liquidator,
+        &data.stable_token_denomination,
+        &3,
+        &7_000_0000000,
+    );
+
+    assert_eq!(liquidated_vaults, vec![&env, vault_1.clone()]);
+    assert_eq!(data.stable_token_client.balance(&liquidator), 5_000_0000000);
+
+    // It stops at the first vault that can't be liquidated
+    let liquidated_vaults: Vec<Vault> = data.contract_client.liquidate_up_to(
+        &liquidator,
+        &data.stable_token_denomination,
+        &5,
+        &10_000_0000000,
+    );
+
+    assert_eq!(liquidated_vaults, vec![&env, vault_2.clone()]);
+    assert_eq!(data.stable_token_client.balance(&liquidator), 0);
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+
+    assert_eq!(vaults_info.total_vaults, 1);
+    assert_eq!(
+        vaults_info.lowest_key,
+        OptionalVaultKey::Some(key_of(&vault_3))
+    );
+
+    // Nothing to liquidate is not an error
+    let liquidated_vaults: Vec<Vault> = data.contract_client.liquidate_up_to(
+        &liquidator,
+        &data.stable_token_denomination,
+        &1,
+        &10_000_0000000,
+    );
+
+    assert_eq!(liquidated_vaults.len(), 0);
+}
@@ -0,0 +1,640 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::errors::{SCErrors, SCErrorsExt};
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use crate::utils::vaults::calculate_vault_index;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{vec, Address, Env};
+
+fn key_of(vault: &Vault) -> VaultKey {
+    VaultKey {
+        index: vault.index,
+        account: vault.account.clone(),
+        denomination: vault.denomination.clone(),
+        id: vault.id,
+    }
+}
+
+#[test]
+fn test_merge_and_split_vaults() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let owner: Address = Address::generate(&env);
+    let other: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&owner, &300_000_0000000);
+    data.collateral_token_admin_client
+        .mint(&other, &200_000_0000000);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &6_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &5_000_0000000,
+        &80_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    let first_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0);
+    let second_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &1);
+
+    // Vaults of different owners can't be merged
+    let other_id: u32 = data.contract_client.new_vault(
+        &OptionalVaultKey::Some(key_of(&first_vault)),
+        &other,
+        &5_000_0000000,
+        &200_000_0000000,
+        &data.stable_token_denomination,
+    );
+    let other_vault: Vault =
+        data.contract_client
+            .get_vault(&other, &data.stable_token_denomination, &other_id);
+
+    assert_eq!(
+        data.contract_client
+            .try_merge_vaults(
+                &OptionalVaultKey::Some(key_of(&first_vault)),
+                &key_of(&other_vault),
+                &OptionalVaultKey::Some(key_of(&second_vault)),
+                &key_of(&first_vault),
+                &OptionalVaultKey::None,
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrorsExt::InvalidVaultMerge.into()
+    );
+
+    // The second vault is the lowest and it comes right before the first one
+    data.contract_client.merge_vaults(
+        &OptionalVaultKey::None,
+        &key_of(&second_vault),
+        &OptionalVaultKey::Some(key_of(&second_vault)),
+        &key_of(&first_vault),
+        &OptionalVaultKey::None,
+    );
+
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&owner, &data.stable_token_denomination),
+        vec![&env, 0]
+    );
+
+    let merged_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0);
+    assert_eq!(
+        merged_vault.total_debt,
+        first_vault.total_debt + second_vault.total_debt
+    );
+    assert_eq!(
+        merged_vault.total_collateral,
+        first_vault.total_collateral + second_vault.total_collateral
+    );
+    assert_eq!(
+        merged_vault.next_key,
+        OptionalVaultKey::Some(key_of(&other_vault))
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+    assert_eq!(vaults_info.total_vaults, 2);
+    assert_eq!(
+        vaults_info.total_col,
+        merged_vault.total_collateral + other_vault.total_collateral
+    );
+    assert_eq!(
+        vaults_info.lowest_key,
+        OptionalVaultKey::Some(key_of(&merged_vault))
+    );
+
+    // Both vaults need to have at least the min debt
+    assert_eq!(
+        data.contract_client
+            .try_split_vault(
+                &OptionalVaultKey::None,
+                &key_of(&merged_vault),
+                &OptionalVaultKey::None,
+                &other,
+                &7_000_0000000,
+                &90_000_0000000,
+                &OptionalVaultKey::None,
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrors::InvalidMinDebtAmount.into()
+    );
+
+    // The new vault has a lower collateral ratio than the remaining one so it's the new lowest
+    let new_id: u32 = data.contract_client.split_vault(
+        &OptionalVaultKey::None,
+        &key_of(&merged_vault),
+        &OptionalVaultKey::None,
+        &other,
+        &6_000_0000000,
+        &90_000_0000000,
+        &OptionalVaultKey::None,
+    );
+
+    assert_eq!(new_id, 1);
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&other, &data.stable_token_denomination),
+        vec![&env, 0, 1]
+    );
+
+    let updated_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0);
+    let new_vault: Vault =
+        data.contract_client
+            .get_vault(&other, &data.stable_token_denomination, &1);
+
+    assert_eq!(updated_vault.total_debt, 5_000_0000000);
+    assert_eq!(
+        updated_vault.total_collateral,
+        merged_vault.total_collateral - 90_000_0000000
+    );
+    assert_eq!(new_vault.total_debt, 6_000_0000000);
+    assert_eq!(new_vault.total_collateral, 90_000_0000000);
+    assert_eq!(
+        new_vault.next_key,
+        OptionalVaultKey::Some(key_of(&updated_vault))
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+    assert_eq!(vaults_info.total_vaults, 3);
+    assert_eq!(
+        vaults_info.total_col,
+        merged_vault.total_collateral + other_vault.total_collateral
+    );
+    assert_eq!(
+        vaults_info.lowest_key,
+        OptionalVaultKey::Some(key_of(&new_vault))
+    );
+}
+
+#[test]
+fn test_merge_and_split_need_an_active_currency_and_no_panic_mode() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let owner: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&owner, &300_000_0000000);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &12_000_0000000,
+        &200_000_0000000,
+        &data.stable_token_denomination,
+    );
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &5_000_0000000,
+        &80_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    let first_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0);
+    let second_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &1);
+
+    data.contract_client.set_panic(&true);
+
+    assert_eq!(
+        data.contract_client
+            .try_merge_vaults(
+                &OptionalVaultKey::None,
+                &key_of(&second_vault),
+                &OptionalVaultKey::Some(key_of(&second_vault)),
+                &key_of(&first_vault),
+                &OptionalVaultKey::None,
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrors::PanicModeEnabled.into()
+    );
+    assert_eq!(
+        data.contract_client
+            .try_split_vault(
+                &OptionalVaultKey::Some(key_of(&second_vault)),
+                &key_of(&first_vault),
+                &OptionalVaultKey::Some(key_of(&second_vault)),
+                &owner,
+                &6_000_0000000,
+                &100_000_0000000,
+                &OptionalVaultKey::Some(key_of(&second_vault)),
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrors::PanicModeEnabled.into()
+    );
+
+    data.contract_client.set_panic(&false);
+    data.contract_client
+        .toggle_currency(&data.stable_token_denomination, &false);
+
+    assert_eq!(
+        data.contract_client
+            .try_merge_vaults(
+                &OptionalVaultKey::None,
+                &key_of(&second_vault),
+                &OptionalVaultKey::Some(key_of(&second_vault)),
+                &key_of(&first_vault),
+                &OptionalVaultKey::None,
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrors::CurrencyIsInactive.into()
+    );
+    assert_eq!(
+        data.contract_client
+            .try_split_vault(
+                &OptionalVaultKey::Some(key_of(&second_vault)),
+                &key_of(&first_vault),
+                &OptionalVaultKey::Some(key_of(&second_vault)),
+                &owner,
+                &6_000_0000000,
+                &100_000_0000000,
+                &OptionalVaultKey::Some(key_of(&second_vault)),
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrors::CurrencyIsInactive.into()
+    );
+}
+
+#[test]
+fn test_merge_source_right_before_the_target() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let owner: Address = Address::generate(&env);
+    let other: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&owner, &300_000_0000000);
+    data.collateral_token_admin_client
+        .mint(&other, &100_000_0000000);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &other,
+        &5_000_0000000,
+        &70_000_0000000,
+        &data.stable_token_denomination,
+    );
+    let lowest_vault: Vault =
+        data.contract_client
+            .get_vault(&other, &data.stable_token_denomination, &0);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::Some(key_of(&lowest_vault)),
+        &owner,
+        &6_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+    data.contract_client.new_vault(
+        &OptionalVaultKey::Some(key_of(&lowest_vault)),
+        &owner,
+        &5_000_0000000,
+        &80_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    let target_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0);
+    let source_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &1);
+
+    // The order is: lowest vault -> source vault -> target vault
+    assert_eq!(
+        data.contract_client.get_vaults(
+            &OptionalVaultKey::None,
+            &data.stable_token_denomination,
+            &3,
+            &false,
+        ),
+        vec![
+            &env,
+            data.contract_client
+                .get_vault(&other, &data.stable_token_denomination, &0),
+            source_vault.clone(),
+            target_vault.clone()
+        ]
+    );
+
+    data.contract_client.merge_vaults(
+        &OptionalVaultKey::Some(key_of(&lowest_vault)),
+        &key_of(&source_vault),
+        &OptionalVaultKey::Some(key_of(&source_vault)),
+        &key_of(&target_vault),
+        &OptionalVaultKey::Some(key_of(&lowest_vault)),
+    );
+
+    let merged_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0);
+    assert_eq!(
+        merged_vault.total_debt,
+        source_vault.total_debt + target_vault.total_debt
+    );
+    assert_eq!(
+        merged_vault.total_collateral,
+        source_vault.total_collateral + target_vault.total_collateral
+    );
+    assert_eq!(merged_vault.next_key, OptionalVaultKey::None);
+    assert_eq!(
+        data.contract_client
+            .get_vault(&other, &data.stable_token_denomination, &0)
+            .next_key,
+        OptionalVaultKey::Some(key_of(&merged_vault))
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+    assert_eq!(vaults_info.total_vaults, 2);
+    assert_eq!(
+        vaults_info.lowest_key,
+        OptionalVaultKey::Some(key_of(&lowest_vault))
+    );
+    assert_eq!(
+        data.contract_client.get_vaults(
+            &OptionalVaultKey::None,
+            &data.stable_token_denomination,
+            &2,
+            &false,
+        ),
+        vec![
+            &env,
+            data.contract_client
+                .get_vault(&other, &data.stable_token_denomination, &0),
+            merged_vault
+        ]
+    );
+}
+
+#[test]
+fn test_merge_lowest_source_into_a_later_target() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let owner: Address = Address::generate(&env);
+    let other: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&owner, &300_000_0000000);
+    data.collateral_token_admin_client
+        .mint(&other, &100_000_0000000);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &5_000_0000000,
+        &70_000_0000000,
+        &data.stable_token_denomination,
+    );
+    let source_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::Some(key_of(&source_vault)),
+        &other,
+        &5_000_0000000,
+        &80_000_0000000,
+        &data.stable_token_denomination,
+    );
+    let middle_vault: Vault =
+        data.contract_client
+            .get_vault(&other, &data.stable_token_denomination, &0);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::Some(key_of(&middle_vault)),
+        &owner,
+        &6_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+    let target_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &1);
+    let source_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0);
+
+    // The order is: source vault -> middle vault -> target vault, and the merged vault goes back to the start
+    data.contract_client.merge_vaults(
+        &OptionalVaultKey::None,
+        &key_of(&source_vault),
+        &OptionalVaultKey::Some(key_of(&middle_vault)),
+        &key_of(&target_vault),
+        &OptionalVaultKey::None,
+    );
+
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&owner, &data.stable_token_denomination),
+        vec![&env, 1]
+    );
+
+    let merged_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &1);
+    let middle_vault: Vault =
+        data.contract_client
+            .get_vault(&other, &data.stable_token_denomination, &0);
+    assert_eq!(
+        merged_vault.total_debt,
+        source_vault.total_debt + target_vault.total_debt
+    );
+    assert_eq!(
+        merged_vault.next_key,
+        OptionalVaultKey::Some(key_of(&middle_vault))
+    );
+    assert_eq!(middle_vault.next_key, OptionalVaultKey::None);
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+    assert_eq!(vaults_info.total_vaults, 2);
+    assert_eq!(
+        vaults_info.lowest_key,
+        OptionalVaultKey::Some(key_of(&merged_vault))
+    );
+    assert_eq!(
+        vaults_info.total_col,
+        merged_vault.total_collateral + middle_vault.total_collateral
+    );
+}
+
+#[test]
+fn test_split_new_vault_right_after_the_updated_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let owner: Address = Address::generate(&env);
+    let other: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&owner, &200_000_0000000);
+    data.collateral_token_admin_client
+        .mint(&other, &100_000_0000000);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &other,
+        &5_000_0000000,
+        &70_000_0000000,
+        &data.stable_token_denomination,
+    );
+    let lowest_vault: Vault =
+        data.contract_client
+            .get_vault(&other, &data.stable_token_denomination, &0);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::Some(key_of(&lowest_vault)),
+        &owner,
+        &10_000_0000000,
+        &200_000_0000000,
+        &data.stable_token_denomination,
+    );
+    let vault: Vault = data
+        .contract_client
+        .get_vault(&owner, &data.stable_token_denomination, &0);
+
+    // The updated vault keeps the lower collateral ratio so the new vault goes right after it
+    let updated_debt: u128 = vault.total_debt - 5_000_0000000;
+    let updated_collateral: u128 = vault.total_collateral - 120_000_0000000;
+    let updated_key: VaultKey = VaultKey {
+        index: env.as_contract(&data.contract_client.address, || {
+            calculate_vault_index(
+                &env,
+                &data.stable_token_denomination,
+                updated_debt,
+                updated_collateral,
+            )
+        }),
+        account: owner.clone(),
+        denomination: data.stable_token_denomination.clone(),
+        id: 0,
+    };
+
+    let new_id: u32 = data.contract_client.split_vault(
+        &OptionalVaultKey::Some(key_of(&lowest_vault)),
+        &key_of(&vault),
+        &OptionalVaultKey::Some(key_of(&lowest_vault)),
+        &owner,
+        &5_000_0000000,
+        &120_000_0000000,
+        &OptionalVaultKey::Some(updated_key.clone()),
+    );
+
+    assert_eq!(new_id, 1);
+
+    let updated_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0);
+    let new_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &1);
+
+    assert_eq!(key_of(&updated_vault), updated_key);
+    assert_eq!(updated_vault.total_debt, updated_debt);
+    assert_eq!(updated_vault.total_collateral, updated_collateral);
+    assert_eq!(
+        updated_vault.next_key,
+        OptionalVaultKey::Some(key_of(&new_vault))
+    );
+    assert_eq!(new_vault.next_key, OptionalVaultKey::None);
+    assert_eq!(
+        data.contract_client
+            .get_vault(&other, &data.stable_token_denomination, &0)
+            .next_key,
+        OptionalVaultKey::Some(updated_key)
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+    assert_eq!(vaults_info.total_vaults, 3);
+    assert_eq!(
+        vaults_info.lowest_key,
+        OptionalVaultKey::Some(key_of(&lowest_vault))
+    );
+}
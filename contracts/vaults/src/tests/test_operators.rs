@@ -0,0 +1,281 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::errors::SCErrors;
+use crate::storage::operators::OperatorApproval;
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use crate::utils::payments::calc_fee;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{Address, Env};
+
+fn current_vault_key(data: &TestData, owner: &Address, id: u32) -> VaultKey {
+    let vault: Vault = data
+        .contract_client
+        .get_vault(&owner, &data.stable_token_denomination, &id);
+
+    VaultKey {
+        index: vault.index,
+        account: vault.account,
+        denomination: vault.denomination,
+        id: vault.id,
+    }
+}
+
+#[test]
+fn test_operator_approvals() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let owner: Address = Address::generate(&env);
+    let operator: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&owner, &100_000_0000000);
+    data.collateral_token_admin_client
+        .mint(&operator, &10_000_0000000);
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &5_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    let not_approved_error = data
+        .contract_client
+        .try_increase_collateral_as_operator(
+            &operator,
+            &OptionalVaultKey::None,
+            &current_vault_key(&data, &owner, 0),
+            &OptionalVaultKey::None,
+            &10_000_0000000,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(not_approved_error, SCErrors::OperatorIsNotApproved.into());
+
+    let approval: OperatorApproval = OperatorApproval {
+        deposit: true,
+        repay: false,
+        withdraw_allowance: 0,
+        mint_allowance: 1_000_0000000,
+        expiration: env.ledger().timestamp() + 3600,
+    };
+    data.contract_client.approve_operator(
+        &owner,
+        &data.stable_token_denomination,
+        &0,
+        &operator,
+        &approval,
+    );
+
+    // The operator pays for the collateral it adds
+    data.contract_client.increase_collateral_as_operator(
+        &operator,
+        &OptionalVaultKey::None,
+        &current_vault_key(&data, &owner, 0),
+        &OptionalVaultKey::None,
+        &10_000_0000000,
+    );
+
+    let vault: Vault = data
+        .contract_client
+        .get_vault(&owner, &data.stable_token_denomination, &0);
+    assert_eq!(
+        vault.total_collateral,
+        110_000_0000000
+            - calc_fee(&data.fee, &100_000_0000000)
+            - calc_fee(&data.fee, &10_000_0000000)
+    );
+    assert_eq!(data.collateral_token_client.balance(&operator), 0);
+
+    // Minted stablecoins always go to the owner and are taken from the allowance
+    data.contract_client.increase_debt_as_operator(
+        &operator,
+        &OptionalVaultKey::None,
+        &current_vault_key(&data, &owner, 0),
+        &OptionalVaultKey::None,
+        &600_0000000,
+    );
+
+    assert_eq!(data.stable_token_client.balance(&owner), 5_600_0000000);
+    assert_eq!(data.stable_token_client.balance(&operator), 0);
+    assert_eq!(
+        data.contract_client
+            .get_operator_approval(&owner, &data.stable_token_denomination, &0, &operator)
+            .unwrap()
+            .mint_allowance,
+        400_0000000
+    );
+
+    let allowance_error = data
+        .contract_client
+        .try_increase_debt_as_operator(
+            &operator,
+            &OptionalVaultKey::None,
+            &current_vault_key(&data, &owner, 0),
+            &OptionalVaultKey::None,
+            &500_0000000,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(allowance_error, SCErrors::OperatorAllowanceExceeded.into());
+
+    let withdraw_error = data
+        .contract_client
+        .try_withdraw_collateral_as_operator(
+            &operator,
+            &OptionalVaultKey::None,
+            &current_vault_key(&data, &owner, 0),
+            &OptionalVaultKey::None,
+            &1_0000000,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(withdraw_error, SCErrors::OperatorAllowanceExceeded.into());
+
+    data.stable_token_admin_client.mint(&operator, &100_0000000);
+    let repay_error = data
+        .contract_client
+        .try_pay_debt_as_operator(
+            &operator,
+            &OptionalVaultKey::None,
+            &current_vault_key(&data, &owner, 0),
+            &OptionalVaultKey::None,
+            &100_0000000,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(repay_error, SCErrors::OperatorIsNotApproved.into());
+
+    // Once the approval expires the operator can't do anything
+    env.ledger().with_mut(|ledger| ledger.timestamp += 3600);
+    let expired_error = data
+        .contract_client
+        .try_increase_collateral_as_operator(
+            &operator,
+            &OptionalVaultKey::None,
+            &current_vault_key(&data, &owner, 0),
+            &OptionalVaultKey::None,
+            &10_000_0000000,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(expired_error, SCErrors::OperatorIsNotApproved.into());
+
+    data.contract_client
+        .revoke_operator(&owner, &data.stable_token_denomination, &0, &operator);
+    assert_eq!(
+        data.contract_client.get_operator_approval(
+            &owner,
+            &data.stable_token_denomination,
+            &0,
+            &operator
+        ),
+        None
+    );
+}
+
+#[test]
+fn test_operator_approval_covers_a_single_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let owner: Address = Address::generate(&env);
+    let operator: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&owner, &220_000_0000000);
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &5_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    // The second vault has a higher collateral ratio so it goes after the first one
+    data.contract_client.new_vault(
+        &OptionalVaultKey::Some(current_vault_key(&data, &owner, 0)),
+        &owner,
+        &5_000_0000000,
+        &120_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    data.contract_client.approve_operator(
+        &owner,
+        &data.stable_token_denomination,
+        &0,
+        &operator,
+        &OperatorApproval {
+            deposit: false,
+            repay: false,
+            withdraw_allowance: 1_000_0000000,
+            mint_allowance: 0,
+            expiration: env.ledger().timestamp() + 3600,
+        },
+    );
+
+    let other_vault_error = data
+        .contract_client
+        .try_withdraw_collateral_as_operator(
+            &operator,
+            &OptionalVaultKey::Some(current_vault_key(&data, &owner, 0)),
+            &current_vault_key(&data, &owner, 1),
+            &OptionalVaultKey::Some(current_vault_key(&data, &owner, 0)),
+            &1_000_0000000,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(other_vault_error, SCErrors::OperatorIsNotApproved.into());
+    assert_eq!(
+        data.contract_client.get_operator_approval(
+            &owner,
+            &data.stable_token_denomination,
+            &1,
+            &operator
+        ),
+        None
+    );
+
+    // The approved vault can still be used, the collateral goes to the owner
+    data.contract_client.withdraw_collateral_as_operator(
+        &operator,
+        &OptionalVaultKey::None,
+        &current_vault_key(&data, &owner, 0),
+        &OptionalVaultKey::None,
+        &1_000_0000000,
+    );
+
+    assert_eq!(data.collateral_token_client.balance(&owner), 1_000_0000000);
+    assert_eq!(data.collateral_token_client.balance(&operator), 0);
+}
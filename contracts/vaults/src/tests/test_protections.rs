@@ -0,0 +1,293 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::errors::SCErrorsExt;
+use crate::storage::protections::{ProtectionAction, ProtectionOrder};
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use crate::utils::payments::calc_fee;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{vec, Address, Env};
+
+#[test]
+fn test_protection_orders() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let owner: Address = Address::generate(&env);
+    let keeper: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&owner, &120_000_0000000);
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &6_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    let invalid_order_error = data
+        .contract_client
+        .try_add_protection_order(
+            &owner,
+            &data.stable_token_denomination,
+            &0,
+            &ProtectionOrder {
+                action: ProtectionAction::Repay,
+                trigger_rate: 1_5000000,
+                amount: 10_0000000,
+                tip: 10_0000000,
+            },
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        invalid_order_error,
+        SCErrorsExt::InvalidProtectionOrder.into()
+    );
+
+    let repay_order: ProtectionOrder = ProtectionOrder {
+        action: ProtectionAction::Repay,
+        trigger_rate: 1_5000000,
+        amount: 1_000_0000000,
+        tip: 10_0000000,
+    };
+    let add_collateral_order: ProtectionOrder = ProtectionOrder {
+        action: ProtectionAction::AddCollateral,
+        trigger_rate: 1_3000000,
+        amount: 20_000_0000000,
+        tip: 100_0000000,
+    };
+    data.contract_client.add_protection_order(
+        &owner,
+        &data.stable_token_denomination,
+        &0,
+        &repay_order,
+    );
+    data.contract_client.add_protection_order(
+        &owner,
+        &data.stable_token_denomination,
+        &0,
+        &add_collateral_order,
+    );
+
+    assert_eq!(
+        data.contract_client
+            .get_protection_orders(&owner, &data.stable_token_denomination, &0),
+        vec![&env, repay_order.clone(), add_collateral_order.clone()]
+    );
+    assert_eq!(data.stable_token_client.balance(&owner), 5_000_0000000);
+    assert_eq!(data.collateral_token_client.balance(&owner), 0);
+
+    let not_triggered_error = data
+        .contract_client
+        .try_execute_protection(
+            &keeper,
+            &owner,
+            &data.stable_token_denomination,
+            &0,
+            &OptionalVaultKey::None,
+            &OptionalVaultKey::None,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        not_triggered_error,
+        SCErrorsExt::ProtectionIsNotTriggered.into()
+    );
+
+    // The ratio is now ~1.16 so both orders can be executed, the first one is used
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &700000,
+    );
+    data.contract_client.execute_protection(
+        &keeper,
+        &owner,
+        &data.stable_token_denomination,
+        &0,
+        &OptionalVaultKey::None,
+        &OptionalVaultKey::None,
+    );
+
+    let vault: Vault = data
+        .contract_client
+        .get_vault(&owner, &data.stable_token_denomination, &0);
+    assert_eq!(vault.total_debt, 5_010_0000000);
+    assert_eq!(data.stable_token_client.balance(&keeper), 10_0000000);
+    assert_eq!(
+        data.contract_client
+            .get_protection_orders(&owner, &data.stable_token_denomination, &0),
+        vec![&env, add_collateral_order.clone()]
+    );
+
+    // After the repayment the ratio is ~1.39, over the trigger of the order left
+    assert_eq!(
+        data.contract_client
+            .try_execute_protection(
+                &keeper,
+                &owner,
+                &data.stable_token_denomination,
+                &0,
+                &OptionalVaultKey::None,
+                &OptionalVaultKey::None
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrorsExt::ProtectionIsNotTriggered.into()
+    );
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &600000,
+    );
+    data.contract_client.execute_protection(
+        &keeper,
+        &owner,
+        &data.stable_token_denomination,
+        &0,
+        &OptionalVaultKey::None,
+        &OptionalVaultKey::None,
+    );
+
+    let added_collateral: u128 = 19_900_0000000 - calc_fee(&data.fee, &19_900_0000000);
+    let updated_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0);
+    assert_eq!(
+        updated_vault.total_collateral,
+        vault.total_collateral + added_collateral
+    );
+    assert_eq!(data.collateral_token_client.balance(&keeper), 100_0000000);
+    assert_eq!(
+        data.contract_client
+            .get_protection_orders(&owner, &data.stable_token_denomination, &0)
+            .len(),
+        0
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+    assert_eq!(vaults_info.total_debt, 5_010_0000000);
+    assert_eq!(vaults_info.total_col, updated_vault.total_collateral);
+
+    // Canceling an order returns the escrow to the owner
+    data.contract_client.add_protection_order(
+        &owner,
+        &data.stable_token_denomination,
+        &0,
+        &repay_order,
+    );
+    assert_eq!(data.stable_token_client.balance(&owner), 4_000_0000000);
+    data.contract_client
+        .cancel_protection_order(&owner, &data.stable_token_denomination, &0, &0);
+    assert_eq!(data.stable_token_client.balance(&owner), 5_000_0000000);
+
+    assert_eq!(
+        data.contract_client
+            .try_cancel_protection_order(&owner, &data.stable_token_denomination, &0, &0)
+            .unwrap_err()
+            .unwrap(),
+        SCErrorsExt::ProtectionOrderDoesntExist.into()
+    );
+}
+
+#[test]
+fn test_repay_protection_on_a_vault_at_the_min_debt() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let owner: Address = Address::generate(&env);
+    let keeper: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&owner, &90_000_0000000);
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &5_000_0000000,
+        &90_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    let repay_order: ProtectionOrder = ProtectionOrder {
+        action: ProtectionAction::Repay,
+        trigger_rate: 1_5000000,
+        amount: 1_000_0000000,
+        tip: 10_0000000,
+    };
+    data.contract_client.add_protection_order(
+        &owner,
+        &data.stable_token_denomination,
+        &0,
+        &repay_order,
+    );
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &700000,
+    );
+
+    let vault: Vault = data
+        .contract_client
+        .get_vault(&owner, &data.stable_token_denomination, &0);
+
+    // The vault can't repay anything without going under the min debt so the keeper can't use the order
+    assert_eq!(
+        data.contract_client
+            .try_execute_protection(
+                &keeper,
+                &owner,
+                &data.stable_token_denomination,
+                &0,
+                &OptionalVaultKey::None,
+                &OptionalVaultKey::None,
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrorsExt::ProtectionIsNotTriggered.into()
+    );
+
+    assert_eq!(data.stable_token_client.balance(&keeper), 0);
+    assert_eq!(
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0),
+        vault
+    );
+    assert_eq!(
+        data.contract_client
+            .get_protection_orders(&owner, &data.stable_token_denomination, &0),
+        vec![&env, repay_order]
+    );
+}
@@ -75,9 +75,9 @@ fn test_redeem() {
         &data.stable_token_denomination,
     );
 
-    let depositor_1_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_1, &data.stable_token_denomination);
+    let depositor_1_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_1, &data.stable_token_denomination, &0);
 
     assert_eq!(depositor_1_vault.index, depositor_1_index);
 
@@ -92,9 +92,9 @@ fn test_redeem() {
         &data.stable_token_denomination,
     );
 
-    let depositor_2_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_2, &data.stable_token_denomination);
+    let depositor_2_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_2, &data.stable_token_denomination, &0);
 
     assert_eq!(depositor_2_vault.index, depositor_2_index);
 
@@ -106,6 +106,7 @@ fn test_redeem() {
             index: depositor_2_index.clone(),
             account: depositor_2.clone(),
             denomination: data.stable_token_denomination.clone(),
+            id: 0,
         }),
         &depositor_3,
         &depositor_3_debt,
@@ -113,9 +114,9 @@ fn test_redeem() {
         &data.stable_token_denomination,
     );
 
-    let depositor_3_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_3, &data.stable_token_denomination);
+    let depositor_3_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_3, &data.stable_token_denomination, &0);
 
     assert_eq!(depositor_3_vault.index, depositor_3_index);
 
@@ -127,6 +128,7 @@ fn test_redeem() {
             index: depositor_3_index.clone(),
             account: depositor_3.clone(),
             denomination: data.stable_token_denomination.clone(),
+            id: 0,
         }),
         &depositor_4,
         &depositor_4_debt,
@@ -134,9 +136,9 @@ fn test_redeem() {
         &data.stable_token_denomination,
     );
 
-    let depositor_4_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_4, &data.stable_token_denomination);
+    let depositor_4_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_4, &data.stable_token_denomination, &0);
 
     assert_eq!(depositor_4_vault.index, depositor_4_index);
 
@@ -285,6 +287,7 @@ fn test_redeem() {
             index: depositor_4_index,
             account: depositor_4,
             denomination: data.stable_token_denomination.clone(),
+            id: 0,
         }),
         &25_0000000,
     );
@@ -303,9 +306,9 @@ fn test_redeem() {
 
     assert_eq!(lowest_vault.account, depositor_4_vault.account);
 
-    let updated_depositor_3_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_3, &data.stable_token_denomination);
+    let updated_depositor_3_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_3, &data.stable_token_denomination, &0);
 
     assert_eq!(
         updated_depositor_3_vault.total_debt,
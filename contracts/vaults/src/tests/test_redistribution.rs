@@ -0,0 +1,261 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::errors::SCErrors;
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use crate::utils::payments::calc_fee;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env};
+
+fn assert_approx(value: u128, expected: u128) {
+    assert!(
+        value.abs_diff(expected) <= 100,
+        "value {} is not close to {}",
+        value,
+        expected
+    );
+}
+
+// Creates two vaults that can be liquidated after the price drops to 531953 and a healthy one
+fn setup_vaults(env: &Env, data: &TestData) -> (Address, Address, Address) {
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let depositor_1: Address = Address::generate(&env);
+    let depositor_2: Address = Address::generate(&env);
+    let depositor_3: Address = Address::generate(&env);
+
+    // Each new vault has a lower or equal index than the previous ones so it's always the new lowest
+    for (depositor, collateral) in [
+        (&depositor_3, 500_000_0000000u128),
+        (&depositor_2, 100_000_0000000u128),
+        (&depositor_1, 100_000_0000000u128),
+    ] {
+        data.collateral_token_admin_client
+            .mint(depositor, &(collateral as i128 * 2));
+
+        data.contract_client.new_vault(
+            &OptionalVaultKey::None,
+            depositor,
+            &5_000_0000000,
+            &collateral,
+            &data.stable_token_denomination,
+        );
+    }
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &531953,
+    );
+
+    (depositor_1, depositor_2, depositor_3)
+}
+
+fn vault_key(vault: &Vault) -> VaultKey {
+    VaultKey {
+        index: vault.index,
+        account: vault.account.clone(),
+        denomination: vault.denomination.clone(),
+        id: vault.id,
+    }
+}
+
+#[test]
+fn test_redistribute_insolvent_vaults() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let (depositor_1, depositor_2, depositor_3) = setup_vaults(&env, &data);
+    let caller: Address = Address::generate(&env);
+
+    let small_collateral: u128 = 100_000_0000000 - calc_fee(&data.fee, &100_000_0000000);
+    let big_collateral: u128 = 500_000_0000000 - calc_fee(&data.fee, &500_000_0000000);
+    let first_fee: u128 = calc_fee(&data.fee, &small_collateral);
+    let first_redistributed: u128 = small_collateral - first_fee;
+
+    let redistributed_vaults: soroban_sdk::Vec<Vault> =
+        data.contract_client
+            .redistribute(&caller, &data.stable_token_denomination, &1u32);
+
+    assert_eq!(redistributed_vaults.len(), 1);
+    assert_eq!(redistributed_vaults.get(0).unwrap().account, depositor_1);
+    assert_eq!(
+        data.contract_client
+            .try_get_vault(&depositor_1, &data.stable_token_denomination, &0)
+            .unwrap_err()
+            .unwrap(),
+        SCErrors::VaultDoesntExist.into()
+    );
+
+    let redistribution: Redistribution = data
+        .contract_client
+        .get_redistribution(&data.stable_token_denomination);
+
+    assert_eq!(redistribution.debt_factor, 1_500000000);
+
+    // The remaining vaults have the same debt so they receive the same amounts
+    let vault_2: Vault =
+        data.contract_client
+            .get_vault(&depositor_2, &data.stable_token_denomination, &0);
+    let vault_3: Vault =
+        data.contract_client
+            .get_vault(&depositor_3, &data.stable_token_denomination, &0);
+
+    assert_eq!(vault_2.total_debt, 7_500_0000000);
+    assert_eq!(vault_3.total_debt, 7_500_0000000);
+    assert_approx(
+        vault_2.total_collateral,
+        small_collateral + first_redistributed / 2,
+    );
+    assert_approx(
+        vault_3.total_collateral,
+        big_collateral + first_redistributed / 2,
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+
+    assert_eq!(vaults_info.total_vaults, 2);
+    assert_eq!(vaults_info.total_debt, 15_000_0000000);
+    assert_eq!(
+        vaults_info.total_col,
+        small_collateral * 2 + big_collateral - first_fee
+    );
+
+    // Touching a vault saves the pending values and keeps the list sorted
+    data.contract_client.increase_collateral(
+        &OptionalVaultKey::Some(vault_key(&vault_2)),
+        &vault_key(&vault_3),
+        &OptionalVaultKey::Some(vault_key(&vault_2)),
+        &10_000_0000000,
+    );
+
+    let updated_vault_3: Vault =
+        data.contract_client
+            .get_vault(&depositor_3, &data.stable_token_denomination, &0);
+
+    assert_eq!(updated_vault_3.total_debt, 7_500_0000000);
+    assert_eq!(
+        updated_vault_3.total_collateral,
+        vault_3.total_collateral + 10_000_0000000 - calc_fee(&data.fee, &10_000_0000000)
+    );
+    assert!(updated_vault_3.index > vault_2.index);
+
+    // The second vault is still insolvent so its values (including what it received) go to the last vault
+    let second_fee: u128 = calc_fee(&data.fee, &vault_2.total_collateral);
+    data.contract_client
+        .redistribute(&caller, &data.stable_token_denomination, &1u32);
+
+    let final_vault_3: Vault =
+        data.contract_client
+            .get_vault(&depositor_3, &data.stable_token_denomination, &0);
+
+    assert_eq!(final_vault_3.total_debt, 15_000_0000000);
+    assert_approx(
+        final_vault_3.total_collateral,
+        updated_vault_3.total_collateral + vault_2.total_collateral - second_fee,
+    );
+
+    // The last vault is healthy so it can't be redistributed
+    let not_enough_error = data
+        .contract_client
+        .try_redistribute(&caller, &data.stable_token_denomination, &1u32)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        not_enough_error,
+        SCErrors::NotEnoughVaultsToLiquidate.into()
+    );
+
+    // The vault owner can pay the redistributed debt
+    data.stable_token_admin_client
+        .mint(&depositor_3, &5_000_0000000);
+    data.contract_client.pay_debt(
+        &OptionalVaultKey::None,
+        &vault_key(&final_vault_3),
+        &OptionalVaultKey::None,
+        &5_000_0000000,
+    );
+
+    let paid_vault_3: Vault =
+        data.contract_client
+            .get_vault(&depositor_3, &data.stable_token_denomination, &0);
+
+    assert_eq!(paid_vault_3.total_debt, 10_000_0000000);
+    assert_eq!(
+        data.collateral_token_client.balance(&data.treasury) as u128,
+        calc_fee(&data.fee, &100_000_0000000) * 2
+            + calc_fee(&data.fee, &500_000_0000000)
+            + calc_fee(&data.fee, &10_000_0000000)
+            + first_fee
+            + second_fee
+    );
+}
+
+#[test]
+fn test_redistribute_only_when_the_stability_pool_cant_absorb() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let (_, depositor_2, _) = setup_vaults(&env, &data);
+    let caller: Address = Address::generate(&env);
+
+    let stability_depositor: Address = Address::generate(&env);
+    data.stable_token_admin_client
+        .mint(&stability_depositor, &6_000_0000000);
+    data.contract_client.stability_deposit(
+        &stability_depositor,
+        &data.stable_token_denomination,
+        &6_000_0000000,
+    );
+
+    let can_absorb_error = data
+        .contract_client
+        .try_redistribute(&caller, &data.stable_token_denomination, &1u32)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        can_absorb_error,
+        SCErrors::StabilityPoolCanAbsorbDebt.into()
+    );
+
+    // Once the pool absorbed what it could, the second vault can be redistributed
+    data.contract_client
+        .liquidate(&caller, &data.stable_token_denomination, &1u32);
+    data.contract_client
+        .redistribute(&caller, &data.stable_token_denomination, &1u32);
+
+    assert_eq!(
+        data.contract_client
+            .try_get_vault(&depositor_2, &data.stable_token_denomination, &0)
+            .unwrap_err()
+            .unwrap(),
+        SCErrors::VaultDoesntExist.into()
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+
+    assert_eq!(vaults_info.total_vaults, 1);
+    assert_eq!(vaults_info.total_debt, 10_000_0000000);
+}
@@ -111,7 +111,7 @@ mod vaults_properties {
 
             let depositor_vault: Vault = data
                 .contract_client
-                .get_vault(&depositor, &data.stable_token_denomination);
+                .get_vault(&depositor, &data.stable_token_denomination, &0);
 
             assert_eq!(depositor_vault.index, DEPOSITOR_INDEX);
         }
@@ -169,7 +169,7 @@ mod vaults_properties {
             assert_eq!(data.collateral_token_client.balance(&depositor) as u128, 0);
             assert_eq!(data.stable_token_client.balance(&depositor) as u128, depositor_debt);
 
-            let depositor_vault: Vault = data.contract_client.get_vault(&depositor, &data.stable_token_denomination);
+            let depositor_vault: Vault = data.contract_client.get_vault(&depositor, &data.stable_token_denomination, &0);
             assert_eq!(depositor_vault.total_collateral, depositor_collateral_minus_fees);
 
             let vaults_info: VaultsInfo = data.contract_client.get_vaults_info(&data.stable_token_denomination);
@@ -178,7 +178,7 @@ mod vaults_properties {
             let depositor_index = 1_000_000_000 * depositor_collateral_minus_fees / depositor_debt;
             assert_eq!(depositor_vault.index, depositor_index);
 
-            let vault_key = VaultKey { index: depositor_index, account:depositor.clone(), denomination: data.stable_token_denomination.clone() };
+            let vault_key = VaultKey { index: depositor_index, account:depositor.clone(), denomination: data.stable_token_denomination.clone(), id: 0 };
 
             // Withdrawing the funds
             data.contract_client.pay_debt(
@@ -194,7 +194,7 @@ mod vaults_properties {
             assert_eq!(data.collateral_token_client.balance(&depositor) as u128, depositor_collateral_after_withdraw);
             assert_eq!(data.stable_token_client.balance(&depositor) as u128, 0);
 
-            let no_vault = data.contract_client.try_get_vault(&depositor, &data.stable_token_denomination);
+            let no_vault = data.contract_client.try_get_vault(&depositor, &data.stable_token_denomination, &0);
             assert!(no_vault.is_err());
 
             let vaults_info: VaultsInfo = data.contract_client.get_vaults_info(&data.stable_token_denomination);
@@ -427,9 +427,9 @@ mod weak_fairness {
             &data.stable_token_denomination,
         );
 
-        let depositor_1_vault: Vault = data
-            .contract_client
-            .get_vault(&depositor_1, &data.stable_token_denomination);
+        let depositor_1_vault: Vault =
+            data.contract_client
+                .get_vault(&depositor_1, &data.stable_token_denomination, &0);
 
         assert_eq!(depositor_1_vault.index, 3233_7500000);
 
@@ -447,6 +447,7 @@ mod weak_fairness {
                 index: depositor_1_vault.index.clone(),
                 account: depositor_1_vault.account.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0,
             }),
             &depositor_2,
             &depositor_2_debt,
@@ -454,9 +455,9 @@ mod weak_fairness {
             &data.stable_token_denomination,
         );
 
-        let depositor_2_vault: Vault = data
-            .contract_client
-            .get_vault(&depositor_2, &data.stable_token_denomination);
+        let depositor_2_vault: Vault =
+            data.contract_client
+                .get_vault(&depositor_2, &data.stable_token_denomination, &0);
 
         assert_eq!(depositor_2_vault.index, 3233_7500000);
 
@@ -477,9 +478,9 @@ mod weak_fairness {
             &data.stable_token_denomination,
         );
 
-        let depositor_3_vault: Vault = data
-            .contract_client
-            .get_vault(&depositor_3, &data.stable_token_denomination);
+        let depositor_3_vault: Vault =
+            data.contract_client
+                .get_vault(&depositor_3, &data.stable_token_denomination, &0);
 
         assert_eq!(depositor_3_vault.index, 1747_6464285);
 
@@ -497,6 +498,7 @@ mod weak_fairness {
                 index: depositor_1_vault.index.clone(),
                 account: depositor_1_vault.account.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0,
             }),
             &depositor_4,
             &depositor_4_debt,
@@ -504,9 +506,9 @@ mod weak_fairness {
             &data.stable_token_denomination,
         );
 
-        let depositor_4_vault: Vault = data
-            .contract_client
-            .get_vault(&depositor_4, &data.stable_token_denomination);
+        let depositor_4_vault: Vault =
+            data.contract_client
+                .get_vault(&depositor_4, &data.stable_token_denomination, &0);
 
         assert_eq!(depositor_4_vault.index, 5970_0000000);
 
@@ -524,7 +526,7 @@ mod weak_fairness {
 
         let mut vault = data
             .contract_client
-            .get_vault(&key.account, &key.denomination);
+            .get_vault(&key.account, &key.denomination, &key.id);
 
         for i in 0..ordered_depositors.len() {
             assert_eq!(vault.account, *ordered_depositors[i]);
@@ -554,15 +556,16 @@ mod weak_fairness {
             &(new_depositor_collateral_amount as i128 * 2),
         );
 
-        let depositor_1_vault: Vault = data
-            .contract_client
-            .get_vault(&prev_depositor, &data.stable_token_denomination);
+        let depositor_1_vault: Vault =
+            data.contract_client
+                .get_vault(&prev_depositor, &data.stable_token_denomination, &0);
 
         data.contract_client.new_vault(
             &OptionalVaultKey::Some(VaultKey {
                 index: depositor_1_vault.index.clone(),
                 account: depositor_1_vault.account.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0,
             }),
             &new_depositor,
             &new_depositor_debt,
@@ -570,9 +573,9 @@ mod weak_fairness {
             &data.stable_token_denomination,
         );
 
-        let depositor_5_vault: Vault = data
-            .contract_client
-            .get_vault(&new_depositor, &data.stable_token_denomination);
+        let depositor_5_vault: Vault =
+            data.contract_client
+                .get_vault(&new_depositor, &data.stable_token_denomination, &0);
 
         assert_eq!(depositor_5_vault.index, 3233_7500000);
 
@@ -587,7 +590,7 @@ mod weak_fairness {
 
         let mut vault = data
             .contract_client
-            .get_vault(&key.account, &key.denomination);
+            .get_vault(&key.account, &key.denomination, &key.id);
         for i in 0..expected_depositors.len() {
             assert_eq!(vault.account, *expected_depositors[i]);
 
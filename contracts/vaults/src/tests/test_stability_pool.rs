@@ -0,0 +1,303 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::errors::SCErrors;
+use crate::storage::stability_pool::{StabilityGains, StabilityPool, DECIMAL_PRECISION};
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use crate::utils::payments::calc_fee;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env};
+
+fn assert_approx(value: u128, expected: u128) {
+    assert!(
+        value.abs_diff(expected) <= 2,
+        "value {} is not close to {}",
+        value,
+        expected
+    );
+}
+
+// Creates two vaults that can be liquidated after the price drops to `second_rate` and a healthy one
+fn setup_vaults(env: &Env, data: &TestData) -> (Address, Address, Address) {
+    let first_rate: u128 = 931953;
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &(first_rate as i128),
+    );
+
+    let depositor_1: Address = Address::generate(&env);
+    let depositor_2: Address = Address::generate(&env);
+    let depositor_3: Address = Address::generate(&env);
+
+    // Each new vault has a lower or equal index than the previous ones so it's always the new lowest
+    for (depositor, collateral) in [
+        (&depositor_3, 500_000_0000000u128),
+        (&depositor_2, 100_000_0000000u128),
+        (&depositor_1, 100_000_0000000u128),
+    ] {
+        data.collateral_token_admin_client
+            .mint(depositor, &(collateral as i128));
+
+        data.contract_client.new_vault(
+            &OptionalVaultKey::None,
+            depositor,
+            &5_000_0000000,
+            &collateral,
+            &data.stable_token_denomination,
+        );
+    }
+
+    (depositor_1, depositor_2, depositor_3)
+}
+
+#[test]
+fn test_stability_pool_absorbs_liquidations() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let (depositor_1, _, _) = setup_vaults(&env, &data);
+
+    let stability_depositor_1: Address = Address::generate(&env);
+    let stability_depositor_2: Address = Address::generate(&env);
+    data.stable_token_admin_client
+        .mint(&stability_depositor_1, &3_000_0000000);
+    data.stable_token_admin_client
+        .mint(&stability_depositor_2, &9_000_0000000);
+
+    let invalid_amount_error = data
+        .contract_client
+        .try_stability_deposit(&stability_depositor_1, &data.stable_token_denomination, &0)
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        invalid_amount_error,
+        SCErrors::InvalidStabilityDepositAmount.into()
+    );
+
+    data.contract_client.stability_deposit(
+        &stability_depositor_1,
+        &data.stable_token_denomination,
+        &3_000_0000000,
+    );
+    data.contract_client.stability_deposit(
+        &stability_depositor_2,
+        &data.stable_token_denomination,
+        &9_000_0000000,
+    );
+
+    let pool: StabilityPool = data
+        .contract_client
+        .get_stability_pool(&data.stable_token_denomination);
+
+    assert_eq!(pool.total_deposits, 12_000_0000000);
+    assert_eq!(pool.total_collateral, 0);
+    assert_eq!(pool.deposits, 2);
+    assert_eq!(pool.product, DECIMAL_PRECISION);
+    assert_eq!(data.stable_token_client.balance(&stability_depositor_1), 0);
+
+    let second_rate: u128 = 531953;
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &(second_rate as i128),
+    );
+
+    // The liquidator doesn't need to hold stablecoins, the pool pays the debt
+    let liquidator: Address = Address::generate(&env);
+    data.contract_client
+        .liquidate(&liquidator, &data.stable_token_denomination, &1u32);
+
+    assert_eq!(
+        data.contract_client
+            .try_get_vault(&depositor_1, &data.stable_token_denomination, &0)
+            .unwrap_err()
+            .unwrap(),
+        SCErrors::VaultDoesntExist.into()
+    );
+    assert_eq!(data.collateral_token_client.balance(&liquidator), 0);
+
+    let vault_collateral: u128 = 100_000_0000000 - calc_fee(&data.fee, &100_000_0000000);
+    let liquidation_fee: u128 = calc_fee(&data.fee, &vault_collateral);
+    let seized_collateral: u128 = vault_collateral - liquidation_fee;
+
+    let pool: StabilityPool = data
+        .contract_client
+        .get_stability_pool(&data.stable_token_denomination);
+
+    assert_eq!(pool.total_deposits, 7_000_0000000);
+    assert_eq!(pool.total_collateral, seized_collateral);
+
+    // The debt and collateral are shared pro-rata between depositors
+    let gains_1: StabilityGains = data
+        .contract_client
+        .get_stability_gains(&stability_depositor_1, &data.stable_token_denomination);
+    let gains_2: StabilityGains = data
+        .contract_client
+        .get_stability_gains(&stability_depositor_2, &data.stable_token_denomination);
+
+    assert_approx(gains_1.deposit, 1_750_0000000);
+    assert_approx(gains_2.deposit, 5_250_0000000);
+    assert_approx(gains_1.collateral, seized_collateral / 4);
+    assert_approx(gains_2.collateral, seized_collateral * 3 / 4);
+
+    // Withdrawals are locked while there is a vault the pool should absorb
+    let locked_error = data
+        .contract_client
+        .try_stability_withdraw(
+            &stability_depositor_2,
+            &data.stable_token_denomination,
+            &1_000_0000000,
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        locked_error,
+        SCErrors::StabilityPoolWithdrawalsLocked.into()
+    );
+
+    data.contract_client
+        .liquidate(&liquidator, &data.stable_token_denomination, &1u32);
+
+    let pool: StabilityPool = data
+        .contract_client
+        .get_stability_pool(&data.stable_token_denomination);
+
+    assert_eq!(pool.total_deposits, 2_000_0000000);
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+
+    assert_eq!(vaults_info.total_vaults, 1);
+    assert_eq!(vaults_info.total_debt, 5_000_0000000);
+
+    // Claiming sends the collateral and keeps the compounded deposit in the pool
+    data.contract_client
+        .stability_claim(&stability_depositor_1, &data.stable_token_denomination);
+
+    assert_approx(
+        data.collateral_token_client.balance(&stability_depositor_1) as u128,
+        seized_collateral / 2,
+    );
+
+    let gains_1: StabilityGains = data
+        .contract_client
+        .get_stability_gains(&stability_depositor_1, &data.stable_token_denomination);
+
+    assert_eq!(gains_1.collateral, 0);
+    assert_approx(gains_1.deposit, 500_0000000);
+
+    // Withdrawing everything removes the deposit and pays the pending collateral
+    let gains_2: StabilityGains = data
+        .contract_client
+        .get_stability_gains(&stability_depositor_2, &data.stable_token_denomination);
+
+    assert_approx(gains_2.deposit, 1_500_0000000);
+
+    let too_much_error = data
+        .contract_client
+        .try_stability_withdraw(
+            &stability_depositor_2,
+            &data.stable_token_denomination,
+            &(gains_2.deposit + 1),
+        )
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        too_much_error,
+        SCErrors::InvalidStabilityDepositAmount.into()
+    );
+
+    data.contract_client.stability_withdraw(
+        &stability_depositor_2,
+        &data.stable_token_denomination,
+        &gains_2.deposit,
+    );
+
+    assert_eq!(
+        data.stable_token_client.balance(&stability_depositor_2) as u128,
+        gains_2.deposit
+    );
+    assert_approx(
+        data.collateral_token_client.balance(&stability_depositor_2) as u128,
+        seized_collateral * 3 / 2,
+    );
+    assert_eq!(
+        data.contract_client
+            .try_get_stability_deposit(&stability_depositor_2, &data.stable_token_denomination)
+            .unwrap_err()
+            .unwrap(),
+        SCErrors::StabilityDepositDoesntExist.into()
+    );
+
+    let pool: StabilityPool = data
+        .contract_client
+        .get_stability_pool(&data.stable_token_denomination);
+
+    assert_eq!(pool.deposits, 1);
+    assert_eq!(pool.total_deposits, 2_000_0000000 - gains_2.deposit);
+}
+
+#[test]
+fn test_liquidator_pays_when_stability_pool_is_not_enough() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    let (_, _, _) = setup_vaults(&env, &data);
+
+    let stability_depositor: Address = Address::generate(&env);
+    data.stable_token_admin_client
+        .mint(&stability_depositor, &5_000_0000000);
+
+    // The pool can not be emptied by a liquidation, so a deposit equal to the debt is not enough
+    data.contract_client.stability_deposit(
+        &stability_depositor,
+        &data.stable_token_denomination,
+        &5_000_0000000,
+    );
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &531953,
+    );
+
+    let liquidator: Address = Address::generate(&env);
+    data.stable_token_admin_client
+        .mint(&liquidator, &5_000_0000000);
+
+    data.contract_client
+        .liquidate(&liquidator, &data.stable_token_denomination, &1u32);
+
+    let vault_collateral: u128 = 100_000_0000000 - calc_fee(&data.fee, &100_000_0000000);
+
+    assert_eq!(data.stable_token_client.balance(&liquidator), 0);
+    assert_eq!(
+        data.collateral_token_client.balance(&liquidator) as u128,
+        vault_collateral - calc_fee(&data.fee, &vault_collateral)
+    );
+
+    let pool: StabilityPool = data
+        .contract_client
+        .get_stability_pool(&data.stable_token_denomination);
+
+    assert_eq!(pool.total_deposits, 5_000_0000000);
+    assert_eq!(pool.total_collateral, 0);
+}
@@ -1,6 +1,5 @@
 #![cfg(test)]
 
-use crate::errors::SCErrors;
 use crate::storage::vaults::{OptionalVaultKey, Vault, VaultKey};
 use crate::tests::test_utils::{
     create_base_data, create_base_variables, set_initial_state, update_oracle_price,
@@ -72,13 +71,9 @@ fn test_transfer_debt() {
         &data.stable_token_denomination,
     );
 
-    let depositor_1_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_1, &data.stable_token_denomination);
-
-    let depositor_2_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_2, &data.stable_token_denomination);
+    let depositor_1_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_1, &data.stable_token_denomination, &0);
 
     let new_owner: Address = Address::generate(&env);
 
@@ -91,67 +86,73 @@ fn test_transfer_debt() {
                 index: depositor_1_vault.index.clone(),
                 account: depositor_1_vault.account.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0
             },
             &new_owner,
         )
         .is_err());
 
-    let already_has_vault_error = data
-        .contract_client
-        .mock_all_auths()
-        .try_transfer_debt(
-            &OptionalVaultKey::Some(VaultKey {
+    let transfer_invoke: MockAuthInvoke = MockAuthInvoke {
+        contract: &data.contract_client.address,
+        fn_name: "transfer_debt",
+        args: (
+            OptionalVaultKey::None,
+            VaultKey {
                 index: depositor_1_vault.index.clone(),
                 account: depositor_1_vault.account.clone(),
                 denomination: data.stable_token_denomination.clone(),
-            }),
+                id: 0,
+            },
+            new_owner.clone(),
+        )
+            .into_val(&env),
+        sub_invokes: &[],
+    };
+
+    // Should fail because the new owner didn't agree with the transfer
+    assert!(data
+        .contract_client
+        .mock_auths(&[MockAuth {
+            address: &depositor_1,
+            invoke: &transfer_invoke,
+        }])
+        .try_transfer_debt(
+            &OptionalVaultKey::None,
             &VaultKey {
-                index: depositor_2_vault.index.clone(),
-                account: depositor_2_vault.account.clone(),
+                index: depositor_1_vault.index.clone(),
+                account: depositor_1_vault.account.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0
             },
-            &depositor_2,
+            &new_owner,
         )
-        .unwrap_err()
-        .unwrap();
-
-    assert_eq!(
-        already_has_vault_error,
-        SCErrors::UserAlreadyHasDenominationVault.into(),
-    );
+        .is_err());
 
     data.contract_client
-        .mock_auths(&[MockAuth {
-            address: &depositor_1,
-            invoke: &MockAuthInvoke {
-                contract: &data.contract_client.address,
-                fn_name: "transfer_debt",
-                args: (
-                    OptionalVaultKey::None,
-                    VaultKey {
-                        index: depositor_1_vault.index.clone(),
-                        account: depositor_1_vault.account.clone(),
-                        denomination: data.stable_token_denomination.clone(),
-                    },
-                    new_owner.clone(),
-                )
-                    .into_val(&env),
-                sub_invokes: &[],
+        .mock_auths(&[
+            MockAuth {
+                address: &depositor_1,
+                invoke: &transfer_invoke,
             },
-        }])
+            MockAuth {
+                address: &new_owner,
+                invoke: &transfer_invoke,
+            },
+        ])
         .transfer_debt(
             &OptionalVaultKey::None,
             &VaultKey {
                 index: depositor_1_vault.index.clone(),
                 account: depositor_1_vault.account.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0,
             },
             &new_owner,
         );
 
     let new_vault = data
         .contract_client
-        .get_vault(&new_owner, &data.stable_token_denomination);
+        .get_vault(&new_owner, &data.stable_token_denomination, &0);
 
     assert_eq!(new_owner, new_vault.account);
     assert_eq!(depositor_1_vault.total_debt, new_vault.total_debt);
@@ -0,0 +1,193 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::errors::SCErrorsExt;
+use crate::storage::transfers::TransferOffer;
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{vec, Address, Env};
+
+fn key_of(vault: &Vault) -> VaultKey {
+    VaultKey {
+        index: vault.index,
+        account: vault.account.clone(),
+        denomination: vault.denomination.clone(),
+        id: vault.id,
+    }
+}
+
+#[test]
+fn test_vault_transfer_offers() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let seller: Address = Address::generate(&env);
+    let buyer: Address = Address::generate(&env);
+    let stranger: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&seller, &100_000_0000000);
+    data.native_token_admin_client.mint(&buyer, &1_000_0000000);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &seller,
+        &5_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+    let vault: Vault = data
+        .contract_client
+        .get_vault(&seller, &data.stable_token_denomination, &0);
+
+    // The owner can't offer the vault to itself
+    assert_eq!(
+        data.contract_client
+            .try_offer_vault_transfer(
+                &seller,
+                &data.stable_token_denomination,
+                &0,
+                &TransferOffer {
+                    recipient: seller.clone(),
+                    asset: data.native_token_client.address.clone(),
+                    price: 0,
+                    expiration: env.ledger().timestamp() + 600,
+                },
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrorsExt::InvalidTransferOffer.into()
+    );
+
+    let offer: TransferOffer = TransferOffer {
+        recipient: buyer.clone(),
+        asset: data.native_token_client.address.clone(),
+        price: 400_0000000,
+        expiration: env.ledger().timestamp() + 600,
+    };
+    data.contract_client
+        .offer_vault_transfer(&seller, &data.stable_token_denomination, &0, &offer);
+
+    assert_eq!(
+        data.contract_client
+            .get_vault_transfer_offer(&seller, &data.stable_token_denomination, &0),
+        Some(offer.clone())
+    );
+
+    // Only the recipient can accept the offer
+    assert_eq!(
+        data.contract_client
+            .try_accept_vault_transfer(
+                &stranger,
+                &OptionalVaultKey::None,
+                &key_of(&vault),
+                &vault.total_debt,
+                &vault.total_collateral,
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrorsExt::TransferOfferDoesntExist.into()
+    );
+
+    // The recipient doesn't accept more debt than the one it agreed to
+    assert_eq!(
+        data.contract_client
+            .try_accept_vault_transfer(
+                &buyer,
+                &OptionalVaultKey::None,
+                &key_of(&vault),
+                &(vault.total_debt - 1),
+                &vault.total_collateral,
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrorsExt::TransferTermsNotMet.into()
+    );
+
+    let new_id: u32 = data.contract_client.accept_vault_transfer(
+        &buyer,
+        &OptionalVaultKey::None,
+        &key_of(&vault),
+        &vault.total_debt,
+        &vault.total_collateral,
+    );
+
+    assert_eq!(new_id, 0);
+    assert_eq!(data.native_token_client.balance(&seller), 400_0000000);
+    assert_eq!(data.native_token_client.balance(&buyer), 600_0000000);
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&seller, &data.stable_token_denomination)
+            .len(),
+        0
+    );
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&buyer, &data.stable_token_denomination),
+        vec![&env, 0]
+    );
+    assert_eq!(
+        data.contract_client
+            .get_vault_transfer_offer(&seller, &data.stable_token_denomination, &0),
+        None
+    );
+
+    let bought_vault: Vault =
+        data.contract_client
+            .get_vault(&buyer, &data.stable_token_denomination, &0);
+    assert_eq!(bought_vault.total_debt, vault.total_debt);
+    assert_eq!(bought_vault.total_collateral, vault.total_collateral);
+
+    // Expired offers can't be accepted
+    data.contract_client.offer_vault_transfer(
+        &buyer,
+        &data.stable_token_denomination,
+        &0,
+        &TransferOffer {
+            recipient: seller.clone(),
+            asset: data.native_token_client.address.clone(),
+            price: 0,
+            expiration: env.ledger().timestamp() + 600,
+        },
+    );
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 601);
+
+    assert_eq!(
+        data.contract_client
+            .try_accept_vault_transfer(
+                &seller,
+                &OptionalVaultKey::None,
+                &key_of(&bought_vault),
+                &bought_vault.total_debt,
+                &bought_vault.total_collateral,
+            )
+            .unwrap_err()
+            .unwrap(),
+        SCErrorsExt::TransferOfferIsExpired.into()
+    );
+
+    data.contract_client
+        .cancel_vault_transfer(&buyer, &data.stable_token_denomination, &0);
+
+    assert_eq!(
+        data.contract_client
+            .try_cancel_vault_transfer(&buyer, &data.stable_token_denomination, &0)
+            .unwrap_err()
+            .unwrap(),
+        SCErrorsExt::TransferOfferDoesntExist.into()
+    );
+}
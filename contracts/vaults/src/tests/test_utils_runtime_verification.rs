@@ -414,9 +414,9 @@ pub fn setup_vaults(env: &Env, data: &TestDataVaults, depositors: &[&Address]) {
         &data.stable_token_denomination,
     );
 
-    let depositor_1_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_1, &data.stable_token_denomination);
+    let depositor_1_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_1, &data.stable_token_denomination, &0);
 
     // Second: This deposit should have an index of: 3233_7500000
     let depositor_2 = depositors[1];
@@ -431,6 +431,7 @@ pub fn setup_vaults(env: &Env, data: &TestDataVaults, depositors: &[&Address]) {
             index: depositor_1_vault.index.clone(),
             account: depositor_1_vault.account.clone(),
             denomination: data.stable_token_denomination.clone(),
+            id: 0,
         }),
         &depositor_2,
         &depositor_2_debt,
@@ -467,6 +468,7 @@ pub fn setup_vaults(env: &Env, data: &TestDataVaults, depositors: &[&Address]) {
             index: depositor_1_vault.index.clone(),
             account: depositor_1_vault.account.clone(),
             denomination: data.stable_token_denomination.clone(),
+            id: 0,
         }),
         &depositor_4,
         &depositor_4_debt,
@@ -0,0 +1,158 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::storage::vaults::*;
+use crate::tests::test_utils::{
+    create_base_data, create_base_variables, set_initial_state, update_oracle_price,
+    InitialVariables, TestData,
+};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{vec, Address, Env};
+
+fn key_of(vault: &Vault) -> VaultKey {
+    VaultKey {
+        index: vault.index,
+        account: vault.account.clone(),
+        denomination: vault.denomination.clone(),
+        id: vault.id,
+    }
+}
+
+#[test]
+fn test_multiple_vaults_per_account() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let data: TestData = create_base_data(&env);
+    let base_variables: InitialVariables = create_base_variables(&env, &data);
+    set_initial_state(&env, &data, &base_variables);
+
+    update_oracle_price(
+        &env,
+        &data.oracle_contract_client,
+        &data.stable_token_denomination,
+        &931953,
+    );
+
+    let owner: Address = Address::generate(&env);
+    let other: Address = Address::generate(&env);
+    data.collateral_token_admin_client
+        .mint(&owner, &300_000_0000000);
+    data.collateral_token_admin_client
+        .mint(&other, &200_000_0000000);
+
+    let first_id: u32 = data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &5_000_0000000,
+        &100_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    // The second vault has a lower collateral ratio so it's the new lowest
+    let second_id: u32 = data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &5_000_0000000,
+        &80_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    assert_eq!(first_id, 0);
+    assert_eq!(second_id, 1);
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&owner, &data.stable_token_denomination),
+        vec![&env, 0, 1]
+    );
+
+    let first_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &0);
+    let second_vault: Vault =
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &1);
+
+    assert_eq!(first_vault.id, 0);
+    assert_eq!(second_vault.id, 1);
+    assert!(second_vault.index < first_vault.index);
+    assert_eq!(
+        second_vault.next_key,
+        OptionalVaultKey::Some(key_of(&first_vault))
+    );
+
+    let vaults_info: VaultsInfo = data
+        .contract_client
+        .get_vaults_info(&data.stable_token_denomination);
+    assert_eq!(vaults_info.total_vaults, 2);
+    assert_eq!(vaults_info.total_debt, 10_000_0000000);
+
+    data.contract_client.new_vault(
+        &OptionalVaultKey::Some(key_of(&first_vault)),
+        &other,
+        &5_000_0000000,
+        &200_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    // Closing one of the vaults doesn't affect the other one
+    data.contract_client.pay_debt(
+        &OptionalVaultKey::Some(key_of(&second_vault)),
+        &key_of(&first_vault),
+        &OptionalVaultKey::None,
+        &5_000_0000000,
+    );
+
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&owner, &data.stable_token_denomination),
+        vec![&env, 1]
+    );
+    assert!(data
+        .contract_client
+        .try_get_vault(&owner, &data.stable_token_denomination, &0)
+        .is_err());
+    assert_eq!(
+        data.contract_client
+            .get_vault(&owner, &data.stable_token_denomination, &1),
+        data.contract_client
+            .get_vault_from_key(&key_of(&second_vault))
+    );
+
+    // A transferred vault gets a new id from the new owner, even if it already has vaults
+    let transferred_id: u32 =
+        data.contract_client
+            .transfer_debt(&OptionalVaultKey::None, &key_of(&second_vault), &other);
+
+    assert_eq!(transferred_id, 1);
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&owner, &data.stable_token_denomination)
+            .len(),
+        0
+    );
+    assert_eq!(
+        data.contract_client
+            .get_vault_ids(&other, &data.stable_token_denomination),
+        vec![&env, 0, 1]
+    );
+
+    let transferred_vault: Vault =
+        data.contract_client
+            .get_vault(&other, &data.stable_token_denomination, &1);
+    assert_eq!(transferred_vault.total_debt, second_vault.total_debt);
+    assert_eq!(
+        transferred_vault.total_collateral,
+        second_vault.total_collateral
+    );
+
+    // Ids are never reused
+    let third_id: u32 = data.contract_client.new_vault(
+        &OptionalVaultKey::None,
+        &owner,
+        &5_000_0000000,
+        &80_000_0000000,
+        &data.stable_token_denomination,
+    );
+
+    assert_eq!(third_id, 2);
+}
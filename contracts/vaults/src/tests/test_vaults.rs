@@ -205,6 +205,7 @@ fn test_new_vault() {
                 index: u128::MAX,
                 account: Address::generate(&env),
                 denomination: symbol_short!("usd"),
+                id: 0,
             }),
             &depositor,
             &initial_debt,
@@ -222,7 +223,7 @@ fn test_new_vault() {
     // Fail if the Vault doesn't exist
     let vault_doesnt_exist_error = data
         .contract_client
-        .try_get_vault(&depositor, &data.stable_token_denomination)
+        .try_get_vault(&depositor, &data.stable_token_denomination, &0)
         .unwrap_err()
         .unwrap();
 
@@ -301,9 +302,9 @@ fn test_new_vault() {
         .contract_client
         .get_vaults_info(&data.stable_token_denomination);
 
-    let user_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor, &data.stable_token_denomination);
+    let user_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
 
     assert_eq!(vault_info.total_vaults, 1);
     assert_eq!(
@@ -312,6 +313,7 @@ fn test_new_vault() {
             index: user_vault.index.clone(),
             account: user_vault.account.clone(),
             denomination: user_vault.denomination.clone(),
+            id: 0
         })
     );
     assert_eq!(vault_info.total_debt, initial_debt);
@@ -330,24 +332,6 @@ fn test_new_vault() {
     );
     assert_eq!(user_vault.total_debt, initial_debt);
 
-    // Should fail if user tries to create a new vault but already have one
-    let vault_already_exist = data
-        .contract_client
-        .try_new_vault(
-            &OptionalVaultKey::None,
-            &depositor,
-            &initial_debt,
-            &collateral_amount,
-            &data.stable_token_denomination,
-        )
-        .unwrap_err()
-        .unwrap();
-
-    assert_eq!(
-        &vault_already_exist,
-        &SCErrors::UserAlreadyHasDenominationVault.into()
-    );
-
     let depositor_2 = Address::generate(&env);
 
     data.collateral_token_admin_client
@@ -361,6 +345,7 @@ fn test_new_vault() {
                 denomination: data.stable_token_denomination.clone(),
                 index: 1,
                 account: Address::generate(&env),
+                id: 0,
             }),
             &depositor_2,
             &initial_debt,
@@ -392,9 +377,9 @@ fn test_new_vault() {
         .contract_client
         .get_vaults_info(&data.stable_token_denomination);
 
-    let second_user_vault = data
-        .contract_client
-        .get_vault(&depositor, &data.stable_token_denomination);
+    let second_user_vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
 
     assert_eq!(updated_vaults_info.total_vaults, 2);
     assert_eq!(updated_vaults_info.total_debt, initial_debt * 2);
@@ -466,6 +451,7 @@ fn test_increase_collateral() {
                 index: 1,
                 account: depositor.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0,
             },
             &OptionalVaultKey::None,
             &100_0000000,
@@ -483,9 +469,9 @@ fn test_increase_collateral() {
         &data.stable_token_denomination,
     );
 
-    let current_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor, &data.stable_token_denomination);
+    let current_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
 
     assert_eq!(
         &current_vault.total_collateral,
@@ -500,6 +486,7 @@ fn test_increase_collateral() {
             index: current_vault.index.clone(),
             account: current_vault.account.clone(),
             denomination: current_vault.denomination.clone(),
+            id: 0,
         },
         &OptionalVaultKey::None,
         &collateral_to_add,
@@ -519,6 +506,7 @@ fn test_increase_collateral() {
                             index: current_vault.index.clone(),
                             account: current_vault.account.clone(),
                             denomination: current_vault.denomination.clone(),
+                            id: 0
                         },
                         OptionalVaultKey::None,
                         collateral_to_add.clone(),
@@ -558,9 +546,9 @@ fn test_increase_collateral() {
         )
     );
 
-    let updated_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor, &data.stable_token_denomination);
+    let updated_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
 
     assert_ne!(&current_vault.index, &updated_vault.index);
     assert_eq!(
@@ -611,6 +599,7 @@ fn test_increase_collateral() {
             ),
             denomination: data.stable_token_denomination.clone(),
             account: depositor_2.clone(),
+            id: 0,
         }),
         &depositor_3,
         &base_variables.initial_debt,
@@ -619,19 +608,19 @@ fn test_increase_collateral() {
     );
 
     // Currently this is the highest vault
-    let vault_1: Vault = data
-        .contract_client
-        .get_vault(&depositor, &data.stable_token_denomination);
+    let vault_1: Vault =
+        data.contract_client
+            .get_vault(&depositor, &data.stable_token_denomination, &0);
 
     // Currently this is the middle vault
-    let vault_2: Vault = data
-        .contract_client
-        .get_vault(&depositor_2, &data.stable_token_denomination);
+    let vault_2: Vault =
+        data.contract_client
+            .get_vault(&depositor_2, &data.stable_token_denomination, &0);
 
     // Currently this is the lowest vault
-    let vault_3: Vault = data
-        .contract_client
-        .get_vault(&depositor_3, &data.stable_token_denomination);
+    let vault_3: Vault =
+        data.contract_client
+            .get_vault(&depositor_3, &data.stable_token_denomination, &0);
 
     // If prev_key is None, the target Vault needs to be the lowest vault otherwise panic
     let none_must_be_the_lowest_error = data
@@ -642,6 +631,7 @@ fn test_increase_collateral() {
                 index: vault_3.index.clone(),
                 account: vault_3.account.clone(),
                 denomination: vault_3.denomination.clone(),
+                id: 0,
             },
             &OptionalVaultKey::None,
             &collateral_to_add,
@@ -662,11 +652,13 @@ fn test_increase_collateral() {
                 index: vault_1.index.clone(),
                 account: vault_1.account.clone(),
                 denomination: vault_1.denomination.clone(),
+                id: 0,
             }),
             &VaultKey {
                 index: vault_3.index.clone(),
                 account: vault_3.account.clone(),
                 denomination: vault_3.denomination.clone(),
+                id: 0,
             },
             &OptionalVaultKey::None,
             &collateral_to_add,
@@ -687,11 +679,13 @@ fn test_increase_collateral() {
                 index: vault_3.index.clone(),
                 account: vault_3.account.clone(),
                 denomination: vault_3.denomination.clone(),
+                id: 0,
             }),
             &VaultKey {
                 index: vault_1.index.clone(),
                 account: vault_1.account.clone(),
                 denomination: vault_1.denomination.clone(),
+                id: 0,
             },
             &OptionalVaultKey::None,
             &collateral_to_add,
@@ -709,16 +703,19 @@ fn test_increase_collateral() {
             index: vault_3.index.clone(),
             account: vault_3.account.clone(),
             denomination: vault_3.denomination.clone(),
+            id: 0,
         }),
         &VaultKey {
             index: vault_2.index.clone(),
             account: vault_2.account.clone(),
             denomination: vault_2.denomination.clone(),
+            id: 0,
         },
         &OptionalVaultKey::Some(VaultKey {
             index: vault_1.index.clone(),
             account: vault_1.account.clone(),
             denomination: vault_1.denomination.clone(),
+            id: 0,
         }),
         &(collateral_to_add * 3),
     );
@@ -780,6 +777,7 @@ fn test_increase_debt() {
                 index: 1,
                 account: base_variables.depositor.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0,
             },
             &OptionalVaultKey::None,
             &100_0000000,
@@ -824,6 +822,7 @@ fn test_increase_debt() {
             ),
             account: base_variables.depositor.clone(),
             denomination: data.stable_token_denomination.clone(),
+            id: 0,
         },
         &OptionalVaultKey::None,
         &base_variables.initial_debt,
@@ -849,6 +848,7 @@ fn test_increase_debt() {
                             ),
                             account: base_variables.depositor.clone(),
                             denomination: data.stable_token_denomination.clone(),
+                            id: 0
                         },
                         OptionalVaultKey::None,
                         base_variables.initial_debt,
@@ -925,6 +925,7 @@ fn test_pay_debt() {
                 ),
                 account: base_variables.depositor.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0,
             },
             &OptionalVaultKey::None,
             &base_variables.initial_debt,
@@ -961,9 +962,11 @@ fn test_pay_debt() {
         (base_variables.initial_debt * 2) as i128
     );
 
-    let mut vault: Vault = data
-        .contract_client
-        .get_vault(&base_variables.depositor, &data.stable_token_denomination);
+    let mut vault: Vault = data.contract_client.get_vault(
+        &base_variables.depositor,
+        &data.stable_token_denomination,
+        &0,
+    );
 
     data.contract_client.pay_debt(
         &OptionalVaultKey::None,
@@ -971,6 +974,7 @@ fn test_pay_debt() {
             index: vault.index.clone(),
             account: vault.account.clone(),
             denomination: vault.denomination.clone(),
+            id: 0,
         },
         &OptionalVaultKey::None,
         &base_variables.initial_debt,
@@ -991,6 +995,7 @@ fn test_pay_debt() {
                             index: vault.index.clone(),
                             account: vault.account.clone(),
                             denomination: vault.denomination.clone(),
+                            id: 0
                         },
                         OptionalVaultKey::None,
                         base_variables.initial_debt.clone(),
@@ -1038,9 +1043,11 @@ fn test_pay_debt() {
             - calc_fee(&data.fee, &(base_variables.collateral_amount * 2))) as i128
     );
 
-    vault = data
-        .contract_client
-        .get_vault(&base_variables.depositor, &data.stable_token_denomination);
+    vault = data.contract_client.get_vault(
+        &base_variables.depositor,
+        &data.stable_token_denomination,
+        &0,
+    );
 
     // If the vault will be below the min deb it should fail
     let min_debt_invalid_error = data
@@ -1051,6 +1058,7 @@ fn test_pay_debt() {
                 index: vault.index.clone(),
                 account: vault.account.clone(),
                 denomination: vault.denomination.clone(),
+                id: 0,
             },
             &OptionalVaultKey::None,
             &(base_variables.initial_debt / 2),
@@ -1069,6 +1077,7 @@ fn test_pay_debt() {
             index: vault.index.clone(),
             account: vault.account.clone(),
             denomination: vault.denomination.clone(),
+            id: 0,
         },
         &OptionalVaultKey::None,
         &base_variables.initial_debt,
@@ -1095,7 +1104,11 @@ fn test_pay_debt() {
     // We confirm the vault was removed from the storage
     let vault_removed_error = data
         .contract_client
-        .try_get_vault(&base_variables.depositor, &data.stable_token_denomination)
+        .try_get_vault(
+            &base_variables.depositor,
+            &data.stable_token_denomination,
+            &0,
+        )
         .unwrap_err()
         .unwrap();
 
@@ -1147,6 +1160,7 @@ fn test_withdraw_collateral() {
                 ),
                 account: base_variables.depositor.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0,
             },
             &OptionalVaultKey::None,
             &(collateral_to_use / 10),
@@ -1183,10 +1197,11 @@ fn test_withdraw_collateral() {
         (base_variables.initial_debt * 2) as i128
     );
 
-    let mut vault: Vault = data
-        .contract_client
-        .mock_all_auths()
-        .get_vault(&base_variables.depositor, &data.stable_token_denomination);
+    let mut vault: Vault = data.contract_client.mock_all_auths().get_vault(
+        &base_variables.depositor,
+        &data.stable_token_denomination,
+        &0,
+    );
 
     assert!(data
         .contract_client
@@ -1196,6 +1211,7 @@ fn test_withdraw_collateral() {
                 index: vault.index.clone(),
                 account: vault.account.clone(),
                 denomination: vault.denomination.clone(),
+                id: 0
             },
             &OptionalVaultKey::None,
             &(collateral_to_use / 10),
@@ -1208,6 +1224,7 @@ fn test_withdraw_collateral() {
             index: vault.index.clone(),
             account: vault.account.clone(),
             denomination: vault.denomination.clone(),
+            id: 0,
         },
         &OptionalVaultKey::None,
         &(collateral_to_use / 10),
@@ -1238,10 +1255,11 @@ fn test_withdraw_collateral() {
         collateral_to_use - calc_fee(&data.fee, &collateral_to_use) - (collateral_to_use / 10)
     );
 
-    vault = data
-        .contract_client
-        .mock_all_auths()
-        .get_vault(&base_variables.depositor, &data.stable_token_denomination);
+    vault = data.contract_client.mock_all_auths().get_vault(
+        &base_variables.depositor,
+        &data.stable_token_denomination,
+        &0,
+    );
 
     // If the vault will be below the min col ratio it should fail
     let min_col_rate_error = data
@@ -1253,6 +1271,7 @@ fn test_withdraw_collateral() {
                 index: vault.index.clone(),
                 account: vault.account.clone(),
                 denomination: vault.denomination.clone(),
+                id: 0,
             },
             &OptionalVaultKey::None,
             &(collateral_to_use - (collateral_to_use / 10)),
@@ -1324,11 +1343,14 @@ fn get_vaults() {
         total_collateral: (base_variables.collateral_amount * 2)
             - calc_fee(&data.fee, &(base_variables.collateral_amount * 2)),
         denomination: data.stable_token_denomination.clone(),
+        id: 0,
     };
 
-    let vault_from_basic: Vault = data
-        .contract_client
-        .get_vault(&base_variables.depositor, &data.stable_token_denomination);
+    let vault_from_basic: Vault = data.contract_client.get_vault(
+        &base_variables.depositor,
+        &data.stable_token_denomination,
+        &0,
+    );
 
     assert_eq!(&vault_from_basic, &vault_to_validate);
 
@@ -1336,6 +1358,7 @@ fn get_vaults() {
         index: vault_from_basic.index.clone(),
         account: vault_from_basic.account.clone(),
         denomination: vault_from_basic.denomination.clone(),
+        id: 0,
     });
 
     assert_eq!(&vault_from_key, &vault_to_validate);
@@ -1425,10 +1448,11 @@ fn panic_mode_enabled() {
 
     data.contract_client.mock_all_auths().set_panic(&true);
 
-    let vault: Vault = data
-        .contract_client
-        .mock_all_auths()
-        .get_vault(&base_variables.depositor, &data.stable_token_denomination);
+    let vault: Vault = data.contract_client.mock_all_auths().get_vault(
+        &base_variables.depositor,
+        &data.stable_token_denomination,
+        &0,
+    );
 
     let panic_mode_enabled_error_2 = data
         .contract_client
@@ -1439,6 +1463,7 @@ fn panic_mode_enabled() {
                 index: vault.index,
                 account: base_variables.depositor.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0,
             },
             &OptionalVaultKey::None,
             &base_variables.initial_debt,
@@ -1491,8 +1516,9 @@ fn panic_mode_enabled() {
             &OptionalVaultKey::None,
             &VaultKey {
                 index: vault.index,
-                account: base_variables.depositor,
+                account: base_variables.depositor.clone(),
                 denomination: data.stable_token_denomination.clone(),
+                id: 0,
             },
             &OptionalVaultKey::None,
             &base_variables.initial_debt,
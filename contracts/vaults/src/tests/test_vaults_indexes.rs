@@ -57,9 +57,9 @@ fn test_indexes_orders() {
         &data.stable_token_denomination,
     );
 
-    let depositor_1_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_1, &data.stable_token_denomination);
+    let depositor_1_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_1, &data.stable_token_denomination, &0);
 
     // Second depositor
     // This deposit should have an index of: 1857_1428571 - fee
@@ -78,9 +78,9 @@ fn test_indexes_orders() {
         &data.stable_token_denomination,
     );
 
-    let depositor_2_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_2, &data.stable_token_denomination);
+    let depositor_2_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_2, &data.stable_token_denomination, &0);
 
     // Third depositor
     // This deposit should have an index of: 3250_0000000
@@ -96,6 +96,7 @@ fn test_indexes_orders() {
             index: depositor_1_vault.index.clone(),
             account: depositor_1_vault.account.clone(),
             denomination: data.stable_token_denomination.clone(),
+            id: 0,
         }),
         &depositor_3,
         &depositor_3_debt,
@@ -103,9 +104,9 @@ fn test_indexes_orders() {
         &data.stable_token_denomination,
     );
 
-    let depositor_3_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_3, &data.stable_token_denomination);
+    let depositor_3_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_3, &data.stable_token_denomination, &0);
 
     // fourth depositor
     // This deposit should have an index of: 3250_0000000
@@ -121,6 +122,7 @@ fn test_indexes_orders() {
             index: depositor_1_vault.index.clone(),
             account: depositor_1_vault.account.clone(),
             denomination: data.stable_token_denomination.clone(),
+            id: 0,
         }),
         &depositor_4,
         &depositor_4_debt,
@@ -128,9 +130,9 @@ fn test_indexes_orders() {
         &data.stable_token_denomination,
     );
 
-    let depositor_4_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_4, &data.stable_token_denomination);
+    let depositor_4_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_4, &data.stable_token_denomination, &0);
 
     // fifth depositor
     // This deposit should have an index of: 1756_4285710
@@ -149,9 +151,9 @@ fn test_indexes_orders() {
         &data.stable_token_denomination,
     );
 
-    let depositor_5_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_5, &data.stable_token_denomination);
+    let depositor_5_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_5, &data.stable_token_denomination, &0);
 
     // Sixth depositor
     // This deposit should have an index of: 6000_0000000
@@ -167,6 +169,7 @@ fn test_indexes_orders() {
             index: depositor_3_vault.index.clone(),
             account: depositor_3_vault.account.clone(),
             denomination: data.stable_token_denomination.clone(),
+            id: 0,
         }),
         &depositor_6,
         &depositor_6_debt,
@@ -174,9 +177,9 @@ fn test_indexes_orders() {
         &data.stable_token_denomination,
     );
 
-    let depositor_6_vault: Vault = data
-        .contract_client
-        .get_vault(&depositor_6, &data.stable_token_denomination);
+    let depositor_6_vault: Vault =
+        data.contract_client
+            .get_vault(&depositor_6, &data.stable_token_denomination, &0);
 
     // 2nd part of the test
     // We are going to get the lowest vault and we should be able to go from lowest to higher
@@ -192,9 +195,9 @@ fn test_indexes_orders() {
         OptionalVaultKey::Some(data) => data,
     };
 
-    let first_vault: Vault = data
-        .contract_client
-        .get_vault(&lowest_key.account, &lowest_key.denomination);
+    let first_vault: Vault =
+        data.contract_client
+            .get_vault(&lowest_key.account, &lowest_key.denomination, &0);
 
     let first_lowest_key = match first_vault.next_key {
         OptionalVaultKey::None => panic!("We don't reach this point"),
@@ -204,9 +207,11 @@ fn test_indexes_orders() {
     assert_eq!(first_vault.index, depositor_5_vault.index);
     assert_eq!(first_vault.account, depositor_5);
 
-    let second_vault: Vault = data
-        .contract_client
-        .get_vault(&first_lowest_key.account, &first_lowest_key.denomination);
+    let second_vault: Vault = data.contract_client.get_vault(
+        &first_lowest_key.account,
+        &first_lowest_key.denomination,
+        &0,
+    );
 
     let second_lowest_key = match second_vault.next_key {
         OptionalVaultKey::None => panic!("We don't reach this point"),
@@ -216,9 +221,11 @@ fn test_indexes_orders() {
     assert_eq!(second_vault.index, depositor_2_vault.index);
     assert_eq!(second_vault.account, depositor_2);
 
-    let third_vault: Vault = data
-        .contract_client
-        .get_vault(&second_lowest_key.account, &second_lowest_key.denomination);
+    let third_vault: Vault = data.contract_client.get_vault(
+        &second_lowest_key.account,
+        &second_lowest_key.denomination,
+        &0,
+    );
 
     let third_lowest_key = match third_vault.next_key {
         OptionalVaultKey::None => panic!("We don't reach this point"),
@@ -228,9 +235,11 @@ fn test_indexes_orders() {
     assert_eq!(third_vault.index, depositor_1_vault.index);
     assert_eq!(third_vault.account, depositor_1);
 
-    let fourth_vault: Vault = data
-        .contract_client
-        .get_vault(&third_lowest_key.account, &third_lowest_key.denomination);
+    let fourth_vault: Vault = data.contract_client.get_vault(
+        &third_lowest_key.account,
+        &third_lowest_key.denomination,
+        &0,
+    );
 
     let fourth_lowest_key = match fourth_vault.next_key {
         OptionalVaultKey::None => panic!("We don't reach this point"),
@@ -240,9 +249,11 @@ fn test_indexes_orders() {
     assert_eq!(fourth_vault.index, depositor_4_vault.index);
     assert_eq!(fourth_vault.account, depositor_4);
 
-    let fifth_vault: Vault = data
-        .contract_client
-        .get_vault(&fourth_lowest_key.account, &fourth_lowest_key.denomination);
+    let fifth_vault: Vault = data.contract_client.get_vault(
+        &fourth_lowest_key.account,
+        &fourth_lowest_key.denomination,
+        &0,
+    );
 
     let fifth_lowest_key = match fifth_vault.next_key {
         OptionalVaultKey::None => panic!("We don't reach this point"),
@@ -252,9 +263,11 @@ fn test_indexes_orders() {
     assert_eq!(fifth_vault.index, depositor_3_vault.index);
     assert_eq!(fifth_vault.account, depositor_3);
 
-    let sixth_vault: Vault = data
-        .contract_client
-        .get_vault(&fifth_lowest_key.account, &fifth_lowest_key.denomination);
+    let sixth_vault: Vault = data.contract_client.get_vault(
+        &fifth_lowest_key.account,
+        &fifth_lowest_key.denomination,
+        &0,
+    );
 
     match sixth_vault.next_key {
         OptionalVaultKey::None => {}
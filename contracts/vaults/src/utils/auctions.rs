@@ -0,0 +1,71 @@
+use crate::errors::SCErrors;
+use crate::storage::auctions::{Auction, AuctionConfig, AuctionsFunc};
+use soroban_sdk::{panic_with_error, symbol_short, Address, Env, Symbol};
+
+pub fn validate_auction_config(e: &Env, auction_config: &AuctionConfig) {
+    if auction_config.premium < 1_0000000
+        || auction_config.min_price == 0
+        || auction_config.min_price > auction_config.premium
+        || auction_config.duration == 0
+    {
+        panic_with_error!(&e, &SCErrors::InvalidAuctionConfig);
+    }
+}
+
+// Creates and saves a new auction starting at the current time
+// - `rate` - The current oracle price of the collateral
+pub fn new_auction(
+    e: &Env,
+    auction_config: &AuctionConfig,
+    denomination: &Symbol,
+    owner: &Address,
+    debt: u128,
+    collateral: u128,
+    rate: u128,
+) -> Auction {
+    let id: u64 = e.auctions_counter() + 1;
+    e.set_auctions_counter(id);
+
+    let auction: Auction = Auction {
+        id,
+        denomination: denomination.clone(),
+        owner: owner.clone(),
+        debt,
+        collateral,
+        start_price: rate * auction_config.premium / 1_0000000,
+        min_price: rate * auction_config.min_price / 1_0000000,
+        started_at: e.ledger().timestamp(),
+        duration: auction_config.duration,
+    };
+
+    e.set_auction(&auction);
+    e.bump_auction(id);
+    emit_auction_event(&e, symbol_short!("start"), &auction);
+
+    auction
+}
+
+pub fn is_auction_expired(e: &Env, auction: &Auction) -> bool {
+    e.ledger().timestamp() - auction.started_at >= auction.duration
+}
+
+// The price decays linearly from the start price to the min price during the duration of the auction, expired
+// auctions have a price of zero because they can't be bid on
+pub fn auction_price(e: &Env, auction: &Auction) -> u128 {
+    if is_auction_expired(&e, &auction) {
+        return 0;
+    }
+
+    let elapsed: u64 = e.ledger().timestamp() - auction.started_at;
+    auction.min_price
+        + (auction.start_price - auction.min_price) * ((auction.duration - elapsed) as u128)
+            / (auction.duration as u128)
+}
+
+// Topics are ("auction", action, id) and the data is the auction after the action
+pub fn emit_auction_event(e: &Env, action: Symbol, auction: &Auction) {
+    e.events().publish(
+        (symbol_short!("auction"), action, auction.id),
+        auction.clone(),
+    );
+}
@@ -0,0 +1,36 @@
+use crate::errors::SCErrors;
+use crate::storage::core::CoreState;
+use crate::storage::fees::{FeeSchedule, FeesFunc, MAX_FEE, MAX_REDEMPTION_FEE};
+use soroban_sdk::{panic_with_error, Env, Symbol};
+
+// Returns the fee schedule that applies to the denomination, the order of priority is:
+// 1. The schedule set for the denomination
+// 2. The default schedule
+// 3. A schedule built from the legacy `CoreState.fee` and the original redemption tiers (1% and 2.5%)
+pub fn get_fee_schedule(e: &Env, core_state: &CoreState, denomination: &Symbol) -> FeeSchedule {
+    e.fee_schedule(denomination)
+        .or_else(|| e.default_fee_schedule())
+        .unwrap_or(FeeSchedule {
+            open: core_state.fee,
+            deposit: core_state.fee,
+            withdraw: 0,
+            close: core_state.fee,
+            liquidation: core_state.fee,
+            redemption: 100000,
+            partial_redemption: 250000,
+            stablecoin_fees: false,
+        })
+}
+
+pub fn validate_fee_schedule(e: &Env, fee_schedule: &FeeSchedule) {
+    if fee_schedule.open > MAX_FEE
+        || fee_schedule.deposit > MAX_FEE
+        || fee_schedule.withdraw > MAX_FEE
+        || fee_schedule.close > MAX_FEE
+        || fee_schedule.liquidation > MAX_FEE
+        || fee_schedule.redemption > MAX_REDEMPTION_FEE
+        || fee_schedule.partial_redemption > MAX_REDEMPTION_FEE
+    {
+        panic_with_error!(e, &SCErrors::InvalidFee);
+    }
+}
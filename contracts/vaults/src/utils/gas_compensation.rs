@@ -0,0 +1,47 @@
+use crate::storage::gas_compensation::{GasCompensation, GasCompensationFunc};
+use crate::storage::vaults::VaultKey;
+use soroban_sdk::{token, Address, Env};
+
+// Takes the current gas compensation (if there is one) from the payer, usually the owner of the new vault, and keeps it
+// in this contract
+pub fn take_gas_reserve(e: &Env, payer: &Address, vault_key: &VaultKey) {
+    let gas_compensation: GasCompensation = match e.gas_compensation() {
+        None => return,
+        Some(value) => value,
+    };
+
+    token::Client::new(&e, &gas_compensation.asset).transfer(
+        &payer,
+        &e.current_contract_address(),
+        &(gas_compensation.amount as i128),
+    );
+
+    e.set_gas_reserve(&vault_key, &gas_compensation);
+    e.bump_gas_reserve(&vault_key);
+}
+
+// Sends the reserve of the vault (if it has one) to `destination`, IE the owner when the vault is closed
+// or the liquidator when it's liquidated
+pub fn release_gas_reserve(e: &Env, vault_key: &VaultKey, destination: &Address) {
+    let gas_reserve: GasCompensation = match e.gas_reserve(&vault_key) {
+        None => return,
+        Some(value) => value,
+    };
+
+    e.remove_gas_reserve(&vault_key);
+
+    token::Client::new(&e, &gas_reserve.asset).transfer(
+        &e.current_contract_address(),
+        &destination,
+        &(gas_reserve.amount as i128),
+    );
+}
+
+// Moves the reserve to the new owner of the vault
+pub fn move_gas_reserve(e: &Env, from: &VaultKey, to: &VaultKey) {
+    if let Some(gas_reserve) = e.gas_reserve(&from) {
+        e.remove_gas_reserve(&from);
+        e.set_gas_reserve(&to, &gas_reserve);
+        e.bump_gas_reserve(&to);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::errors::SCErrors;
+use crate::flash_mint_receiver;
+use crate::storage::auctions::{AuctionConfig, AuctionsFunc};
+use crate::storage::core::CoreState;
+use crate::storage::currencies::Currency;
+use crate::storage::flash_mint::FlashMintConfig;
+use crate::storage::stability_pool::{StabilityPool, StabilityPoolFunc};
+use crate::storage::vaults::{OptionalVaultKey, Vault, VaultKey, VaultsFunc, VaultsInfo};
+use crate::utils::auctions::new_auction;
+use crate::utils::fees::get_fee_schedule;
+use crate::utils::gas_compensation::release_gas_reserve;
+use crate::utils::payments::{burn_stablecoin, calc_fee, pay_fee, withdraw_collateral};
+use crate::utils::stability_pool::absorb_debt;
+use crate::utils::vaults::{can_be_liquidated, remove_vault_id, withdraw_vault};
+use soroban_sdk::{panic_with_error, token, Address, Bytes, Env, Symbol};
+
+// Everything a liquidation call accumulates before moving the funds, vaults are absorbed by the stability pool
+// while it has enough deposits, the rest is auctioned if the denomination has auctions enabled or paid by the liquidator
+pub struct Liquidation {
+    pub liquidator: Address,
+    pub vaults_info: VaultsInfo,
+    pub stability_pool: Option<StabilityPool>,
+    pub auction_config: Option<AuctionConfig>,
+    pub liquidation_fee: u128,
+    pub rate: u128,
+    pub absorbed_debt: u128,
+    pub absorbed_fee: u128,
+    pub auctioned_fee: u128,
+    pub collateral_to_withdraw: u128,
+    pub amount_to_deposit: u128,
+}
+
+pub fn new_liquidation(
+    e: &Env,
+    core_state: &CoreState,
+    liquidator: &Address,
+    denomination: &Symbol,
+    rate: u128,
+) -> Liquidation {
+    Liquidation {
+        liquidator: liquidator.clone(),
+        vaults_info: e.vaults_info(&denomination).unwrap(),
+        stability_pool: e.stability_pool(&denomination),
+        auction_config: e.auction_config(&denomination),
+        liquidation_fee: get_fee_schedule(&e, &core_state, &denomination).liquidation,
+        rate,
+        absorbed_debt: 0,
+        absorbed_fee: 0,
+        auctioned_fee: 0,
+        collateral_to_withdraw: 0,
+        amount_to_deposit: 0,
+    }
+}
+
+// Removes the vault and adds it to the liquidation.
+// Returns false (without doing anything) if the liquidator would need to burn more than `max_debt` in total.
+//
+// **Arguments:**
+// - `vault` - The vault to liquidate with its pending redistribution already applied
+// - `prev_key` - The key of the vault that comes BEFORE the liquidated one, None if the vault is the lowest
+pub fn liquidate_vault(
+    e: &Env,
+    liquidation: &mut Liquidation,
+    vault: &Vault,
+    prev_key: &OptionalVaultKey,
+    max_debt: u128,
+) -> bool {
+    if !can_be_liquidated(&vault, &liquidation.vaults_info, &liquidation.rate) {
+        panic_with_error!(&e, SCErrors::UserVaultCantBeLiquidated);
+    }
+
+    let vault_key: VaultKey = VaultKey {
+        index: vault.index,
+        account: vault.account.clone(),
+        denomination: vault.denomination.clone(),
+        id: vault.id,
+    };
+
+    if prev_key == &OptionalVaultKey::None
+        && liquidation.vaults_info.lowest_key != OptionalVaultKey::Some(vault_key.clone())
+    {
+        panic_with_error!(&e, SCErrors::PrevVaultCantBeNone);
+    }
+
+    let fee: u128 = calc_fee(&liquidation.liquidation_fee, &vault.total_collateral);
+    let absorbed: bool = match liquidation.stability_pool.as_mut() {
+        None => false,
+        Some(pool) => absorb_debt(&e, pool, vault.total_debt, vault.total_collateral - fee),
+    };
+
+    if absorbed {
+        liquidation.absorbed_debt = liquidation.absorbed_debt + vault.total_debt;
+        liquidation.absorbed_fee = liquidation.absorbed_fee + fee;
+    } else if let Some(config) = liquidation.auction_config.as_ref() {
+        new_auction(
+            &e,
+            config,
+            &vault.denomination,
+            &vault.account,
+            vault.total_debt,
+            vault.total_collateral - fee,
+            liquidation.rate,
+        );
+        liquidation.auctioned_fee = liquidation.auctioned_fee + fee;
+    } else {
+        if liquidation.amount_to_deposit + vault.total_debt > max_debt {
+            return false;
+        }
+
+        liquidation.collateral_to_withdraw =
+            liquidation.collateral_to_withdraw + vault.total_collateral;
+        liquidation.amount_to_deposit = liquidation.amount_to_deposit + vault.total_debt;
+    }
+
+    liquidation.vaults_info.total_vaults = liquidation.vaults_info.total_vaults - 1;
+    liquidation.vaults_info.total_col = liquidation.vaults_info.total_col - vault.total_collateral;
+    liquidation.vaults_info.total_debt = liquidation.vaults_info.total_debt - vault.total_debt;
+
+    withdraw_vault(&e, &vault, &prev_key);
+    remove_vault_id(&e, &vault.account, &vault.denomination, vault.id);
+    release_gas_reserve(&e, &vault_key, &liquidation.liquidator);
+
+    if prev_key == &OptionalVaultKey::None {
+        liquidation.vaults_info.lowest_key = vault.next_key.clone();
+    }
+
+    true
+}
+
+// Saves the updated state and moves the funds of the liquidation
+pub fn settle_liquidation(
+    e: &Env,
+    core_state: &CoreState,
+    currency: &Currency,
+    liquidation: &Liquidation,
+) {
+    settle_absorbed_debt(&e, &core_state, &currency, &liquidation);
+
+    if liquidation.amount_to_deposit > 0 {
+        burn_stablecoin(
+            &e,
+            &currency,
+            &liquidation.liquidator,
+            liquidation.amount_to_deposit as i128,
+        );
+
+        withdraw_liquidator_collateral(&e, &core_state, &liquidation);
+    }
+}
+
+// Same as `settle_liquidation` but the stablecoins the liquidator has to pay are flash minted. The collateral is sent
+// to the liquidator (the flash mint receiver) first and its `on_flash_mint` callback must send back to this contract
+// the amount plus the flash mint fee, both are burned after the callback.
+pub fn settle_flash_liquidation(
+    e: &Env,
+    core_state: &CoreState,
+    currency: &Currency,
+    liquidation: &Liquidation,
+    flash_mint_config: &FlashMintConfig,
+    initiator: &Address,
+    data: &Bytes,
+) {
+    settle_absorbed_debt(&e, &core_state, &currency, &liquidation);
+
+    if liquidation.amount_to_deposit == 0 {
+        return;
+    }
+
+    let fee: u128 = calc_fee(&flash_mint_config.fee, &liquidation.amount_to_deposit);
+    let stablecoin: token::Client = token::Client::new(&e, &currency.contract);
+    let initial_balance: i128 = stablecoin.balance(&e.current_contract_address());
+
+    withdraw_liquidator_collateral(&e, &core_state, &liquidation);
+
+    flash_mint_receiver::Client::new(&e, &liquidation.liquidator).on_flash_mint(
+        &initiator,
+        &currency.denomination,
+        &liquidation.amount_to_deposit,
+        &fee,
+        &data,
+    );
+
+    let repaid: i128 = stablecoin.balance(&e.current_contract_address()) - initial_balance;
+    if repaid < (liquidation.amount_to_deposit + fee) as i128 {
+        panic_with_error!(&e, &SCErrors::FlashMintNotRepaid);
+    }
+
+    burn_stablecoin(
+        &e,
+        &currency,
+        &e.current_contract_address(),
+        (liquidation.amount_to_deposit + fee) as i128,
+    );
+}
+
+// Burns the debt absorbed by the stability pool and pays the fees of the absorbed and auctioned vaults
+fn settle_absorbed_debt(
+    e: &Env,
+    core_state: &CoreState,
+    currency: &Currency,
+    liquidation: &Liquidation,
+) {
+    e.set_vaults_info(&liquidation.vaults_info);
+
+    if let Some(pool) = liquidation.stability_pool.as_ref() {
+        e.set_stability_pool(&pool);
+    }
+
+    if liquidation.absorbed_debt > 0 {
+        burn_stablecoin(
+            &e,
+            &currency,
+            &e.current_contract_address(),
+            liquidation.absorbed_debt as i128,
+        );
+    }
+
+    if liquidation.absorbed_fee + liquidation.auctioned_fee > 0 {
+        pay_fee(
+            &e,
+            &core_state,
+            &e.current_contract_address(),
+            (liquidation.absorbed_fee + liquidation.auctioned_fee) as i128,
+        );
+    }
+}
+
+// Sends the collateral of the vaults paid by the liquidator, minus the liquidation fee
+fn withdraw_liquidator_collateral(e: &Env, core_state: &CoreState, liquidation: &Liquidation) {
+    let fee: u128 = calc_fee(
+        &liquidation.liquidation_fee,
+        &liquidation.collateral_to_withdraw,
+    );
+    let end_collateral: u128 = liquidation.collateral_to_withdraw - fee;
+    withdraw_collateral(
+        &e,
+        &core_state,
+        &liquidation.liquidator,
+        end_collateral as i128,
+    );
+    pay_fee(&e, &core_state, &e.current_contract_address(), fee as i128);
+}
@@ -1,5 +1,14 @@
+pub mod auctions;
 pub mod currencies;
+pub mod fees;
+pub mod gas_compensation;
 pub mod indexes;
+pub mod liquidations;
+pub mod operators;
 pub mod payments;
+pub mod protections;
+pub mod stability_pool;
+pub mod swaps;
+pub mod transfers;
 pub mod validations;
 pub mod vaults;
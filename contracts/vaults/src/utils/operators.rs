@@ -0,0 +1,78 @@
+use crate::errors::SCErrors;
+use crate::storage::operators::{OperatorApproval, OperatorsFunc};
+use crate::storage::vaults::VaultKey;
+use soroban_sdk::{panic_with_error, Address, Env};
+
+pub enum VaultPermission {
+    Deposit,
+    Repay,
+    Withdraw(u128),
+    Mint(u128),
+}
+
+// Requires the auth of the caller and, if the caller is not the owner of the vault, checks it's an operator
+// allowed to do the action. Withdraw and mint amounts are taken from the allowances of the approval.
+pub fn require_vault_auth(
+    e: &Env,
+    caller: &Address,
+    vault_key: &VaultKey,
+    permission: VaultPermission,
+) {
+    caller.require_auth();
+
+    if caller == &vault_key.account {
+        return;
+    }
+
+    // An expired approval is the same as not having one
+    let mut approval: OperatorApproval = match e.operator_approval(
+        &vault_key.account,
+        &vault_key.denomination,
+        vault_key.id,
+        &caller,
+    ) {
+        Some(value) if value.expiration > e.ledger().timestamp() => value,
+        _ => panic_with_error!(&e, &SCErrors::OperatorIsNotApproved),
+    };
+
+    match permission {
+        VaultPermission::Deposit => {
+            if !approval.deposit {
+                panic_with_error!(&e, &SCErrors::OperatorIsNotApproved);
+            }
+        }
+        VaultPermission::Repay => {
+            if !approval.repay {
+                panic_with_error!(&e, &SCErrors::OperatorIsNotApproved);
+            }
+        }
+        VaultPermission::Withdraw(amount) => {
+            if amount > approval.withdraw_allowance {
+                panic_with_error!(&e, &SCErrors::OperatorAllowanceExceeded);
+            }
+
+            approval.withdraw_allowance = approval.withdraw_allowance - amount;
+        }
+        VaultPermission::Mint(amount) => {
+            if amount > approval.mint_allowance {
+                panic_with_error!(&e, &SCErrors::OperatorAllowanceExceeded);
+            }
+
+            approval.mint_allowance = approval.mint_allowance - amount;
+        }
+    }
+
+    e.set_operator_approval(
+        &vault_key.account,
+        &vault_key.denomination,
+        vault_key.id,
+        &caller,
+        &approval,
+    );
+    e.bump_operator_approval(
+        &vault_key.account,
+        &vault_key.denomination,
+        vault_key.id,
+        &caller,
+    );
+}
@@ -1,13 +1,106 @@
+use crate::locking_pool;
 use crate::storage::core::CoreState;
 use crate::storage::currencies::Currency;
-use soroban_sdk::{self, token, Address, Env};
+use crate::storage::fees::{FeesDistribution, FeesFunc};
+use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
+use soroban_sdk::{self, symbol_short, token, vec, Address, Env, IntoVal, Vec};
 
 pub fn calc_fee(fee: &u128, amount: &u128) -> u128 {
     (amount * fee).div_ceil(1_0000000)
 }
 
 pub fn pay_fee(env: &Env, core_state: &CoreState, payer: &Address, fee: i128) {
-    token::Client::new(env, &core_state.col_token).transfer(payer, &core_state.treasury, &fee);
+    let fees_distribution: FeesDistribution = match env.fees_distribution() {
+        None => {
+            token::Client::new(env, &core_state.col_token).transfer(
+                payer,
+                &core_state.treasury,
+                &fee,
+            );
+            return;
+        }
+        Some(fees_distribution) => fees_distribution,
+    };
+
+    // The fee is first moved to this contract so it can be split between the locking pool and the treasury
+    let contract: Address = env.current_contract_address();
+    if payer != &contract {
+        token::Client::new(env, &core_state.col_token).transfer(payer, &contract, &fee);
+    }
+
+    // The pool side is accumulated so the locking pool doesn't reject the distribution for being too small
+    let pool_side: i128 = ((fee as u128) * fees_distribution.share / 1_0000000) as i128;
+    let pending: u128 = env.pending_distribution() + (pool_side as u128);
+    let mut treasury_side: i128 = fee - pool_side;
+    if pending > 0 && pending >= fees_distribution.min_amount {
+        if !distribute_fee(env, core_state, &fees_distribution, pending as i128) {
+            treasury_side += pending as i128;
+        }
+        env.set_pending_distribution(&0);
+    } else {
+        env.set_pending_distribution(&pending);
+    }
+
+    if treasury_side > 0 {
+        token::Client::new(env, &core_state.col_token).transfer(
+            &contract,
+            &core_state.treasury,
+            &treasury_side,
+        );
+    }
+}
+
+// Sends the fees waiting to be distributed to the treasury, used when the distribution is updated or removed
+pub fn flush_pending_distribution(env: &Env, core_state: &CoreState) {
+    let pending: u128 = env.pending_distribution();
+    if pending == 0 {
+        return;
+    }
+
+    token::Client::new(env, &core_state.col_token).transfer(
+        &env.current_contract_address(),
+        &core_state.treasury,
+        &(pending as i128),
+    );
+    env.set_pending_distribution(&0);
+}
+
+// Calls `distribute` in the locking pool with this contract as the caller.
+// Returns false if the locking pool rejected it (for example if the pool has no balance), in that case nothing was moved.
+fn distribute_fee(
+    env: &Env,
+    core_state: &CoreState,
+    fees_distribution: &FeesDistribution,
+    amount: i128,
+) -> bool {
+    let contract: Address = env.current_contract_address();
+
+    // The locking pool pulls the rewards from the caller, so we need to authorize that transfer
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: core_state.col_token.clone(),
+                fn_name: symbol_short!("transfer"),
+                args: (
+                    contract.clone(),
+                    fees_distribution.locking_pool.clone(),
+                    amount,
+                )
+                    .into_val(env),
+            },
+            sub_invocations: Vec::new(env),
+        }),
+    ]);
+
+    locking_pool::Client::new(env, &fees_distribution.locking_pool)
+        .try_distribute(
+            &contract,
+            &fees_distribution.deposit_asset,
+            &core_state.col_token,
+            &(amount as u128),
+        )
+        .is_ok()
 }
 
 pub fn deposit_collateral(env: &Env, core_state: &CoreState, depositor: &Address, amount: i128) {
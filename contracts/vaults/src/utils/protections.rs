@@ -0,0 +1,57 @@
+use crate::errors::SCErrorsExt;
+use crate::storage::core::CoreState;
+use crate::storage::currencies::Currency;
+use crate::storage::protections::{ProtectionAction, ProtectionOrder};
+use crate::utils::payments::{deposit_collateral, withdraw_collateral};
+use soroban_sdk::{panic_with_error, token, Address, Env};
+
+pub fn validate_protection_order(e: &Env, order: &ProtectionOrder) {
+    if order.trigger_rate == 0 || order.amount == 0 || order.tip >= order.amount {
+        panic_with_error!(&e, &SCErrorsExt::InvalidProtectionOrder);
+    }
+}
+
+// Moves the `amount` of the order from the owner to this contract
+pub fn escrow_protection_order(
+    e: &Env,
+    core_state: &CoreState,
+    currency: &Currency,
+    owner: &Address,
+    order: &ProtectionOrder,
+) {
+    match order.action {
+        ProtectionAction::Repay => token::Client::new(&e, &currency.contract).transfer(
+            &owner,
+            &e.current_contract_address(),
+            &(order.amount as i128),
+        ),
+        ProtectionAction::AddCollateral => {
+            deposit_collateral(&e, &core_state, &owner, order.amount as i128)
+        }
+    }
+}
+
+// Sends `amount` of the escrowed asset of the order to the recipient
+pub fn release_protection_escrow(
+    e: &Env,
+    core_state: &CoreState,
+    currency: &Currency,
+    order: &ProtectionOrder,
+    recipient: &Address,
+    amount: u128,
+) {
+    if amount == 0 {
+        return;
+    }
+
+    match order.action {
+        ProtectionAction::Repay => token::Client::new(&e, &currency.contract).transfer(
+            &e.current_contract_address(),
+            &recipient,
+            &(amount as i128),
+        ),
+        ProtectionAction::AddCollateral => {
+            withdraw_collateral(&e, &core_state, &recipient, amount as i128)
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::storage::core::CoreState;
+use crate::storage::stability_pool::{
+    StabilityDeposit, StabilityPool, StabilityPoolFunc, DECIMAL_PRECISION, SCALE_FACTOR,
+};
+use crate::utils::payments::withdraw_collateral;
+use core::cmp::Ordering;
+use soroban_sdk::{Address, Env, Symbol};
+
+pub fn new_stability_pool(denomination: &Symbol) -> StabilityPool {
+    StabilityPool {
+        denomination: denomination.clone(),
+        total_deposits: 0,
+        total_collateral: 0,
+        deposits: 0,
+        product: DECIMAL_PRECISION,
+        sum: 0,
+        scale: 0,
+    }
+}
+
+fn sum_at_scale(e: &Env, pool: &StabilityPool, scale: u32) -> u128 {
+    match scale.cmp(&pool.scale) {
+        Ordering::Equal => pool.sum,
+        Ordering::Less => e.scale_sum(&pool.denomination, scale),
+        Ordering::Greater => 0,
+    }
+}
+
+// The current value of the deposit after the losses from the debt absorbed by the pool
+pub fn compounded_deposit(pool: &StabilityPool, deposit: &StabilityDeposit) -> u128 {
+    match pool.scale - deposit.scale_snapshot {
+        0 => deposit.amount * pool.product / deposit.product_snapshot,
+        1 => deposit.amount * pool.product / deposit.product_snapshot / SCALE_FACTOR,
+        // After two scale changes the deposit has lost at least a 1e-18 of its value, so we consider it depleted
+        _ => 0,
+    }
+}
+
+// The collateral earned by the deposit since its last update
+pub fn collateral_gain(e: &Env, pool: &StabilityPool, deposit: &StabilityDeposit) -> u128 {
+    let first_portion: u128 = sum_at_scale(e, pool, deposit.scale_snapshot) - deposit.sum_snapshot;
+    let second_portion: u128 = sum_at_scale(e, pool, deposit.scale_snapshot + 1) / SCALE_FACTOR;
+
+    deposit.amount * (first_portion + second_portion) / deposit.product_snapshot
+}
+
+// Calculates the product after absorbing the debt and if the scale needs to change
+fn next_product(pool: &StabilityPool, debt: u128) -> (u128, bool) {
+    let product_factor: u128 =
+        (pool.total_deposits - debt) * DECIMAL_PRECISION / pool.total_deposits;
+
+    let new_product: u128 = pool.product * product_factor / DECIMAL_PRECISION;
+    if new_product < SCALE_FACTOR {
+        (
+            pool.product * product_factor * SCALE_FACTOR / DECIMAL_PRECISION,
+            true,
+        )
+    } else {
+        (new_product, false)
+    }
+}
+
+// The pool can absorb the debt if it has more deposits than the debt and the product doesn't reach zero
+pub fn can_absorb_debt(pool: &StabilityPool, debt: u128) -> bool {
+    debt < pool.total_deposits && next_product(pool, debt).0 > 0
+}
+
+// Burns the debt from the pool deposits and adds the collateral to the depositors gains.
+// Returns false (without updating the pool) if the pool doesn't have enough deposits to absorb the debt, the pool can
+// never be fully emptied by a liquidation so the product doesn't reach zero.
+pub fn absorb_debt(e: &Env, pool: &mut StabilityPool, debt: u128, collateral: u128) -> bool {
+    if !can_absorb_debt(pool, debt) {
+        return false;
+    }
+
+    let (new_product, scale_changed) = next_product(pool, debt);
+
+    pool.sum += collateral * pool.product / pool.total_deposits;
+
+    if scale_changed {
+        e.set_scale_sum(&pool.denomination, pool.scale, pool.sum);
+        pool.scale += 1;
+        pool.sum = 0;
+    }
+
+    pool.product = new_product;
+    pool.total_deposits -= debt;
+    pool.total_collateral += collateral;
+
+    true
+}
+
+// Sends the collateral gains to the depositor and returns the current value of the deposit.
+// The deposit is not updated, the caller is responsible for saving it with the new snapshots.
+pub fn settle_stability_deposit(
+    e: &Env,
+    core_state: &CoreState,
+    pool: &mut StabilityPool,
+    depositor: &Address,
+    deposit: &StabilityDeposit,
+) -> u128 {
+    let gain: u128 = collateral_gain(e, pool, deposit);
+
+    if gain > 0 {
+        pool.total_collateral = pool.total_collateral.saturating_sub(gain);
+        withdraw_collateral(e, core_state, depositor, gain as i128);
+    }
+
+    compounded_deposit(pool, deposit)
+}
+
+pub fn new_stability_deposit(pool: &StabilityPool, amount: u128) -> StabilityDeposit {
+    StabilityDeposit {
+        amount,
+        product_snapshot: pool.product,
+        sum_snapshot: pool.sum,
+        scale_snapshot: pool.scale,
+    }
+}
@@ -0,0 +1,33 @@
+use crate::errors::SCErrors;
+use crate::swap_adapter;
+use soroban_sdk::{panic_with_error, token, Address, Env};
+
+// Calls the swap adapter (which must already hold the `amount_in` of `token_in`) and returns how much of `token_out`
+// this contract received. We check the balance instead of trusting the value returned by the adapter.
+pub fn swap_with_adapter(
+    e: &Env,
+    swap_adapter: &Address,
+    token_in: &Address,
+    token_out: &Address,
+    amount_in: u128,
+    min_amount_out: u128,
+) -> u128 {
+    let contract: Address = e.current_contract_address();
+    let token_out_client: token::Client = token::Client::new(&e, &token_out);
+    let initial_balance: i128 = token_out_client.balance(&contract);
+
+    swap_adapter::Client::new(&e, &swap_adapter).swap(
+        &token_in,
+        &token_out,
+        &amount_in,
+        &min_amount_out,
+        &contract,
+    );
+
+    let amount_out: u128 = (token_out_client.balance(&contract) - initial_balance) as u128;
+    if amount_out < min_amount_out {
+        panic_with_error!(&e, &SCErrors::SwapOutputIsBelowMinimum);
+    }
+
+    amount_out
+}
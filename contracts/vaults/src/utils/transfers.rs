@@ -0,0 +1,94 @@
+use crate::errors::{SCErrors, SCErrorsExt};
+use crate::storage::transfers::TransferOffer;
+use crate::storage::vaults::{OptionalVaultKey, VaultKey, VaultsFunc, VaultsInfo};
+use crate::utils::gas_compensation::move_gas_reserve;
+use crate::utils::validations::assert_regular_vault_updates_validations;
+use crate::utils::vaults::{
+    create_and_insert_vault, new_vault_id, remove_vault_id, search_vault, withdraw_vault,
+};
+use soroban_sdk::{panic_with_error, Address, Env};
+
+pub fn validate_transfer_offer(e: &Env, owner: &Address, offer: &TransferOffer) {
+    if &offer.recipient == owner || offer.expiration <= e.ledger().timestamp() {
+        panic_with_error!(&e, &SCErrorsExt::InvalidTransferOffer);
+    }
+}
+
+// Moves the vault to the `destination`, the vault keeps its index and position but it gets a new id from the new owner.
+// Pending transfer offers of the vault are removed and the gas reserve is moved with it.
+//
+// **Returns:**
+// - The updated VaultKey of the vault
+pub fn transfer_vault(
+    e: &Env,
+    prev_key: &OptionalVaultKey,
+    vault_key: &VaultKey,
+    destination: &Address,
+) -> VaultKey {
+    let (mut target_vault, mut target_vault_key, _) = search_vault(
+        &e,
+        &vault_key.account,
+        &vault_key.denomination,
+        vault_key.id,
+    );
+
+    let mut vaults_info: VaultsInfo = e.vaults_info(&target_vault_key.denomination).unwrap();
+
+    let lowest_key = match vaults_info.lowest_key.clone() {
+        // It should be impossible to reach this case, but just in case we panic if it happens.
+        OptionalVaultKey::None => panic_with_error!(&e, &SCErrors::ThereAreNoVaults),
+        OptionalVaultKey::Some(key) => key,
+    };
+
+    assert_regular_vault_updates_validations(
+        &e,
+        &target_vault,
+        &target_vault_key,
+        &prev_key,
+        &vault_key,
+        &prev_key,
+        &lowest_key,
+    );
+
+    // We remove the vault so we can update it to the new owner
+    withdraw_vault(&e, &target_vault, &prev_key);
+
+    // If the target vault is the lowest, we update the lowest value
+    if lowest_key == target_vault_key {
+        vaults_info.lowest_key = target_vault.next_key.clone();
+    }
+
+    // The vault gets a new id from the new owner
+    let prev_owner_key: VaultKey = target_vault_key.clone();
+    remove_vault_id(
+        &e,
+        &prev_owner_key.account,
+        &prev_owner_key.denomination,
+        prev_owner_key.id,
+    );
+    target_vault.id = new_vault_id(&e, &destination, &target_vault.denomination);
+    target_vault.account = destination.clone();
+    target_vault_key.id = target_vault.id;
+    target_vault_key.account = destination.clone();
+
+    let (_, updated_target_vault_key, updated_target_vault_index_key, updated_lowest_key) =
+        create_and_insert_vault(
+            &e,
+            &vaults_info.lowest_key,
+            &target_vault_key,
+            &prev_key,
+            target_vault.total_debt.clone(),
+            target_vault.total_collateral.clone(),
+        );
+
+    move_gas_reserve(&e, &prev_owner_key, &updated_target_vault_key);
+
+    vaults_info.lowest_key = updated_lowest_key;
+
+    e.set_vaults_info(&vaults_info);
+
+    e.bump_vault(&updated_target_vault_key);
+    e.bump_vault_index(&updated_target_vault_index_key);
+
+    updated_target_vault_key
+}
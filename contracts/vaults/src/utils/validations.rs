@@ -1,6 +1,6 @@
 use crate::errors::SCErrors;
 use crate::storage::vaults::{OptionalVaultKey, Vault, VaultKey};
-use crate::utils::vaults::validate_prev_keys;
+use crate::utils::vaults::{calculate_deposit_ratio, validate_prev_keys};
 use soroban_sdk::{panic_with_error, Env, Vec};
 
 pub fn assert_regular_vault_updates_validations(
@@ -45,3 +45,27 @@ pub fn assert_col_rate_under_min(
         panic_with_error!(e, SCErrors::CollateralRateUnderMinimum);
     }
 }
+
+// The rule for the vaults updated with a swap (`leverage` and `deleverage`): the vault must end over the opening
+// collateral ratio like after `increase_debt` or `withdraw_collateral`, unless the swap doesn't lower its ratio.
+// This way a vault under the opening ratio can always be deleveraged to make it safer, but it can't be leveraged.
+pub fn assert_swap_col_rate(
+    e: &Env,
+    rate_price: &i128,
+    prev_debt: &u128,
+    prev_collateral: &u128,
+    new_debt: &u128,
+    new_collateral: &u128,
+    opening_col_rate: &u128,
+) {
+    let price: u128 = rate_price.clone() as u128;
+    let new_deposit_rate: u128 = calculate_deposit_ratio(&price, &new_collateral, &new_debt);
+
+    if &new_deposit_rate >= opening_col_rate {
+        return;
+    }
+
+    if new_deposit_rate < calculate_deposit_ratio(&price, &prev_collateral, &prev_debt) {
+        panic_with_error!(e, SCErrors::CollateralRateUnderMinimum);
+    }
+}
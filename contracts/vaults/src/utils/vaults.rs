@@ -1,7 +1,10 @@
 use crate::errors::SCErrors;
+use crate::storage::transfers::TransfersFunc;
 use crate::storage::vaults::{
-    OptionalVaultKey, Vault, VaultIndexKey, VaultKey, VaultsFunc, VaultsInfo,
+    AccountVaults, OptionalVaultKey, Redistribution, Vault, VaultIndexKey, VaultKey, VaultsFunc,
+    VaultsInfo, REDISTRIBUTION_PRECISION,
 };
+use crate::utils::indexes::calculate_user_vault_index;
 use soroban_sdk::{panic_with_error, Address, Env, Symbol, Vec};
 
 // Creates and insert a Vault into the storage while updating the prev vault in case it exists.
@@ -96,16 +99,23 @@ pub fn create_and_insert_vault(
         total_debt: initial_debt,
         total_collateral: collateral_amount,
         index: new_vault_key.index.clone(),
+        id: new_vault_key.id,
     };
     e.set_vault(&new_vault);
     e.set_vault_index(&new_vault_key);
 
+    // The vault values are up to date so it doesn't have anything pending from previous redistributions
+    if let Some(redistribution) = e.redistribution(&new_vault_key.denomination) {
+        e.set_redistribution_snapshot(&new_vault_key, &redistribution);
+    }
+
     (
         new_vault,
         new_vault_key.clone(),
         VaultIndexKey {
             user: new_vault_key.account.clone(),
             denomination: new_vault_key.denomination.clone(),
+            id: new_vault_key.id,
         },
         updated_lowest_key,
     )
@@ -115,10 +125,12 @@ pub fn search_vault(
     e: &Env,
     user: &Address,
     denomination: &Symbol,
+    id: u32,
 ) -> (Vault, VaultKey, VaultIndexKey) {
     let vault_index_key: VaultIndexKey = VaultIndexKey {
         user: user.clone(),
         denomination: denomination.clone(),
+        id,
     };
 
     let vault_index: u128 = e
@@ -129,12 +141,15 @@ pub fn search_vault(
         index: vault_index,
         account: user.clone(),
         denomination: denomination.clone(),
+        id,
     };
 
-    let user_vault: Vault = e
+    let mut user_vault: Vault = e
         .vault(&vault_key)
         .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::VaultDoesntExist));
 
+    apply_redistribution(&e, &mut user_vault);
+
     (user_vault, vault_key, vault_index_key)
 }
 
@@ -162,7 +177,8 @@ pub fn get_vaults(
     }
 
     for _ in 0..total {
-        let vault: Vault = e.vault(&target_key).unwrap();
+        let mut vault: Vault = e.vault(&target_key).unwrap();
+        apply_redistribution(&e, &mut vault);
 
         if !can_be_liquidated(&vault, &vaults_info, &rate) && only_to_liquidate {
             break;
@@ -193,10 +209,14 @@ pub fn withdraw_vault(e: &Env, vault: &Vault, prev_key: &OptionalVaultKey) {
         index: vault.index.clone(),
         account: vault.account.clone(),
         denomination: vault.denomination.clone(),
+        id: vault.id,
     };
 
     if let OptionalVaultKey::Some(key) = prev_key {
-        let (mut prev_vault, _, _) = search_vault(&e, &key.account, &key.denomination);
+        // We only update the next key of the prev vault so we don't apply its pending redistribution
+        let mut prev_vault: Vault = e
+            .vault(&key)
+            .unwrap_or_else(|| panic_with_error!(&e, &SCErrors::VaultDoesntExist));
 
         // We check that the Next Key correctly targets the target Vault
         // If the Next key is None, it means the target Vault is not the Vault that comes after this one
@@ -213,12 +233,42 @@ pub fn withdraw_vault(e: &Env, vault: &Vault, prev_key: &OptionalVaultKey) {
     }
 
     e.remove_vault(&target_vault_key);
+    e.remove_redistribution_snapshot(&target_vault_key);
     e.remove_vault_index(&VaultIndexKey {
         user: vault.account.clone(),
         denomination: vault.denomination.clone(),
+        id: vault.id,
     });
 }
 
+// Reserves the id of a new vault of the account
+pub fn new_vault_id(e: &Env, account: &Address, denomination: &Symbol) -> u32 {
+    let mut account_vaults: AccountVaults = e.account_vaults(&account, &denomination);
+    let id: u32 = account_vaults.next_id;
+
+    account_vaults.next_id = account_vaults.next_id + 1;
+    account_vaults.ids.push_back(id);
+    e.set_account_vaults(&account, &denomination, &account_vaults);
+
+    id
+}
+
+// Removes the id of a vault that was closed, liquidated or moved to another account
+pub fn remove_vault_id(e: &Env, account: &Address, denomination: &Symbol, id: u32) {
+    // A pending transfer offer can't be accepted once the vault is closed or has a new owner
+    e.remove_transfer_offer(&account, &denomination, id);
+
+    let mut account_vaults: AccountVaults = e.account_vaults(&account, &denomination);
+
+    if let Some(position) = account_vaults.ids.first_index_of(id) {
+        account_vaults.ids.remove(position);
+    }
+
+    // A vault from before the ids existed is removed before its account has a list, its id can't be reused either
+    account_vaults.next_id = account_vaults.next_id.max(id + 1);
+    e.set_account_vaults(&account, &denomination, &account_vaults);
+}
+
 pub fn calculate_deposit_ratio(currency_rate: &u128, collateral: &u128, debt: &u128) -> u128 {
     currency_rate * collateral / debt.clone()
 }
@@ -238,3 +288,77 @@ pub fn validate_prev_keys(e: &Env, target_vault_key: &VaultKey, prev_keys: &Vec<
         }
     }
 }
+
+pub fn get_redistribution(e: &Env, denomination: &Symbol) -> Redistribution {
+    e.redistribution(denomination).unwrap_or(Redistribution {
+        debt_factor: REDISTRIBUTION_PRECISION,
+        collateral_factor: 0,
+    })
+}
+
+// Adds to the vault the debt and collateral it received from redistributions since it was saved.
+// The vault is not saved, the caller needs to save it (for example with `create_and_insert_vault`) so the snapshot is updated.
+pub fn apply_redistribution(e: &Env, vault: &mut Vault) {
+    let redistribution: Redistribution = match e.redistribution(&vault.denomination) {
+        None => return,
+        Some(value) => value,
+    };
+
+    let snapshot: Redistribution = e
+        .redistribution_snapshot(&VaultKey {
+            index: vault.index,
+            account: vault.account.clone(),
+            denomination: vault.denomination.clone(),
+            id: vault.id,
+        })
+        .unwrap_or(Redistribution {
+            debt_factor: REDISTRIBUTION_PRECISION,
+            collateral_factor: 0,
+        });
+
+    if snapshot == redistribution {
+        return;
+    }
+
+    let debt: u128 = vault.total_debt;
+    vault.total_debt = debt * redistribution.debt_factor / snapshot.debt_factor;
+    vault.total_collateral += debt
+        * (redistribution.collateral_factor - snapshot.collateral_factor)
+        / snapshot.debt_factor;
+}
+
+// Calculates the index of a vault with its values normalized to the state before any redistribution.
+// Redistributions multiply the debt of every vault by the same factor and add collateral in proportion to the debt,
+// so normalizing the values keeps the order of the saved indexes without touching the vaults.
+// If there wasn't any redistribution, this is the same as `calculate_user_vault_index`.
+pub fn calculate_vault_index(
+    e: &Env,
+    denomination: &Symbol,
+    total_debt: u128,
+    total_collateral: u128,
+) -> u128 {
+    let redistribution: Redistribution = get_redistribution(&e, &denomination);
+    let normalized_debt: u128 = total_debt * REDISTRIBUTION_PRECISION / redistribution.debt_factor;
+    let normalized_collateral: u128 = total_collateral.saturating_sub(
+        normalized_debt * redistribution.collateral_factor / REDISTRIBUTION_PRECISION,
+    );
+
+    calculate_user_vault_index(normalized_debt, normalized_collateral)
+}
+
+// Shares the debt and collateral of the removed vault between all the remaining vaults of the denomination
+// - `remaining_debt` - The total debt of the vaults that will receive the redistribution
+pub fn redistribute_vault(
+    e: &Env,
+    denomination: &Symbol,
+    remaining_debt: u128,
+    debt: u128,
+    collateral: u128,
+) -> Redistribution {
+    let mut redistribution: Redistribution = get_redistribution(&e, &denomination);
+    redistribution.collateral_factor += collateral * redistribution.debt_factor / remaining_debt;
+    redistribution.debt_factor =
+        redistribution.debt_factor * (remaining_debt + debt) / remaining_debt;
+    e.set_redistribution(&denomination, &redistribution);
+    redistribution
+}